@@ -1,3 +1,5 @@
+pub mod romaji;
+
 // c.f. main/src/lib/japanese.ts for japanese char ranges
 // TODO: `is_hiragana`, `is_katakana`, `to_katakana` implementation is different from japanese.ts
 
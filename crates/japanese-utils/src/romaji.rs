@@ -0,0 +1,246 @@
+//! Conversion of romaji to kana.
+//!
+//! Both Hepburn (`shi`, `tsu`, `ja`) and Kunrei (`si`, `tu`, `zya`) spellings are accepted.
+
+/// Converts romaji `text` to hiragana.
+///
+/// - Double consonant (`kk`, `tch`) is converted to sokuon `っ`
+/// - `n` not followed by a vowel or `y`, `n'`, and `m` before `b`, `m` or `p` is converted to `ん`
+/// - Long vowels with macron or circumflex (`ō`, `ô`) are converted to two vowels (`おう`),
+///   and `-` is converted to `ー`
+/// - Spaces are ignored, and letters may be uppercase
+///
+/// Small kana spellings (`xa`, `ltsu`) are not accepted,
+/// as they are rarely typed on purpose and make English words like `hello` convertible.
+///
+/// Returns `None` if `text` is not romaji, i.e. any part of it can't be converted to kana.
+pub fn romaji_to_hiragana(text: &str) -> Option<String> {
+    let mut chars: Vec<char> = vec![];
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        match ch {
+            ' ' => {}
+            'ā' | 'â' => chars.extend(['a', 'a']),
+            'ī' | 'î' => chars.extend(['i', 'i']),
+            'ū' | 'û' => chars.extend(['u', 'u']),
+            'ē' | 'ê' => chars.extend(['e', 'e']),
+            'ō' | 'ô' => chars.extend(['o', 'u']),
+            _ => chars.push(ch),
+        }
+    }
+    if chars.is_empty() {
+        return None;
+    }
+
+    let mut kana = String::with_capacity(chars.len() * 3);
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let next = chars.get(i + 1).copied();
+        if ch == '-' {
+            kana.push('ー');
+            i += 1;
+            continue;
+        }
+        if ch == 'n' && next == Some('\'') {
+            kana.push('ん');
+            i += 2;
+            continue;
+        }
+        if ch == 'n' && !next.is_some_and(|next| is_vowel(next) || next == 'y') {
+            kana.push('ん');
+            i += 1;
+            continue;
+        }
+        if ch == 'm' && matches!(next, Some('b' | 'm' | 'p')) {
+            kana.push('ん');
+            i += 1;
+            continue;
+        }
+        if ch.is_ascii_lowercase() && !is_vowel(ch) && (next == Some(ch) || is_tch(&chars[i..])) {
+            kana.push('っ');
+            i += 1;
+            continue;
+        }
+
+        let (syllable_kana, len) = (1..=4)
+            .rev()
+            .filter(|len| i + len <= chars.len())
+            .find_map(|len| {
+                let syllable: String = chars[i..i + len].iter().collect();
+                syllable_to_hiragana(&syllable).map(|kana| (kana, len))
+            })?;
+        kana.push_str(syllable_kana);
+        i += len;
+    }
+    Some(kana)
+}
+
+fn is_vowel(ch: char) -> bool {
+    matches!(ch, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+/// `tch` is Hepburn spelling of sokuon before `ch`, e.g. `matcha`
+fn is_tch(chars: &[char]) -> bool {
+    chars.starts_with(&['t', 'c', 'h'])
+}
+
+fn syllable_to_hiragana(syllable: &str) -> Option<&'static str> {
+    let kana = match syllable {
+        "a" => "あ",
+        "i" => "い",
+        "u" => "う",
+        "e" => "え",
+        "o" => "お",
+        "ka" => "か",
+        "ki" => "き",
+        "ku" => "く",
+        "ke" => "け",
+        "ko" => "こ",
+        "ga" => "が",
+        "gi" => "ぎ",
+        "gu" => "ぐ",
+        "ge" => "げ",
+        "go" => "ご",
+        "sa" => "さ",
+        "shi" | "si" => "し",
+        "su" => "す",
+        "se" => "せ",
+        "so" => "そ",
+        "za" => "ざ",
+        "ji" | "zi" => "じ",
+        "zu" => "ず",
+        "ze" => "ぜ",
+        "zo" => "ぞ",
+        "ta" => "た",
+        "chi" | "ti" => "ち",
+        "tsu" | "tu" => "つ",
+        "te" => "て",
+        "to" => "と",
+        "da" => "だ",
+        "di" => "ぢ",
+        "du" | "dzu" => "づ",
+        "de" => "で",
+        "do" => "ど",
+        "na" => "な",
+        "ni" => "に",
+        "nu" => "ぬ",
+        "ne" => "ね",
+        "no" => "の",
+        "ha" => "は",
+        "hi" => "ひ",
+        "fu" | "hu" => "ふ",
+        "he" => "へ",
+        "ho" => "ほ",
+        "ba" => "ば",
+        "bi" => "び",
+        "bu" => "ぶ",
+        "be" => "べ",
+        "bo" => "ぼ",
+        "pa" => "ぱ",
+        "pi" => "ぴ",
+        "pu" => "ぷ",
+        "pe" => "ぺ",
+        "po" => "ぽ",
+        "ma" => "ま",
+        "mi" => "み",
+        "mu" => "む",
+        "me" => "め",
+        "mo" => "も",
+        "ya" => "や",
+        "yu" => "ゆ",
+        "yo" => "よ",
+        "ra" => "ら",
+        "ri" => "り",
+        "ru" => "る",
+        "re" => "れ",
+        "ro" => "ろ",
+        "wa" => "わ",
+        "wo" => "を",
+        "kya" => "きゃ",
+        "kyu" => "きゅ",
+        "kyo" => "きょ",
+        "gya" => "ぎゃ",
+        "gyu" => "ぎゅ",
+        "gyo" => "ぎょ",
+        "sha" | "sya" => "しゃ",
+        "shu" | "syu" => "しゅ",
+        "sho" | "syo" => "しょ",
+        "she" => "しぇ",
+        "ja" | "zya" | "jya" => "じゃ",
+        "ju" | "zyu" | "jyu" => "じゅ",
+        "jo" | "zyo" | "jyo" => "じょ",
+        "je" => "じぇ",
+        "cha" | "tya" => "ちゃ",
+        "chu" | "tyu" => "ちゅ",
+        "cho" | "tyo" => "ちょ",
+        "che" => "ちぇ",
+        "dya" => "ぢゃ",
+        "dyu" => "ぢゅ",
+        "dyo" => "ぢょ",
+        "nya" => "にゃ",
+        "nyu" => "にゅ",
+        "nyo" => "にょ",
+        "hya" => "ひゃ",
+        "hyu" => "ひゅ",
+        "hyo" => "ひょ",
+        "bya" => "びゃ",
+        "byu" => "びゅ",
+        "byo" => "びょ",
+        "pya" => "ぴゃ",
+        "pyu" => "ぴゅ",
+        "pyo" => "ぴょ",
+        "mya" => "みゃ",
+        "myu" => "みゅ",
+        "myo" => "みょ",
+        "rya" => "りゃ",
+        "ryu" => "りゅ",
+        "ryo" => "りょ",
+        "fa" => "ふぁ",
+        "fi" => "ふぃ",
+        "fe" => "ふぇ",
+        "fo" => "ふぉ",
+        _ => return None,
+    };
+    Some(kana)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::romaji_to_hiragana;
+
+    macro_rules! romaji_tests {
+        ($($name: ident : $romaji: literal, $kana: expr ;)+) => {
+            $(
+                #[test]
+                fn $name() {
+                    assert_eq!(romaji_to_hiragana($romaji).as_deref(), $kana);
+                }
+            )+
+        }
+    }
+
+    romaji_tests! {
+        basic: "taberu", Some("たべる");
+        hepburn: "shitsumon", Some("しつもん");
+        kunrei: "situmon", Some("しつもん");
+        hepburn_digraph: "benkyou", Some("べんきょう");
+        kunrei_digraph: "zyugyou", Some("じゅぎょう");
+        sokuon: "kitte", Some("きって");
+        sokuon_tch: "matcha", Some("まっちゃ");
+        n_apostrophe: "kin'en", Some("きんえん");
+        n_before_vowel: "kinen", Some("きねん");
+        double_n: "konnichiwa", Some("こんにちわ");
+        m_before_labial: "shimbun", Some("しんぶん");
+        macron: "tōkyō", Some("とうきょう");
+        circumflex: "tôkyô", Some("とうきょう");
+        long_vowel_mark: "ko-hi-", Some("こーひー");
+        uppercase_and_space: "Ohayou Gozaimasu", Some("おはようございます");
+        english_word: "cat", None;
+        trailing_consonant: "dog", None;
+        apostrophe_not_after_n: "ka'", None;
+        small_kana: "xa", None;
+        sokuon_before_small_kana: "hello", None;
+        small_kana_l: "lime", None;
+        empty: "", None;
+    }
+}
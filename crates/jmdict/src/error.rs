@@ -1,3 +1,4 @@
+use std::fmt;
 use std::io;
 use std::str::Utf8Error;
 
@@ -15,6 +16,8 @@ pub enum Error {
     Utf8Error(#[from] Utf8Error),
     #[error("Xml parse error: {0}")]
     QuickXml(#[from] quick_xml::Error),
+    #[error("Json error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("Invalid xml: {0}")]
     InvalidXml(String),
     #[error("Attribute error {0}")]
@@ -27,6 +30,58 @@ pub enum Error {
     MultipleEntryIds(u32),
     #[error("Entry has no id, at pos: {0}")]
     NoEntryId(u64),
+    #[error("Entity &{entity}; of entry {ent_seq} is not declared in DOCTYPE")]
+    UndeclaredEntity { entity: String, ent_seq: u32 },
+    #[error("Invalid line {line}: {message}")]
+    InvalidLine { line: usize, message: String },
+    #[error("{source}, at {position}")]
+    AtPosition {
+        position: ErrorPosition,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    pub(crate) fn at(self, position: ErrorPosition) -> Self {
+        Self::AtPosition {
+            position,
+            source: Box::new(self),
+        }
+    }
+
+    /// Position in xml where the error occurred, if known
+    pub fn position(&self) -> Option<&ErrorPosition> {
+        match self {
+            Self::AtPosition { position, .. } => Some(position),
+            _ => None,
+        }
+    }
+}
+
+/// Position in xml file that was being parsed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorPosition {
+    /// Byte offset from the start of the xml
+    pub byte_offset: u64,
+    /// Line number, starting from 1
+    pub line: u64,
+    /// `<ent_seq>` of the entry being parsed, if it was already parsed
+    pub ent_seq: Option<u32>,
+    /// Path of elements from `<entry>` to the element being parsed, e.g. `entry/sense/gloss`
+    pub path: String,
+}
+
+impl fmt::Display for ErrorPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} (byte {})", self.line, self.byte_offset)?;
+        if !self.path.is_empty() {
+            write!(f, " in <{}>", self.path)?;
+        }
+        if let Some(ent_seq) = self.ent_seq {
+            write!(f, " of entry {}", ent_seq)?;
+        }
+        Ok(())
+    }
 }
 
 impl From<String> for Error {
@@ -1,5 +1,7 @@
 mod parse;
 mod types;
+mod write;
 
-pub use parse::{parse_jmdict_xml, JMDictParser};
+pub use parse::{parse_jmdict_xml, JMDictParser, JMDictPatchParser};
 pub use types::*;
+pub use write::{write_jmdict_xml, JMDictXmlWriter};
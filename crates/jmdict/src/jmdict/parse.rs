@@ -1,30 +1,46 @@
 use core::str;
+use std::collections::BTreeMap;
 use std::io::BufRead;
 
-use log::warn;
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
 use super::types::{
-    JMDialect, JMDict, JMEntry, JMKanji, JMKanjiInfo, JMPartOfSpeech, JMReading, JMSense,
-    JMSenseMisc,
+    JMDialect, JMDict, JMEntry, JMExample, JMExampleSentence, JMGloss, JMGlossType, JMKanji,
+    JMKanjiInfo, JMLanguageSource, JMPartOfSpeech, JMPatch, JMPriority, JMReading, JMReference,
+    JMSense, JMSenseField, JMSenseMisc,
 };
 use crate::jmdict::types::JMReadingInfo;
-use crate::utils::parse_entity_enum_into;
-use crate::xml::{get_next_child_in, parse_string_in_tag_into, TagName, DATE_REG};
+use crate::utils::{parse_entity_enum_into, push_warning};
+use crate::xml::{
+    error_position, get_next_child_in, parse_entity_declarations, parse_string_in_tag_into,
+    parse_string_with_child_in_tag_into, ElementPath, LineCounter, TagName, DATE_REG,
+};
 use crate::{Error, Result};
 
 pub const JMDICT_META_ENTRY_ID: u32 = 9999999;
 
+const JMDICT_ROOT: &str = "JMdict";
+const JMDICT_PATCH_ROOT: &str = "JMdict_patch";
+
 pub struct JMDictParser<R: BufRead> {
-    reader: Reader<R>,
+    reader: Reader<LineCounter<R>>,
     buf: Vec<u8>,
     creation_date: Option<String>,
+    entities: BTreeMap<String, String>,
+    warnings: Vec<String>,
+    /// `<ent_seq>` of the entry being parsed
+    ent_seq: Option<u32>,
+    path: ElementPath,
 }
 
 impl<R: BufRead> JMDictParser<R> {
     pub fn new(reader: R) -> Result<Self> {
-        let mut reader = Reader::from_reader(reader);
+        Self::with_root(reader, JMDICT_ROOT)
+    }
+
+    fn with_root(reader: R, root: &str) -> Result<Self> {
+        let mut reader = Reader::from_reader(LineCounter::new(reader));
         reader.config_mut().trim_text_start = true;
         reader.config_mut().trim_text_end = true;
         reader.config_mut().expand_empty_elements = true;
@@ -34,30 +50,56 @@ impl<R: BufRead> JMDictParser<R> {
             reader,
             buf,
             creation_date: None,
+            entities: BTreeMap::new(),
+            warnings: vec![],
+            ent_seq: None,
+            path: ElementPath::default(),
         };
-        parser.parse_jmdict_start()?;
+        parser
+            .parse_jmdict_start(root)
+            .map_err(|e| parser.error_at(e))?;
         Ok(parser)
     }
 
     pub fn next_entry(&mut self) -> Result<Option<JMEntry>> {
-        self.parse_entry_in_jmdict()
+        self.path.clear();
+        self.parse_entry_in_jmdict().map_err(|e| self.error_at(e))
+    }
+
+    fn error_at(&self, error: Error) -> Error {
+        error.at(error_position(&self.reader, self.ent_seq, &self.path))
+    }
+
+    /// Warnings for unknown or unexpected data that was skipped or kept as-is while parsing
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
     }
 
     pub fn creation_date(&self) -> Option<&str> {
         self.creation_date.as_deref()
     }
 
-    fn parse_jmdict_start(&mut self) -> Result<()> {
+    /// Entity declarations in DOCTYPE, from entity name to its description.
+    ///
+    /// Available once parser is created, as DOCTYPE precedes the root element.
+    pub fn entities(&self) -> &BTreeMap<String, String> {
+        &self.entities
+    }
+
+    fn parse_jmdict_start(&mut self, root: &str) -> Result<()> {
         loop {
             self.buf.clear();
             match self.reader.read_event_into(&mut self.buf)? {
-                Event::Start(tag) => match tag.name().0 {
-                    b"JMdict" => return Ok(()),
-                    _ => {
-                        warn!("Unknown global tag: {}", tag.tag_name());
+                Event::Start(tag) => {
+                    if tag.name().0 == root.as_bytes() {
+                        return Ok(());
                     }
-                },
-                Event::Eof => return Err(Error::InvalidXml("<JMdict> not found".into())),
+                    push_warning!(self.warnings, "Unknown global tag: {}", tag.tag_name());
+                }
+                Event::DocType(doctype) => {
+                    self.entities = parse_entity_declarations(&doctype);
+                }
+                Event::Eof => return Err(Error::InvalidXml(format!("<{}> not found", root))),
                 _ => {}
             }
         }
@@ -70,9 +112,18 @@ impl<R: BufRead> JMDictParser<R> {
                 None => return Ok(None),
             };
             match start.name().0 {
-                b"entry" => return self.parse_in_entry().map(Some),
+                b"entry" => {
+                    self.path.enter(start.name().0);
+                    let entry = self.parse_in_entry()?;
+                    self.path.leave();
+                    return Ok(Some(entry));
+                }
                 _ => {
-                    warn!("Unknown tag in <jmdict>: <{}>", start.tag_name());
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <jmdict>: <{}>",
+                        start.tag_name()
+                    );
                 }
             }
         }
@@ -81,8 +132,10 @@ impl<R: BufRead> JMDictParser<R> {
     fn parse_in_entry(&mut self) -> Result<JMEntry> {
         let mut id: Option<u32> = None;
         let mut entry = JMEntry::default();
+        self.ent_seq = None;
 
         while let Some(start) = get_next_child_in(&mut self.reader, &mut self.buf, "entry")? {
+            self.path.enter(start.name().0);
             match start.name().0 {
                 b"ent_seq" => {
                     if let Some(id) = id {
@@ -93,6 +146,7 @@ impl<R: BufRead> JMDictParser<R> {
                     let seq_id = str::parse::<u32>(&idstr)
                         .map_err(|_| format!("Couldn't parse as u32 number: {}", idstr))?;
                     id = Some(seq_id);
+                    self.ent_seq = Some(seq_id);
                 }
                 b"k_ele" => {
                     let form = self.parse_in_form()?;
@@ -107,9 +161,14 @@ impl<R: BufRead> JMDictParser<R> {
                     entry.senses.push(sense);
                 }
                 _ => {
-                    warn!("Unknown tag in <entry>: <{}>", start.tag_name())
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <entry>: <{}>",
+                        start.tag_name()
+                    )
                 }
             };
+            self.path.leave();
         }
 
         self.buf.clear();
@@ -126,14 +185,88 @@ impl<R: BufRead> JMDictParser<R> {
         }
     }
 
+    fn parse_patch_in_root(&mut self) -> Result<Option<JMPatch>> {
+        loop {
+            let start = match get_next_child_in(&mut self.reader, &mut self.buf, JMDICT_PATCH_ROOT)?
+            {
+                Some(tag) => tag,
+                None => return Ok(None),
+            };
+            self.path.enter(start.name().0);
+            let patch = match start.name().0 {
+                b"add" => JMPatch::Add(self.parse_entry_in_patch("add")?),
+                b"modify" => JMPatch::Modify(self.parse_entry_in_patch("modify")?),
+                b"delete" => JMPatch::Delete(self.parse_ent_seq_in_delete()?),
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <{}>: <{}>",
+                        JMDICT_PATCH_ROOT,
+                        start.tag_name()
+                    );
+                    self.path.leave();
+                    continue;
+                }
+            };
+            self.path.leave();
+            return Ok(Some(patch));
+        }
+    }
+
+    /// Parses the single `<entry>` in `<add>` or `<modify>`
+    fn parse_entry_in_patch(&mut self, op: &str) -> Result<JMEntry> {
+        let mut entry = None;
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, op)? {
+            if tag.name().0 != b"entry" {
+                return Err(Error::Unexpected {
+                    expected: "<entry>".into(),
+                    actual: format!("starting tag <{}>", tag.tag_name()),
+                });
+            }
+            if entry.is_some() {
+                return Err(Error::InvalidXml(format!("Multiple <entry> in <{}>", op)));
+            }
+            self.path.enter(b"entry");
+            entry = Some(self.parse_in_entry()?);
+            self.path.leave();
+        }
+        entry.ok_or_else(|| Error::InvalidXml(format!("No <entry> in <{}>", op)))
+    }
+
+    fn parse_ent_seq_in_delete(&mut self) -> Result<u32> {
+        let mut id = None;
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "delete")? {
+            if tag.name().0 != b"ent_seq" {
+                return Err(Error::Unexpected {
+                    expected: "<ent_seq>".into(),
+                    actual: format!("starting tag <{}>", tag.tag_name()),
+                });
+            }
+            if let Some(id) = id {
+                return Err(Error::MultipleEntryIds(id));
+            }
+            let idstr = parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ent_seq")?;
+            let seq_id = str::parse::<u32>(&idstr)
+                .map_err(|_| format!("Couldn't parse as u32 number: {}", idstr))?;
+            id = Some(seq_id);
+            self.ent_seq = Some(seq_id);
+        }
+        id.ok_or(Error::NoEntryId(self.reader.buffer_position()))
+    }
+
     fn parse_in_form(&mut self) -> Result<JMKanji> {
         let mut form = JMKanji::default();
 
         while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "k_ele")? {
+            self.path.enter(tag.name().0);
             match tag.name().0 {
                 b"keb" => {
                     if !form.kanji.is_empty() {
-                        warn!("Found multiple <keb> in form '{}' in JMDict", form.kanji)
+                        push_warning!(
+                            self.warnings,
+                            "Found multiple <keb> in form '{}' in JMDict",
+                            form.kanji
+                        )
                     }
                     form.kanji = parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"keb")?;
                 }
@@ -143,20 +276,23 @@ impl<R: BufRead> JMDictParser<R> {
                         &mut self.reader,
                         &mut self.buf,
                         "ke_inf",
-                        form.info
+                        form.info,
+                        self.warnings
                     );
                 }
                 b"ke_pri" => {
-                    form.priority.push(parse_string_in_tag_into(
-                        &mut self.reader,
-                        &mut self.buf,
-                        b"ke_pri",
-                    )?);
+                    let text =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ke_pri")?;
+                    match JMPriority::parse(&text) {
+                        Some(priority) => form.priority.push(priority),
+                        None => push_warning!(self.warnings, "Unknown <ke_pri>: {}", text),
+                    }
                 }
                 _ => {
-                    warn!("Unknown tag in <entry>: {}", tag.tag_name());
+                    push_warning!(self.warnings, "Unknown tag in <entry>: {}", tag.tag_name());
                 }
             };
+            self.path.leave();
         }
 
         Ok(form)
@@ -166,6 +302,7 @@ impl<R: BufRead> JMDictParser<R> {
         let mut reading = JMReading::default();
 
         while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "r_ele")? {
+            self.path.enter(tag.name().0);
             match tag.name().0 {
                 b"reb" => {
                     reading.reading =
@@ -189,20 +326,23 @@ impl<R: BufRead> JMDictParser<R> {
                         &mut self.reader,
                         &mut self.buf,
                         "re_inf",
-                        reading.info
+                        reading.info,
+                        self.warnings
                     );
                 }
                 b"re_pri" => {
-                    reading.priority.push(parse_string_in_tag_into(
-                        &mut self.reader,
-                        &mut self.buf,
-                        b"re_pri",
-                    )?);
+                    let text =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"re_pri")?;
+                    match JMPriority::parse(&text) {
+                        Some(priority) => reading.priority.push(priority),
+                        None => push_warning!(self.warnings, "Unknown <re_pri>: {}", text),
+                    }
                 }
                 _ => {
-                    warn!("Unknown tag in <r_ele>: {}", &tag.tag_name());
+                    push_warning!(self.warnings, "Unknown tag in <r_ele>: {}", &tag.tag_name());
                 }
             };
+            self.path.leave();
         }
 
         Ok(reading)
@@ -212,6 +352,7 @@ impl<R: BufRead> JMDictParser<R> {
         let mut sense = JMSense::default();
 
         while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "sense")? {
+            self.path.enter(tag.name().0);
             match tag.name().0 {
                 b"stagk" => {
                     sense.to_form.push(parse_string_in_tag_into(
@@ -233,17 +374,27 @@ impl<R: BufRead> JMDictParser<R> {
                         &mut self.reader,
                         &mut self.buf,
                         "pos",
-                        sense.pos
+                        sense.pos,
+                        self.warnings
                     );
                 }
                 b"xref" => {
-                    parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"xref")?;
+                    let text = parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"xref")?;
+                    sense.references.push(JMReference::parse(&text));
                 }
                 b"ant" => {
-                    parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ant")?;
+                    let text = parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ant")?;
+                    sense.antonyms.push(JMReference::parse(&text));
                 }
                 b"field" => {
-                    parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"field")?;
+                    parse_entity_enum_into!(
+                        JMSenseField,
+                        &mut self.reader,
+                        &mut self.buf,
+                        "field",
+                        sense.fields,
+                        self.warnings
+                    );
                 }
                 b"misc" => {
                     parse_entity_enum_into!(
@@ -251,7 +402,8 @@ impl<R: BufRead> JMDictParser<R> {
                         &mut self.reader,
                         &mut self.buf,
                         "misc",
-                        sense.misc
+                        sense.misc,
+                        self.warnings
                     );
                 }
                 b"s_inf" => {
@@ -267,37 +419,137 @@ impl<R: BufRead> JMDictParser<R> {
                         &mut self.reader,
                         &mut self.buf,
                         "dial",
-                        sense.dialects
+                        sense.dialects,
+                        self.warnings
                     );
                 }
                 b"gloss" => {
-                    sense.meanings.push(parse_string_in_tag_into(
+                    let mut gloss = JMGloss {
+                        lang: "eng".into(),
+                        ..JMGloss::default()
+                    };
+                    for attr in tag.attributes() {
+                        let attr = attr?;
+                        match attr.key.0 {
+                            b"xml:lang" => gloss.lang = str::from_utf8(&attr.value)?.to_owned(),
+                            b"g_type" => {
+                                gloss.gloss_type = JMGlossType::parse_attr(&attr.value);
+                                if gloss.gloss_type.is_none() {
+                                    push_warning!(
+                                        self.warnings,
+                                        "Unknown g_type: {}",
+                                        String::from_utf8_lossy(&attr.value)
+                                    );
+                                }
+                            }
+                            b"g_gend" => {
+                                gloss.gender = Some(str::from_utf8(&attr.value)?.to_owned())
+                            }
+                            _ => {}
+                        }
+                    }
+                    let (text, highlighted) = parse_string_with_child_in_tag_into(
                         &mut self.reader,
                         &mut self.buf,
                         b"gloss",
-                    )?);
+                        b"pri",
+                    )?;
+                    gloss.text = text;
+                    gloss.highlighted = highlighted;
+                    sense.meanings.push(gloss);
                 }
                 b"lsource" => {
-                    // consume ending tag
-                    parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"lsource")?;
+                    let mut source = JMLanguageSource {
+                        lang: "eng".into(),
+                        ..JMLanguageSource::default()
+                    };
+                    for attr in tag.attributes() {
+                        let attr = attr?;
+                        match attr.key.0 {
+                            b"xml:lang" => source.lang = str::from_utf8(&attr.value)?.to_owned(),
+                            b"ls_type" => source.partial = attr.value.as_ref() == b"part",
+                            b"ls_wasei" => source.wasei = attr.value.as_ref() == b"y",
+                            _ => {
+                                push_warning!(
+                                    self.warnings,
+                                    "Unknown attribute in <lsource>: {}",
+                                    String::from_utf8_lossy(attr.key.0)
+                                );
+                            }
+                        }
+                    }
+                    source.text =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"lsource")?;
+                    sense.language_sources.push(source);
                 }
                 b"example" => {
-                    parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"example")?;
+                    let example = self.parse_in_example()?;
+                    sense.examples.push(example);
                 }
                 _ => {
-                    warn!("Unknown tag in <sense>: {}", tag.tag_name());
+                    push_warning!(self.warnings, "Unknown tag in <sense>: {}", tag.tag_name());
                 }
             };
+            self.path.leave();
         }
 
         Ok(sense)
     }
 
+    fn parse_in_example(&mut self) -> Result<JMExample> {
+        let mut example = JMExample::default();
+
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "example")? {
+            self.path.enter(tag.name().0);
+            match tag.name().0 {
+                b"ex_srce" => {
+                    for attr in tag.attributes() {
+                        let attr = attr?;
+                        if attr.key.0 == b"exsrc_type" {
+                            example.source_type = str::from_utf8(&attr.value)?.to_owned();
+                        }
+                    }
+                    example.source_id =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ex_srce")?;
+                }
+                b"ex_text" => {
+                    example.text =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ex_text")?;
+                }
+                b"ex_sent" => {
+                    let mut sentence = JMExampleSentence {
+                        lang: "eng".into(),
+                        ..JMExampleSentence::default()
+                    };
+                    for attr in tag.attributes() {
+                        let attr = attr?;
+                        if attr.key.0 == b"xml:lang" {
+                            sentence.lang = str::from_utf8(&attr.value)?.to_owned();
+                        }
+                    }
+                    sentence.text =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ex_sent")?;
+                    example.sentences.push(sentence);
+                }
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <example>: {}",
+                        tag.tag_name()
+                    );
+                }
+            };
+            self.path.leave();
+        }
+
+        Ok(example)
+    }
+
     fn parse_creation_date(&mut self, entry: &JMEntry) -> Result<()> {
         debug_assert_eq!(entry.id, JMDICT_META_ENTRY_ID);
         for sense in &entry.senses {
             for meaning in &sense.meanings {
-                if let Some(date) = DATE_REG.find(meaning) {
+                if let Some(date) = DATE_REG.find(&meaning.text) {
                     self.creation_date = Some(date.as_str().to_owned())
                 }
             }
@@ -306,6 +558,41 @@ impl<R: BufRead> JMDictParser<R> {
     }
 }
 
+/// Parses JMdict patch file, one operation at a time.
+///
+/// ```xml
+/// <JMdict_patch>
+/// <add><entry>...</entry></add>
+/// <modify><entry>...</entry></modify>
+/// <delete><ent_seq>1000000</ent_seq></delete>
+/// </JMdict_patch>
+/// ```
+///
+/// `<entry>` is in the same format as in JMdict.
+/// DOCTYPE with entity declarations may be included, but is not required.
+pub struct JMDictPatchParser<R: BufRead> {
+    parser: JMDictParser<R>,
+}
+
+impl<R: BufRead> JMDictPatchParser<R> {
+    pub fn new(reader: R) -> Result<Self> {
+        let parser = JMDictParser::with_root(reader, JMDICT_PATCH_ROOT)?;
+        Ok(JMDictPatchParser { parser })
+    }
+
+    pub fn next_patch(&mut self) -> Result<Option<JMPatch>> {
+        let parser = &mut self.parser;
+        parser.path.clear();
+        parser.ent_seq = None;
+        parser.parse_patch_in_root().map_err(|e| parser.error_at(e))
+    }
+
+    /// Warnings for unknown or unexpected data that was skipped or kept as-is while parsing
+    pub fn warnings(&self) -> &[String] {
+        self.parser.warnings()
+    }
+}
+
 pub fn parse_jmdict_xml<R: BufRead>(reader: R) -> Result<JMDict> {
     let mut parser = JMDictParser::new(reader)?;
     let mut entries = vec![];
@@ -313,8 +600,12 @@ pub fn parse_jmdict_xml<R: BufRead>(reader: R) -> Result<JMDict> {
         entries.push(entry);
     }
     let creation_date = parser.creation_date().map(|d| d.to_owned());
+    let entities = parser.entities().clone();
+    let warnings = parser.warnings().to_vec();
     Ok(JMDict {
         entries,
         creation_date,
+        entities,
+        warnings,
     })
 }
@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
 use crate::utils::jm_entity_enum;
@@ -6,6 +9,9 @@ use crate::utils::jm_entity_enum;
 pub struct JMDict {
     pub entries: Vec<JMEntry>,
     pub creation_date: Option<String>,
+    /// Entity declarations in DOCTYPE, from entity name to its description
+    pub entities: BTreeMap<String, String>,
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -20,30 +26,120 @@ pub struct JMEntry {
 }
 
 impl JMEntry {
+    /// Priority score of entry, calculated from the first reading
     pub fn priority(&self) -> u16 {
-        let priorities = &self.readings.first().unwrap().priority;
-        let mut priority: u16 = 0;
-
-        for p in priorities {
-            // common ~20k entries
-            if ["news1", "ichi1", "spec1", "gai1"].contains(&p.as_str()) {
-                if priority < 100 {
-                    priority += 100
-                } else {
-                    priority += 25
+        self.readings
+            .first()
+            .map_or(0, |reading| JMPriority::score(&reading.priority))
+    }
+}
+
+/// Operation in JMdict patch file
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JMPatch {
+    /// `<add>`. Adds a new entry
+    Add(JMEntry),
+    /// `<modify>`. Replaces the entry with the same id
+    Modify(JMEntry),
+    /// `<delete>`. Removes the entry with the id
+    Delete(u32),
+}
+
+/// `<ke_pri>` and `<re_pri>`
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ::schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum JMPriority {
+    /// Mainichi Shimbun word frequency list, first 12,000 words
+    News1,
+    /// Mainichi Shimbun word frequency list, second 12,000 words
+    News2,
+    /// 'Ichimango goi bunruishuu'
+    Ichi1,
+    /// 'Ichimango goi bunruishuu', demoted due to low frequency in other sources
+    Ichi2,
+    /// Common words not included in other lists
+    Spec1,
+    Spec2,
+    /// Common loanwords, from wordfreq file
+    Gai1,
+    Gai2,
+    /// Rank of word in sets of 500 words in wordfreq file, 1-48
+    Nf(u8),
+}
+
+impl JMPriority {
+    /// Returns `None` if `text` is not a known priority
+    pub fn parse(text: &str) -> Option<JMPriority> {
+        use JMPriority::*;
+
+        match text {
+            "news1" => Some(News1),
+            "news2" => Some(News2),
+            "ichi1" => Some(Ichi1),
+            "ichi2" => Some(Ichi2),
+            "spec1" => Some(Spec1),
+            "spec2" => Some(Spec2),
+            "gai1" => Some(Gai1),
+            "gai2" => Some(Gai2),
+            _ => {
+                let rank = text.strip_prefix("nf")?.parse::<u8>().ok()?;
+                (1..=48).contains(&rank).then_some(Nf(rank))
+            }
+        }
+    }
+
+    /// Text of priority in xml, e.g. "news1" or "nf01"
+    pub fn to_text(&self) -> String {
+        use JMPriority::*;
+
+        match self {
+            News1 => "news1".into(),
+            News2 => "news2".into(),
+            Ichi1 => "ichi1".into(),
+            Ichi2 => "ichi2".into(),
+            Spec1 => "spec1".into(),
+            Spec2 => "spec2".into(),
+            Gai1 => "gai1".into(),
+            Gai2 => "gai2".into(),
+            Nf(rank) => format!("nf{:02}", rank),
+        }
+    }
+
+    /// Combined priority score of a form. Higher is more common.
+    pub fn score(priorities: &[JMPriority]) -> u16 {
+        use JMPriority::*;
+
+        let mut score: u16 = 0;
+        for priority in priorities {
+            match priority {
+                // common ~20k entries
+                News1 | Ichi1 | Spec1 | Gai1 => {
+                    if score < 100 {
+                        score += 100
+                    } else {
+                        score += 25
+                    }
                 }
-            // common ~30k entries
-            } else if ["news2", "ichi2", "spec2", "gai2"].contains(&p.as_str()) {
-                priority += 5
-            // 01 ~ 48, each with ~500 entries
-            } else if let Some(stripped) = p.strip_prefix("nf") {
-                let freq = stripped
-                    .parse::<u16>()
-                    .expect("could not parse XX as number where priority nfXX");
-                priority += 50 - freq;
+                // common ~30k entries
+                News2 | Ichi2 | Spec2 | Gai2 => score += 5,
+                // 01 ~ 48, each with ~500 entries
+                Nf(rank) => score += 50 - u16::from(*rank),
             }
         }
-        priority
+        score
     }
 }
 
@@ -53,7 +149,7 @@ pub struct JMKanji {
     /// 0+ ke_inf
     pub info: Vec<JMKanjiInfo>,
     /// 0+ ke_pri
-    pub priority: Vec<String>,
+    pub priority: Vec<JMPriority>,
 }
 
 impl JMKanji {
@@ -75,7 +171,7 @@ pub struct JMReading {
     /// 0+ re_inf
     pub info: Vec<JMReadingInfo>,
     /// 0+ re_pri
-    pub priority: Vec<String>,
+    pub priority: Vec<JMPriority>,
 }
 
 impl JMReading {
@@ -95,21 +191,174 @@ pub struct JMSense {
     /// 'pos'
     pub pos: Vec<JMPartOfSpeech>,
     /// 'xref'
-    // pub reference: Vec<String>,
+    pub references: Vec<JMReference>,
     /// 'ant'
-    // pub antonym: Vec<String>,
+    pub antonyms: Vec<JMReference>,
     /// 'field'
-    // pub field: Vec<String>,
+    pub fields: Vec<JMSenseField>,
     /// 'misc'
     pub misc: Vec<JMSenseMisc>,
     /// 's_inf'
     pub info: Vec<String>,
+    /// 'lsource'
+    pub language_sources: Vec<JMLanguageSource>,
     /// 'dial'
     pub dialects: Vec<JMDialect>,
     /// 'gloss'
-    pub meanings: Vec<String>,
-    // 'example'
-    // example: Vec<Example>,
+    pub meanings: Vec<JMGloss>,
+    /// 'example'. Only exists in JMdict file with examples (e.g. JMdict_e_examp)
+    pub examples: Vec<JMExample>,
+}
+
+/// Reference to another entry in `<xref>` or `<ant>`
+///
+/// Text is in the form of `term・reading・sense`,
+/// where `reading` and `sense` are optional.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JMReference {
+    /// Kanji or reading of referenced entry
+    pub term: String,
+    /// Reading of referenced entry, if `term` is kanji
+    pub reading: Option<String>,
+    /// 1-based sense number within referenced entry
+    pub sense: Option<u16>,
+}
+
+impl JMReference {
+    pub fn parse(text: &str) -> Self {
+        let mut parts: Vec<&str> = text.split('・').collect();
+        let sense = if parts.len() > 1 {
+            let last = parts[parts.len() - 1];
+            let sense = last.parse::<u16>().ok();
+            if sense.is_some() {
+                parts.pop();
+            }
+            sense
+        } else {
+            None
+        };
+        let (term, reading) = match parts.as_slice() {
+            [term, reading] => (term.to_string(), Some(reading.to_string())),
+            // A term that itself contains '・'
+            _ => (parts.join("・"), None),
+        };
+        Self {
+            term,
+            reading,
+            sense,
+        }
+    }
+}
+
+impl fmt::Display for JMReference {
+    /// Formats reference as in xml, the inverse of [`JMReference::parse`]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.term)?;
+        if let Some(reading) = &self.reading {
+            write!(f, "・{}", reading)?;
+        }
+        if let Some(sense) = &self.sense {
+            write!(f, "・{}", sense)?;
+        }
+        Ok(())
+    }
+}
+
+/// `<example>`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JMExample {
+    /// 'exsrc_type' attribute of `<ex_srce>`. "tat" for Tatoeba
+    pub source_type: String,
+    /// `<ex_srce>`. Id of sentence in source, e.g. Tatoeba sentence id
+    pub source_id: String,
+    /// `<ex_text>`. Form of the term as used in sentence
+    pub text: String,
+    /// `<ex_sent>`. Japanese sentence and its translations
+    pub sentences: Vec<JMExampleSentence>,
+}
+
+/// `<ex_sent>`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JMExampleSentence {
+    /// ISO 639-2 language code of sentence. "jpn" for the Japanese sentence
+    pub lang: String,
+    pub text: String,
+}
+
+/// `<gloss>`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JMGloss {
+    pub text: String,
+    /// ISO 639-2 language code of gloss. Defaults to "eng".
+    pub lang: String,
+    /// 'g_type'
+    pub gloss_type: Option<JMGlossType>,
+    /// 'g_gend'. Gender of gloss word, e.g. "masc", "fem", "neut"
+    pub gender: Option<String>,
+    /// Gloss contains a highlighted `<pri>` element
+    pub highlighted: bool,
+}
+
+/// 'g_type' attribute of `<gloss>`
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ::schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum JMGlossType {
+    /// Literal translation
+    Literal,
+    /// Figurative meaning
+    Figurative,
+    /// Explanation, not a translation
+    Explanation,
+    /// Trademark
+    Trademark,
+}
+
+impl JMGlossType {
+    pub fn parse_attr(value: &[u8]) -> Option<JMGlossType> {
+        match value {
+            b"lit" => Some(Self::Literal),
+            b"fig" => Some(Self::Figurative),
+            b"expl" => Some(Self::Explanation),
+            b"tm" => Some(Self::Trademark),
+            _ => None,
+        }
+    }
+
+    /// Value of 'g_type' attribute, the inverse of [`JMGlossType::parse_attr`]
+    pub fn attr_value(&self) -> &'static str {
+        match self {
+            Self::Literal => "lit",
+            Self::Figurative => "fig",
+            Self::Explanation => "expl",
+            Self::Trademark => "tm",
+        }
+    }
+}
+
+/// `<lsource>`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, ::schemars::JsonSchema)]
+pub struct JMLanguageSource {
+    /// ISO 639-2 language code of source language. Defaults to "eng".
+    pub lang: String,
+    /// Word or phrase in source language. May be empty.
+    pub text: String,
+    /// Source language word only describes part of the sense
+    pub partial: bool,
+    /// Term is constructed from source language words,
+    /// but is not used in source language. (e.g. wasei-eigo)
+    pub wasei: bool,
 }
 
 jm_entity_enum!(
@@ -128,6 +377,202 @@ jm_entity_enum!(
     b"tsug" => TsugaruBen,
 );
 
+jm_entity_enum!(
+    JMSenseField;
+    #[doc="agriculture"]
+    b"agric" => Agriculture,
+    #[doc="anatomy"]
+    b"anat" => Anatomy,
+    #[doc="archeology"]
+    b"archeol" => Archeology,
+    #[doc="architecture"]
+    b"archit" => Architecture,
+    #[doc="art, aesthetics"]
+    b"art" => Art,
+    #[doc="astronomy"]
+    b"astron" => Astronomy,
+    #[doc="audiovisual"]
+    b"audvid" => Audiovisual,
+    #[doc="aviation"]
+    b"aviat" => Aviation,
+    #[doc="baseball"]
+    b"baseb" => Baseball,
+    #[doc="biochemistry"]
+    b"biochem" => Biochemistry,
+    #[doc="biology"]
+    b"biol" => Biology,
+    #[doc="botany"]
+    b"bot" => Botany,
+    #[doc="boxing"]
+    b"boxing" => Boxing,
+    #[doc="Buddhism"]
+    b"Buddh" => Buddhism,
+    #[doc="business"]
+    b"bus" => Business,
+    #[doc="card games"]
+    b"cards" => CardGames,
+    #[doc="chemistry"]
+    b"chem" => Chemistry,
+    #[doc="Chinese mythology"]
+    b"chmyth" => ChineseMythology,
+    #[doc="Christianity"]
+    b"Christn" => Christianity,
+    #[doc="civil engineering"]
+    b"civeng" => CivilEngineering,
+    #[doc="clothing"]
+    b"cloth" => Clothing,
+    #[doc="computing"]
+    b"comp" => Computing,
+    #[doc="crystallography"]
+    b"cryst" => Crystallography,
+    #[doc="dentistry"]
+    b"dent" => Dentistry,
+    #[doc="ecology"]
+    b"ecol" => Ecology,
+    #[doc="economics"]
+    b"econ" => Economics,
+    #[doc="electricity, elec. eng."]
+    b"elec" => Electricity,
+    #[doc="electronics"]
+    b"electr" => Electronics,
+    #[doc="embryology"]
+    b"embryo" => Embryology,
+    #[doc="engineering"]
+    b"engr" => Engineering,
+    #[doc="entomology"]
+    b"ent" => Entomology,
+    #[doc="figure skating"]
+    b"figskt" => FigureSkating,
+    #[doc="film"]
+    b"film" => Film,
+    #[doc="finance"]
+    b"finc" => Finance,
+    #[doc="fishing"]
+    b"fish" => Fishing,
+    #[doc="food, cooking"]
+    b"food" => Food,
+    #[doc="gardening, horticulture"]
+    b"gardn" => Gardening,
+    #[doc="genetics"]
+    b"genet" => Genetics,
+    #[doc="geography"]
+    b"geogr" => Geography,
+    #[doc="geology"]
+    b"geol" => Geology,
+    #[doc="geometry"]
+    b"geom" => Geometry,
+    #[doc="go (game)"]
+    b"go" => Go,
+    #[doc="golf"]
+    b"golf" => Golf,
+    #[doc="grammar"]
+    b"gramm" => Grammar,
+    #[doc="Greek mythology"]
+    b"grmyth" => GreekMythology,
+    #[doc="hanafuda"]
+    b"hanaf" => Hanafuda,
+    #[doc="horse racing"]
+    b"horse" => HorseRacing,
+    #[doc="Internet"]
+    b"internet" => Internet,
+    #[doc="Japanese mythology"]
+    b"jpmyth" => JapaneseMythology,
+    #[doc="kabuki"]
+    b"kabuki" => Kabuki,
+    #[doc="law"]
+    b"law" => Law,
+    #[doc="linguistics"]
+    b"ling" => Linguistics,
+    #[doc="logic"]
+    b"logic" => Logic,
+    #[doc="martial arts"]
+    b"MA" => MartialArts,
+    #[doc="mahjong"]
+    b"mahj" => Mahjong,
+    #[doc="manga"]
+    b"manga" => Manga,
+    #[doc="mathematics"]
+    b"math" => Mathematics,
+    #[doc="mechanical engineering"]
+    b"mech" => MechanicalEngineering,
+    #[doc="medicine"]
+    b"med" => Medicine,
+    #[doc="meteorology"]
+    b"met" => Meteorology,
+    #[doc="military"]
+    b"mil" => Military,
+    #[doc="mineralogy"]
+    b"min" => Mineralogy,
+    #[doc="mining"]
+    b"mining" => Mining,
+    #[doc="motorsport"]
+    b"motor" => Motorsport,
+    #[doc="music"]
+    b"music" => Music,
+    #[doc="noh"]
+    b"noh" => Noh,
+    #[doc="ornithology"]
+    b"ornith" => Ornithology,
+    #[doc="paleontology"]
+    b"paleo" => Paleontology,
+    #[doc="pathology"]
+    b"pathol" => Pathology,
+    #[doc="pharmacology"]
+    b"pharm" => Pharmacology,
+    #[doc="philosophy"]
+    b"phil" => Philosophy,
+    #[doc="photography"]
+    b"photo" => Photography,
+    #[doc="physics"]
+    b"physics" => Physics,
+    #[doc="physiology"]
+    b"physiol" => Physiology,
+    #[doc="politics"]
+    b"politics" => Politics,
+    #[doc="printing"]
+    b"print" => Printing,
+    #[doc="professional wrestling"]
+    b"prowres" => ProfessionalWrestling,
+    #[doc="psychiatry"]
+    b"psy" => Psychiatry,
+    #[doc="psychoanalysis"]
+    b"psyanal" => Psychoanalysis,
+    #[doc="psychology"]
+    b"psych" => Psychology,
+    #[doc="railway"]
+    b"rail" => Railway,
+    #[doc="Roman mythology"]
+    b"rommyth" => RomanMythology,
+    #[doc="Shinto"]
+    b"Shinto" => Shinto,
+    #[doc="shogi"]
+    b"shogi" => Shogi,
+    #[doc="skiing"]
+    b"ski" => Skiing,
+    #[doc="sports"]
+    b"sports" => Sports,
+    #[doc="statistics"]
+    b"stat" => Statistics,
+    #[doc="stock market"]
+    b"stockm" => StockMarket,
+    #[doc="sumo"]
+    b"sumo" => Sumo,
+    #[doc="surgery"]
+    b"surg" => Surgery,
+    #[doc="telecommunications"]
+    b"telec" => Telecommunications,
+    #[doc="trademark"]
+    b"tradem" => Trademark,
+    #[doc="television"]
+    b"tv" => Television,
+    #[doc="veterinary terms"]
+    b"vet" => Veterinary,
+    #[doc="video games"]
+    b"vidg" => VideoGames,
+    #[doc="zoology"]
+    b"zool" => Zoology,
+);
+
 jm_entity_enum!(
     JMKanjiInfo;
     b"ateji" => AtejiReading,
@@ -174,7 +619,8 @@ jm_entity_enum!(
     b"obs" => Obsolete,
     b"on-mim" => Onomatopoeic,
     b"organization" => Organization,
-    b"oth" => Other,
+    #[serde(rename = "other")]
+    b"oth" => OtherName,
     b"person" => Person,
     b"place" => Place,
     b"poet" => Poetical,
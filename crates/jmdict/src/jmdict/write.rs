@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use quick_xml::escape::partial_escape;
+
+use super::types::{JMDict, JMEntry, JMExample, JMGloss, JMKanji, JMReading, JMSense};
+use crate::xml::{
+    check_entities_declared, with_undeclared_entities, write_entity_element, write_start_tag,
+    write_text_element, write_text_element_with_attributes, write_xml_start,
+};
+use crate::Result;
+
+/// Writes JMdict xml, one entry at a time.
+///
+/// Entity values are written with their original entity names,
+/// so the written xml is parsed into the same entries.
+/// Position of `<pri>` within a highlighted gloss is not kept,
+/// and the whole gloss text is written as highlighted.
+pub struct JMDictXmlWriter<W: Write> {
+    writer: W,
+    /// Entities declared in DOCTYPE
+    entities: BTreeMap<String, String>,
+}
+
+impl<W: Write> JMDictXmlWriter<W> {
+    /// `entities` are declared in DOCTYPE, as in [`JMDictParser::entities`].
+    ///
+    /// [`JMDictParser::entities`]: crate::JMDictParser::entities
+    pub fn new(
+        mut writer: W,
+        entities: &BTreeMap<String, String>,
+        creation_date: Option<&str>,
+    ) -> Result<Self> {
+        write_xml_start(&mut writer, "JMdict", entities)?;
+        if let Some(date) = creation_date {
+            writeln!(writer, "<!-- JMdict created: {} -->", date)?;
+        }
+        writeln!(writer, "<JMdict>")?;
+        Ok(JMDictXmlWriter {
+            writer,
+            entities: entities.clone(),
+        })
+    }
+
+    /// Returns error if entry has an unknown entity that is not declared in DOCTYPE
+    pub fn write_entry(&mut self, entry: &JMEntry) -> Result<()> {
+        check_entities_declared(&self.entities, unknown_entities(entry), entry.id)?;
+        let w = &mut self.writer;
+        writeln!(w, "<entry>")?;
+        write_text_element(w, "ent_seq", &entry.id.to_string())?;
+        for kanji in &entry.kanjis {
+            write_kanji(w, kanji)?;
+        }
+        for reading in &entry.readings {
+            write_reading(w, reading)?;
+        }
+        for sense in &entry.senses {
+            write_sense(w, sense)?;
+        }
+        writeln!(w, "</entry>")?;
+        Ok(())
+    }
+
+    /// Closes root element and returns the inner writer
+    pub fn finish(mut self) -> Result<W> {
+        writeln!(self.writer, "</JMdict>")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Names of unknown entities in `entry`
+fn unknown_entities(entry: &JMEntry) -> Vec<&str> {
+    let mut names = vec![];
+    for kanji in &entry.kanjis {
+        names.extend(kanji.info.iter().filter_map(|i| i.unknown_entity_name()));
+    }
+    for reading in &entry.readings {
+        names.extend(reading.info.iter().filter_map(|i| i.unknown_entity_name()));
+    }
+    for sense in &entry.senses {
+        names.extend(sense.pos.iter().filter_map(|p| p.unknown_entity_name()));
+        names.extend(sense.fields.iter().filter_map(|f| f.unknown_entity_name()));
+        names.extend(sense.misc.iter().filter_map(|m| m.unknown_entity_name()));
+        names.extend(
+            sense
+                .dialects
+                .iter()
+                .filter_map(|d| d.unknown_entity_name()),
+        );
+    }
+    names
+}
+
+fn write_kanji<W: Write>(w: &mut W, kanji: &JMKanji) -> Result<()> {
+    writeln!(w, "<k_ele>")?;
+    write_text_element(w, "keb", &kanji.kanji)?;
+    for info in &kanji.info {
+        write_entity_element(w, "ke_inf", info.entity_name())?;
+    }
+    for priority in &kanji.priority {
+        write_text_element(w, "ke_pri", &priority.to_text())?;
+    }
+    writeln!(w, "</k_ele>")?;
+    Ok(())
+}
+
+fn write_reading<W: Write>(w: &mut W, reading: &JMReading) -> Result<()> {
+    writeln!(w, "<r_ele>")?;
+    write_text_element(w, "reb", &reading.reading)?;
+    if reading.nokanji {
+        writeln!(w, "<re_nokanji/>")?;
+    }
+    for form in &reading.to_form {
+        write_text_element(w, "re_restr", form)?;
+    }
+    for info in &reading.info {
+        write_entity_element(w, "re_inf", info.entity_name())?;
+    }
+    for priority in &reading.priority {
+        write_text_element(w, "re_pri", &priority.to_text())?;
+    }
+    writeln!(w, "</r_ele>")?;
+    Ok(())
+}
+
+fn write_sense<W: Write>(w: &mut W, sense: &JMSense) -> Result<()> {
+    writeln!(w, "<sense>")?;
+    for form in &sense.to_form {
+        write_text_element(w, "stagk", form)?;
+    }
+    for reading in &sense.to_reading {
+        write_text_element(w, "stagr", reading)?;
+    }
+    for pos in &sense.pos {
+        write_entity_element(w, "pos", pos.entity_name())?;
+    }
+    for reference in &sense.references {
+        write_text_element(w, "xref", &reference.to_string())?;
+    }
+    for antonym in &sense.antonyms {
+        write_text_element(w, "ant", &antonym.to_string())?;
+    }
+    for field in &sense.fields {
+        write_entity_element(w, "field", field.entity_name())?;
+    }
+    for misc in &sense.misc {
+        write_entity_element(w, "misc", misc.entity_name())?;
+    }
+    for info in &sense.info {
+        write_text_element(w, "s_inf", info)?;
+    }
+    for source in &sense.language_sources {
+        let mut attributes = vec![];
+        if source.lang != "eng" {
+            attributes.push(("xml:lang", source.lang.as_str()));
+        }
+        if source.partial {
+            attributes.push(("ls_type", "part"));
+        }
+        if source.wasei {
+            attributes.push(("ls_wasei", "y"));
+        }
+        write_text_element_with_attributes(w, "lsource", &attributes, &source.text)?;
+    }
+    for dialect in &sense.dialects {
+        write_entity_element(w, "dial", dialect.entity_name())?;
+    }
+    for gloss in &sense.meanings {
+        write_gloss(w, gloss)?;
+    }
+    for example in &sense.examples {
+        write_example(w, example)?;
+    }
+    writeln!(w, "</sense>")?;
+    Ok(())
+}
+
+fn write_gloss<W: Write>(w: &mut W, gloss: &JMGloss) -> Result<()> {
+    let mut attributes = vec![];
+    if gloss.lang != "eng" {
+        attributes.push(("xml:lang", gloss.lang.as_str()));
+    }
+    if let Some(gloss_type) = &gloss.gloss_type {
+        attributes.push(("g_type", gloss_type.attr_value()));
+    }
+    if let Some(gender) = &gloss.gender {
+        attributes.push(("g_gend", gender.as_str()));
+    }
+    if gloss.highlighted {
+        write_start_tag(w, "gloss", &attributes)?;
+        write!(w, "<pri>{}</pri>", partial_escape(&gloss.text))?;
+        writeln!(w, "</gloss>")?;
+        Ok(())
+    } else {
+        write_text_element_with_attributes(w, "gloss", &attributes, &gloss.text)
+    }
+}
+
+fn write_example<W: Write>(w: &mut W, example: &JMExample) -> Result<()> {
+    writeln!(w, "<example>")?;
+    write_text_element_with_attributes(
+        w,
+        "ex_srce",
+        &[("exsrc_type", &example.source_type)],
+        &example.source_id,
+    )?;
+    write_text_element(w, "ex_text", &example.text)?;
+    for sentence in &example.sentences {
+        write_text_element_with_attributes(
+            w,
+            "ex_sent",
+            &[("xml:lang", &sentence.lang)],
+            &sentence.text,
+        )?;
+    }
+    writeln!(w, "</example>")?;
+    Ok(())
+}
+
+/// Writes `jmdict` as JMdict xml.
+///
+/// Unknown entities that are not in `jmdict.entities` are declared with their name as description.
+pub fn write_jmdict_xml<W: Write>(jmdict: &JMDict, writer: W) -> Result<W> {
+    let entities = with_undeclared_entities(
+        &jmdict.entities,
+        jmdict.entries.iter().flat_map(unknown_entities),
+    );
+    let mut writer = JMDictXmlWriter::new(writer, &entities, jmdict.creation_date.as_deref())?;
+    for entry in &jmdict.entries {
+        writer.write_entry(entry)?;
+    }
+    writer.finish()
+}
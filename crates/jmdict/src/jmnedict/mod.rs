@@ -1,5 +1,7 @@
 mod parse;
 mod types;
+mod write;
 
 pub use parse::{parse_jmnedict_xml, JMneDictParser};
 pub use types::{JMneDict, JMneEntry, JMneKanji, JMneNameType, JMneReading, JMneTranslation};
+pub use write::{write_jmnedict_xml, JMneDictXmlWriter};
@@ -1,13 +1,17 @@
 use core::str;
+use std::collections::BTreeMap;
 use std::io::BufRead;
 
-use log::warn;
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
+use crate::jmdict::{JMKanjiInfo, JMPriority, JMReadingInfo};
 use crate::jmnedict::types::JMneNameType;
-use crate::utils::parse_entity_enum_into;
-use crate::xml::{get_next_child_in, parse_string_in_tag_into, TagName, DATE_REG};
+use crate::utils::{parse_entity_enum_into, push_warning};
+use crate::xml::{
+    error_position, get_next_child_in, parse_entity_declarations, parse_string_in_tag_into,
+    ElementPath, LineCounter, TagName, DATE_REG,
+};
 use crate::{Error, Result};
 
 use super::types::{JMneDict, JMneEntry, JMneKanji, JMneReading, JMneTranslation};
@@ -15,14 +19,19 @@ use super::types::{JMneDict, JMneEntry, JMneKanji, JMneReading, JMneTranslation}
 pub const JMNEDICT_META_ENTRY_ID: u32 = 9999990;
 
 pub struct JMneDictParser<R: BufRead> {
-    reader: Reader<R>,
+    reader: Reader<LineCounter<R>>,
     buf: Vec<u8>,
     creation_date: Option<String>,
+    entities: BTreeMap<String, String>,
+    warnings: Vec<String>,
+    /// `<ent_seq>` of the entry being parsed
+    ent_seq: Option<u32>,
+    path: ElementPath,
 }
 
 impl<R: BufRead> JMneDictParser<R> {
     pub fn new(reader: R) -> Result<Self> {
-        let mut reader = Reader::from_reader(reader);
+        let mut reader = Reader::from_reader(LineCounter::new(reader));
         reader.config_mut().trim_text_start = true;
         reader.config_mut().trim_text_end = true;
         reader.config_mut().expand_empty_elements = true;
@@ -32,19 +41,42 @@ impl<R: BufRead> JMneDictParser<R> {
             reader,
             buf,
             creation_date: None,
+            entities: BTreeMap::new(),
+            warnings: vec![],
+            ent_seq: None,
+            path: ElementPath::default(),
         };
-        parser.parse_jmnedict_start()?;
+        parser
+            .parse_jmnedict_start()
+            .map_err(|e| parser.error_at(e))?;
         Ok(parser)
     }
 
     pub fn next_entry(&mut self) -> Result<Option<JMneEntry>> {
-        self.parse_entry_in_jmnedict()
+        self.path.clear();
+        self.parse_entry_in_jmnedict().map_err(|e| self.error_at(e))
+    }
+
+    fn error_at(&self, error: Error) -> Error {
+        error.at(error_position(&self.reader, self.ent_seq, &self.path))
+    }
+
+    /// Warnings for unknown or unexpected data that was skipped or kept as-is while parsing
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
     }
 
     pub fn creation_date(&self) -> Option<&str> {
         self.creation_date.as_deref()
     }
 
+    /// Entity declarations in DOCTYPE, from entity name to its description.
+    ///
+    /// Available once parser is created, as DOCTYPE precedes the root element.
+    pub fn entities(&self) -> &BTreeMap<String, String> {
+        &self.entities
+    }
+
     fn parse_jmnedict_start(&mut self) -> Result<()> {
         loop {
             self.buf.clear();
@@ -54,9 +86,12 @@ impl<R: BufRead> JMneDictParser<R> {
                         return Ok(());
                     }
                     _ => {
-                        warn!("Unknown global tag: {}", tag.tag_name());
+                        push_warning!(self.warnings, "Unknown global tag: {}", tag.tag_name());
                     }
                 },
+                Event::DocType(doctype) => {
+                    self.entities = parse_entity_declarations(&doctype);
+                }
                 Event::Eof => return Err(Error::InvalidXml("<JMnedict> not found".into())),
                 _ => {}
             }
@@ -70,9 +105,18 @@ impl<R: BufRead> JMneDictParser<R> {
                 None => return Ok(None),
             };
             match start.name().0 {
-                b"entry" => return self.parse_in_entry().map(Some),
+                b"entry" => {
+                    self.path.enter(start.name().0);
+                    let entry = self.parse_in_entry()?;
+                    self.path.leave();
+                    return Ok(Some(entry));
+                }
                 _ => {
-                    warn!("Unknown tag in <jmdict>: <{}>", start.tag_name());
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <jmdict>: <{}>",
+                        start.tag_name()
+                    );
                 }
             }
         }
@@ -81,8 +125,10 @@ impl<R: BufRead> JMneDictParser<R> {
     fn parse_in_entry(&mut self) -> Result<JMneEntry> {
         let mut entry = JMneEntry::default();
         let mut id: Option<u32> = None;
+        self.ent_seq = None;
 
         while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "entry")? {
+            self.path.enter(tag.name().0);
             match tag.name().0 {
                 b"ent_seq" => {
                     if let Some(id) = id {
@@ -93,6 +139,7 @@ impl<R: BufRead> JMneDictParser<R> {
                     let seq_id = str::parse::<u32>(&idstr)
                         .map_err(|_| format!("Couldn't parse as u32 number: {}", idstr))?;
                     id = Some(seq_id);
+                    self.ent_seq = Some(seq_id);
                 }
                 b"k_ele" => {
                     entry.kanjis.push(self.parse_in_kanji()?);
@@ -104,9 +151,14 @@ impl<R: BufRead> JMneDictParser<R> {
                     entry.translations.push(self.parse_in_translation()?);
                 }
                 _ => {
-                    warn!("Unknown tag in <entry>: <{}>", &tag.tag_name());
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <entry>: <{}>",
+                        &tag.tag_name()
+                    );
                 }
             };
+            self.path.leave();
         }
 
         self.buf.clear();
@@ -124,13 +176,16 @@ impl<R: BufRead> JMneDictParser<R> {
 
     fn parse_in_kanji(&mut self) -> Result<JMneKanji> {
         let mut kanji: Option<String> = None;
+        let mut infos = vec![];
         let mut priorities = vec![];
 
         while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "k_ele")? {
+            self.path.enter(tag.name().0);
             match tag.name().0 {
                 b"keb" => {
                     if let Some(kanji) = kanji.as_ref() {
-                        warn!(
+                        push_warning!(
+                            self.warnings,
                             "Warning: Found multiple <keb> in form '{}' in JMneDict",
                             kanji
                         )
@@ -141,20 +196,35 @@ impl<R: BufRead> JMneDictParser<R> {
                         b"keb",
                     )?);
                 }
+                b"ke_inf" => {
+                    parse_entity_enum_into!(
+                        JMKanjiInfo,
+                        &mut self.reader,
+                        &mut self.buf,
+                        "ke_inf",
+                        infos,
+                        self.warnings
+                    );
+                }
                 b"ke_pri" => {
-                    let priority =
+                    let text =
                         parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"ke_pri")?;
-                    priorities.push(priority);
+                    match JMPriority::parse(&text) {
+                        Some(priority) => priorities.push(priority),
+                        None => push_warning!(self.warnings, "Unknown <ke_pri>: {}", text),
+                    }
                 }
                 _ => {
-                    warn!("Unknown tag in <k_ele>: {}", tag.tag_name());
+                    push_warning!(self.warnings, "Unknown tag in <k_ele>: {}", tag.tag_name());
                 }
             };
+            self.path.leave();
         }
 
         let kanji = kanji.ok_or(Error::InvalidXml("No <keb> found in <k_ele>".into()))?;
         let kanji = JMneKanji {
             kanji,
+            info: infos,
             priority: priorities,
         };
 
@@ -164,9 +234,11 @@ impl<R: BufRead> JMneDictParser<R> {
     fn parse_in_reading(&mut self) -> Result<JMneReading> {
         let mut reading: Option<String> = None;
         let mut to_kanjis = vec![];
+        let mut infos = vec![];
         let mut priorities = vec![];
 
         while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "r_ele")? {
+            self.path.enter(tag.name().0);
             match tag.name().0 {
                 b"reb" => {
                     reading = Some(parse_string_in_tag_into(
@@ -180,21 +252,36 @@ impl<R: BufRead> JMneDictParser<R> {
                         parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"re_restr")?;
                     to_kanjis.push(to_form);
                 }
+                b"re_inf" => {
+                    parse_entity_enum_into!(
+                        JMReadingInfo,
+                        &mut self.reader,
+                        &mut self.buf,
+                        "re_inf",
+                        infos,
+                        self.warnings
+                    );
+                }
                 b"re_pri" => {
-                    let priority =
+                    let text =
                         parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"re_pri")?;
-                    priorities.push(priority);
+                    match JMPriority::parse(&text) {
+                        Some(priority) => priorities.push(priority),
+                        None => push_warning!(self.warnings, "Unknown <re_pri>: {}", text),
+                    }
                 }
                 _ => {
-                    warn!("Unknown tag in <r_ele>: {}", &tag.tag_name());
+                    push_warning!(self.warnings, "Unknown tag in <r_ele>: {}", &tag.tag_name());
                 }
             };
+            self.path.leave();
         }
 
         let reading = reading.ok_or(Error::InvalidXml("No <reb> found in <r_ele>".into()))?;
         let reading = JMneReading {
             reading,
             to_kanji: to_kanjis,
+            info: infos,
             priority: priorities,
         };
 
@@ -205,6 +292,7 @@ impl<R: BufRead> JMneDictParser<R> {
         let mut translation = JMneTranslation::default();
 
         while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "trans")? {
+            self.path.enter(tag.name().0);
             match tag.name().0 {
                 b"name_type" => {
                     parse_entity_enum_into!(
@@ -212,7 +300,8 @@ impl<R: BufRead> JMneDictParser<R> {
                         &mut self.reader,
                         &mut self.buf,
                         "name_type",
-                        translation.name_type
+                        translation.name_type,
+                        self.warnings
                     );
                 }
                 b"xref" => {
@@ -229,7 +318,8 @@ impl<R: BufRead> JMneDictParser<R> {
                         if attr.key.0 == b"xml:lang" {
                             let lang = attr.value;
                             if lang.as_ref() != b"eng" {
-                                println!(
+                                push_warning!(
+                                    self.warnings,
                                     "<trans_det> has non-english value: {}",
                                     str::from_utf8(&lang)?
                                 );
@@ -248,9 +338,10 @@ impl<R: BufRead> JMneDictParser<R> {
                     }
                 }
                 _ => {
-                    warn!("Unknown tag in <trans>: {}", &tag.tag_name());
+                    push_warning!(self.warnings, "Unknown tag in <trans>: {}", &tag.tag_name());
                 }
             };
+            self.path.leave();
         }
 
         Ok(translation)
@@ -276,8 +367,12 @@ pub fn parse_jmnedict_xml<R: BufRead>(reader: R) -> Result<JMneDict> {
         entries.push(entry);
     }
     let creation_date = parser.creation_date().map(|d| d.to_owned());
+    let entities = parser.entities().clone();
+    let warnings = parser.warnings().to_vec();
     Ok(JMneDict {
         entries,
         creation_date,
+        entities,
+        warnings,
     })
 }
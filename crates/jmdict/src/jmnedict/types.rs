@@ -1,12 +1,17 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::jmdict::JMSenseMisc;
+use crate::jmdict::{JMKanjiInfo, JMPriority, JMReadingInfo, JMSenseMisc};
 use crate::utils::jm_entity_enum;
 
 #[derive(Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct JMneDict {
     pub entries: Vec<JMneEntry>,
     pub creation_date: Option<String>,
+    /// Entity declarations in DOCTYPE, from entity name to its description
+    pub entities: BTreeMap<String, String>,
+    pub warnings: Vec<String>,
 }
 
 /// `<entry>`
@@ -25,9 +30,10 @@ pub struct JMneEntry {
 #[derive(Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct JMneKanji {
     pub kanji: String,
-    // Defined, but not used.
-    // pub info: Vec<String>,
-    pub priority: Vec<String>,
+    /// 0+ ke_inf
+    pub info: Vec<JMKanjiInfo>,
+    /// 0+ ke_pri
+    pub priority: Vec<JMPriority>,
 }
 
 /// `<r_ele>`
@@ -35,9 +41,10 @@ pub struct JMneKanji {
 pub struct JMneReading {
     pub reading: String,
     pub to_kanji: Vec<String>,
-    // Defined, but not used.
-    // pub info: Vec<String>,
-    pub priority: Vec<String>,
+    /// 0+ re_inf
+    pub info: Vec<JMReadingInfo>,
+    /// 0+ re_pri
+    pub priority: Vec<JMPriority>,
 }
 
 /// `<trans>`
@@ -90,7 +97,8 @@ jm_entity_enum!(
     #[doc="organization name"]
     b"organization" => Organization,
     #[doc="other"]
-    b"oth" => Other,
+    #[serde(rename = "other")]
+    b"oth" => OtherName,
     #[doc="full name of a particular person"]
     b"person" => Person,
     #[doc="place name"]
@@ -113,6 +121,15 @@ jm_entity_enum!(
     b"work" => Artwork,
 );
 
+impl JMneEntry {
+    /// Priority score of entry, calculated from the first reading
+    pub fn priority(&self) -> u16 {
+        self.readings
+            .first()
+            .map_or(0, |reading| JMPriority::score(&reading.priority))
+    }
+}
+
 impl JMneReading {
     /// Returns true if reading is applicable to kanji
     ///
@@ -141,7 +158,8 @@ impl From<JMneNameType> for JMSenseMisc {
             JMneNameType::Mythology => JMSenseMisc::Mythology,
             JMneNameType::Object => JMSenseMisc::Object,
             JMneNameType::Organization => JMSenseMisc::Organization,
-            JMneNameType::Other => JMSenseMisc::Other,
+            JMneNameType::OtherName => JMSenseMisc::OtherName,
+            JMneNameType::Other(entity) => JMSenseMisc::Other(entity),
             JMneNameType::Person => JMSenseMisc::Person,
             JMneNameType::Place => JMSenseMisc::Place,
             JMneNameType::Product => JMSenseMisc::Product,
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use super::types::{JMneDict, JMneEntry, JMneKanji, JMneReading, JMneTranslation};
+use crate::xml::{
+    check_entities_declared, with_undeclared_entities, write_entity_element, write_text_element,
+    write_text_element_with_attributes, write_xml_start,
+};
+use crate::Result;
+
+/// Writes JMnedict xml, one entry at a time.
+///
+/// Entity values are written with their original entity names,
+/// so the written xml is parsed into the same entries.
+pub struct JMneDictXmlWriter<W: Write> {
+    writer: W,
+    /// Entities declared in DOCTYPE
+    entities: BTreeMap<String, String>,
+}
+
+impl<W: Write> JMneDictXmlWriter<W> {
+    /// `entities` are declared in DOCTYPE, as in [`JMneDictParser::entities`].
+    ///
+    /// [`JMneDictParser::entities`]: crate::JMneDictParser::entities
+    pub fn new(
+        mut writer: W,
+        entities: &BTreeMap<String, String>,
+        creation_date: Option<&str>,
+    ) -> Result<Self> {
+        write_xml_start(&mut writer, "JMnedict", entities)?;
+        if let Some(date) = creation_date {
+            writeln!(writer, "<!-- JMnedict created: {} -->", date)?;
+        }
+        writeln!(writer, "<JMnedict>")?;
+        Ok(JMneDictXmlWriter {
+            writer,
+            entities: entities.clone(),
+        })
+    }
+
+    /// Returns error if entry has an unknown entity that is not declared in DOCTYPE
+    pub fn write_entry(&mut self, entry: &JMneEntry) -> Result<()> {
+        check_entities_declared(&self.entities, unknown_entities(entry), entry.id)?;
+        let w = &mut self.writer;
+        writeln!(w, "<entry>")?;
+        write_text_element(w, "ent_seq", &entry.id.to_string())?;
+        for kanji in &entry.kanjis {
+            write_kanji(w, kanji)?;
+        }
+        for reading in &entry.readings {
+            write_reading(w, reading)?;
+        }
+        for translation in &entry.translations {
+            write_translation(w, translation)?;
+        }
+        writeln!(w, "</entry>")?;
+        Ok(())
+    }
+
+    /// Closes root element and returns the inner writer
+    pub fn finish(mut self) -> Result<W> {
+        writeln!(self.writer, "</JMnedict>")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Names of unknown entities in `entry`
+fn unknown_entities(entry: &JMneEntry) -> Vec<&str> {
+    let mut names = vec![];
+    for kanji in &entry.kanjis {
+        names.extend(kanji.info.iter().filter_map(|i| i.unknown_entity_name()));
+    }
+    for reading in &entry.readings {
+        names.extend(reading.info.iter().filter_map(|i| i.unknown_entity_name()));
+    }
+    for translation in &entry.translations {
+        names.extend(
+            translation
+                .name_type
+                .iter()
+                .filter_map(|t| t.unknown_entity_name()),
+        );
+    }
+    names
+}
+
+fn write_kanji<W: Write>(w: &mut W, kanji: &JMneKanji) -> Result<()> {
+    writeln!(w, "<k_ele>")?;
+    write_text_element(w, "keb", &kanji.kanji)?;
+    for info in &kanji.info {
+        write_entity_element(w, "ke_inf", info.entity_name())?;
+    }
+    for priority in &kanji.priority {
+        write_text_element(w, "ke_pri", &priority.to_text())?;
+    }
+    writeln!(w, "</k_ele>")?;
+    Ok(())
+}
+
+fn write_reading<W: Write>(w: &mut W, reading: &JMneReading) -> Result<()> {
+    writeln!(w, "<r_ele>")?;
+    write_text_element(w, "reb", &reading.reading)?;
+    for kanji in &reading.to_kanji {
+        write_text_element(w, "re_restr", kanji)?;
+    }
+    for info in &reading.info {
+        write_entity_element(w, "re_inf", info.entity_name())?;
+    }
+    for priority in &reading.priority {
+        write_text_element(w, "re_pri", &priority.to_text())?;
+    }
+    writeln!(w, "</r_ele>")?;
+    Ok(())
+}
+
+fn write_translation<W: Write>(w: &mut W, translation: &JMneTranslation) -> Result<()> {
+    writeln!(w, "<trans>")?;
+    for name_type in &translation.name_type {
+        write_entity_element(w, "name_type", name_type.entity_name())?;
+    }
+    for xref in &translation.xref {
+        write_text_element(w, "xref", xref)?;
+    }
+    // only English translations are parsed
+    for text in &translation.translations {
+        write_text_element_with_attributes(w, "trans_det", &[("xml:lang", "eng")], text)?;
+    }
+    writeln!(w, "</trans>")?;
+    Ok(())
+}
+
+/// Writes `jmnedict` as JMnedict xml.
+///
+/// Unknown entities that are not in `jmnedict.entities` are declared with their name as description.
+pub fn write_jmnedict_xml<W: Write>(jmnedict: &JMneDict, writer: W) -> Result<W> {
+    let entities = with_undeclared_entities(
+        &jmnedict.entities,
+        jmnedict.entries.iter().flat_map(unknown_entities),
+    );
+    let mut writer = JMneDictXmlWriter::new(writer, &entities, jmnedict.creation_date.as_deref())?;
+    for entry in &jmnedict.entries {
+        writer.write_entry(entry)?;
+    }
+    writer.finish()
+}
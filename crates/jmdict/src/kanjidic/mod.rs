@@ -0,0 +1,5 @@
+mod parse;
+mod types;
+
+pub use parse::{parse_kanjidic_xml, KanjidicParser};
+pub use types::{Kanjidic, KanjidicCharacter};
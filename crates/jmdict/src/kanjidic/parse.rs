@@ -0,0 +1,318 @@
+use core::str;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::utils::push_warning;
+use crate::xml::{get_next_child_in, parse_string_in_tag_into, skip_tag_into, TagName};
+use crate::{Error, Result};
+
+use super::types::{Kanjidic, KanjidicCharacter};
+
+pub struct KanjidicParser<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    creation_date: Option<String>,
+    warnings: Vec<String>,
+}
+
+impl<R: BufRead> KanjidicParser<R> {
+    pub fn new(reader: R) -> Result<Self> {
+        let mut reader = Reader::from_reader(reader);
+        reader.config_mut().trim_text_start = true;
+        reader.config_mut().trim_text_end = true;
+        reader.config_mut().expand_empty_elements = true;
+
+        let buf = Vec::new();
+        let mut parser = KanjidicParser {
+            reader,
+            buf,
+            creation_date: None,
+            warnings: vec![],
+        };
+        parser.parse_kanjidic_start()?;
+        Ok(parser)
+    }
+
+    pub fn next_character(&mut self) -> Result<Option<KanjidicCharacter>> {
+        self.parse_character_in_kanjidic()
+    }
+
+    /// Warnings for unknown or unexpected data that was skipped or kept as-is while parsing
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Available once `<header>` is parsed, which precedes all `<character>`
+    pub fn creation_date(&self) -> Option<&str> {
+        self.creation_date.as_deref()
+    }
+
+    fn parse_kanjidic_start(&mut self) -> Result<()> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::Start(tag) => match tag.name().0 {
+                    b"kanjidic2" => {
+                        return Ok(());
+                    }
+                    _ => {
+                        push_warning!(self.warnings, "Unknown global tag: {}", tag.tag_name());
+                    }
+                },
+                Event::Eof => return Err(Error::InvalidXml("<kanjidic2> not found".into())),
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_character_in_kanjidic(&mut self) -> Result<Option<KanjidicCharacter>> {
+        loop {
+            let start = match get_next_child_in(&mut self.reader, &mut self.buf, "kanjidic2")? {
+                Some(tag) => tag,
+                None => return Ok(None),
+            };
+            match start.name().0 {
+                b"character" => return self.parse_in_character().map(Some),
+                b"header" => self.parse_in_header()?,
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <kanjidic2>: <{}>",
+                        start.tag_name()
+                    );
+                }
+            }
+        }
+    }
+
+    fn parse_in_header(&mut self) -> Result<()> {
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "header")? {
+            match tag.name().0 {
+                b"date_of_creation" => {
+                    let date = parse_string_in_tag_into(
+                        &mut self.reader,
+                        &mut self.buf,
+                        b"date_of_creation",
+                    )?;
+                    self.creation_date = Some(date);
+                }
+                b"file_version" => {
+                    skip_tag_into(&mut self.reader, &mut self.buf, b"file_version")?;
+                }
+                b"database_version" => {
+                    skip_tag_into(&mut self.reader, &mut self.buf, b"database_version")?;
+                }
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <header>: <{}>",
+                        tag.tag_name()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_in_character(&mut self) -> Result<KanjidicCharacter> {
+        let mut character = KanjidicCharacter::default();
+
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "character")? {
+            match tag.name().0 {
+                b"literal" => {
+                    character.literal =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"literal")?;
+                }
+                b"radical" => self.parse_in_radical(&mut character)?,
+                b"misc" => self.parse_in_misc(&mut character)?,
+                b"reading_meaning" => self.parse_in_reading_meaning(&mut character)?,
+                b"codepoint" => {
+                    skip_tag_into(&mut self.reader, &mut self.buf, b"codepoint")?;
+                }
+                b"dic_number" => {
+                    skip_tag_into(&mut self.reader, &mut self.buf, b"dic_number")?;
+                }
+                b"query_code" => {
+                    skip_tag_into(&mut self.reader, &mut self.buf, b"query_code")?;
+                }
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <character>: <{}>",
+                        tag.tag_name()
+                    );
+                }
+            }
+        }
+
+        if character.literal.is_empty() {
+            return Err(Error::InvalidXml(format!(
+                "No <literal> found in <character>, at pos: {}",
+                self.reader.buffer_position()
+            )));
+        }
+        Ok(character)
+    }
+
+    fn parse_in_radical(&mut self, character: &mut KanjidicCharacter) -> Result<()> {
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "radical")? {
+            match tag.name().0 {
+                b"rad_value" => {
+                    let mut is_classical = false;
+                    for attr in tag.attributes() {
+                        let attr = attr?;
+                        if attr.key.0 == b"rad_type" {
+                            is_classical = attr.value.as_ref() == b"classical";
+                        }
+                    }
+                    let value: u8 = self.parse_number_in_tag(b"rad_value")?;
+                    if is_classical {
+                        character.radical = Some(value);
+                    }
+                }
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <radical>: <{}>",
+                        tag.tag_name()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_in_misc(&mut self, character: &mut KanjidicCharacter) -> Result<()> {
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "misc")? {
+            match tag.name().0 {
+                b"grade" => {
+                    character.grade = Some(self.parse_number_in_tag(b"grade")?);
+                }
+                b"stroke_count" => {
+                    let count = self.parse_number_in_tag(b"stroke_count")?;
+                    if character.stroke_count.is_none() {
+                        character.stroke_count = Some(count);
+                    }
+                }
+                b"freq" => {
+                    character.frequency = Some(self.parse_number_in_tag(b"freq")?);
+                }
+                b"jlpt" => {
+                    character.jlpt = Some(self.parse_number_in_tag(b"jlpt")?);
+                }
+                b"variant" => {
+                    skip_tag_into(&mut self.reader, &mut self.buf, b"variant")?;
+                }
+                b"rad_name" => {
+                    skip_tag_into(&mut self.reader, &mut self.buf, b"rad_name")?;
+                }
+                _ => {
+                    push_warning!(self.warnings, "Unknown tag in <misc>: <{}>", tag.tag_name());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_in_reading_meaning(&mut self, character: &mut KanjidicCharacter) -> Result<()> {
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "reading_meaning")?
+        {
+            match tag.name().0 {
+                b"rmgroup" => self.parse_in_rmgroup(character)?,
+                b"nanori" => {
+                    character.nanori.push(parse_string_in_tag_into(
+                        &mut self.reader,
+                        &mut self.buf,
+                        b"nanori",
+                    )?);
+                }
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <reading_meaning>: <{}>",
+                        tag.tag_name()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_in_rmgroup(&mut self, character: &mut KanjidicCharacter) -> Result<()> {
+        while let Some(tag) = get_next_child_in(&mut self.reader, &mut self.buf, "rmgroup")? {
+            match tag.name().0 {
+                b"reading" => {
+                    let mut readings = None;
+                    for attr in tag.attributes() {
+                        let attr = attr?;
+                        if attr.key.0 == b"r_type" {
+                            match attr.value.as_ref() {
+                                b"ja_on" => readings = Some(&mut character.on_readings),
+                                b"ja_kun" => readings = Some(&mut character.kun_readings),
+                                _ => {}
+                            }
+                        }
+                    }
+                    let reading =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"reading")?;
+                    if let Some(readings) = readings {
+                        readings.push(reading);
+                    }
+                }
+                b"meaning" => {
+                    // meaning without `m_lang` attribute is in English
+                    let mut is_english = true;
+                    for attr in tag.attributes() {
+                        let attr = attr?;
+                        if attr.key.0 == b"m_lang" {
+                            is_english = attr.value.as_ref() == b"en";
+                        }
+                    }
+                    let meaning =
+                        parse_string_in_tag_into(&mut self.reader, &mut self.buf, b"meaning")?;
+                    if is_english {
+                        character.meanings.push(meaning);
+                    }
+                }
+                _ => {
+                    push_warning!(
+                        self.warnings,
+                        "Unknown tag in <rmgroup>: <{}>",
+                        tag.tag_name()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_number_in_tag<T: FromStr>(&mut self, in_tag: &[u8]) -> Result<T> {
+        let text = parse_string_in_tag_into(&mut self.reader, &mut self.buf, in_tag)?;
+        let number = text.parse::<T>().map_err(|_| {
+            format!(
+                "Couldn't parse as number in <{}>: {}",
+                str::from_utf8(in_tag).unwrap_or("<Invalid UTF-8>"),
+                text
+            )
+        })?;
+        Ok(number)
+    }
+}
+
+pub fn parse_kanjidic_xml<R: BufRead>(reader: R) -> Result<Kanjidic> {
+    let mut parser = KanjidicParser::new(reader)?;
+    let mut characters = vec![];
+    while let Some(character) = parser.next_character()? {
+        characters.push(character);
+    }
+    let creation_date = parser.creation_date().map(|d| d.to_owned());
+    let warnings = parser.warnings().to_vec();
+    Ok(Kanjidic {
+        characters,
+        creation_date,
+        warnings,
+    })
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Kanjidic {
+    pub characters: Vec<KanjidicCharacter>,
+    pub creation_date: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// `<character>`
+#[derive(Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct KanjidicCharacter {
+    /// `<literal>`
+    pub literal: String,
+    /// Classical (Kangxi) radical number, 1-214
+    pub radical: Option<u8>,
+    /// Accepted stroke count.
+    ///
+    /// Common miscounts that follow it in xml are ignored.
+    pub stroke_count: Option<u8>,
+    /// 1-6: Kyouiku kanji grades, 8: remaining Jouyou kanji, 9-10: Jinmeiyou kanji
+    pub grade: Option<u8>,
+    /// Pre-2010 JLPT level, 1-4
+    pub jlpt: Option<u8>,
+    /// Rank among 2500 most used kanji in newspapers
+    pub frequency: Option<u16>,
+    /// Katakana on'yomi
+    pub on_readings: Vec<String>,
+    /// Hiragana kun'yomi. Okurigana is separated with '.'
+    pub kun_readings: Vec<String>,
+    /// Readings only used in names
+    pub nanori: Vec<String>,
+    /// English meanings
+    pub meanings: Vec<String>,
+}
@@ -1,11 +1,15 @@
 pub mod error;
 pub mod jmdict;
 pub mod jmnedict;
+pub mod kanjidic;
+pub mod radical;
+pub mod simplified;
 mod utils;
 mod xml;
 
-pub use crate::error::Error;
-pub use jmdict::{parse_jmdict_xml, JMDictParser};
+pub use crate::error::{Error, ErrorPosition};
+pub use jmdict::{parse_jmdict_xml, JMDictParser, JMDictPatchParser};
 pub use jmnedict::JMneDictParser;
+pub use kanjidic::KanjidicParser;
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,10 @@
+//! RADKFILE and KRADFILE radical decomposition files.
+//!
+//! Original files are EUC-JP encoded.
+//! They must be converted to UTF-8 before parsing, e.g. `iconv -f EUC-JP -t UTF-8`.
+
+mod parse;
+mod types;
+
+pub use parse::{parse_kradfile, parse_radkfile};
+pub use types::{KradKanji, RadkRadical};
@@ -0,0 +1,84 @@
+use std::io::BufRead;
+
+use crate::{Error, Result};
+
+use super::types::{KradKanji, RadkRadical};
+
+/// Parses RADKFILE.
+///
+/// Each radical starts with a line `$ <radical> <stroke count> [<image or jis code>]`,
+/// followed by lines of kanji that contain the radical.
+pub fn parse_radkfile<R: BufRead>(reader: R) -> Result<Vec<RadkRadical>> {
+    let mut radicals: Vec<RadkRadical> = vec![];
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix('$') {
+            let mut parts = rest.split_whitespace();
+            let radical = parts
+                .next()
+                .ok_or_else(|| invalid_line(i, "No radical after '$'"))?;
+            let stroke_count = parts
+                .next()
+                .and_then(|s| s.parse::<u8>().ok())
+                .ok_or_else(|| invalid_line(i, "No valid stroke count for radical"))?;
+            radicals.push(RadkRadical {
+                radical: radical.to_string(),
+                stroke_count,
+                kanjis: vec![],
+            });
+        } else {
+            let radical = radicals
+                .last_mut()
+                .ok_or_else(|| invalid_line(i, "Kanji found before any radical"))?;
+            radical.kanjis.extend(
+                line.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(String::from),
+            );
+        }
+    }
+
+    Ok(radicals)
+}
+
+/// Parses KRADFILE.
+///
+/// Each line is in format `<kanji> : <radical> <radical> ...`
+pub fn parse_kradfile<R: BufRead>(reader: R) -> Result<Vec<KradKanji>> {
+    let mut kanjis = vec![];
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (kanji, radicals) = line
+            .split_once(':')
+            .ok_or_else(|| invalid_line(i, "No ':' found"))?;
+        let kanji = kanji.trim();
+        if kanji.is_empty() {
+            return Err(invalid_line(i, "No kanji before ':'"));
+        }
+        kanjis.push(KradKanji {
+            kanji: kanji.to_string(),
+            radicals: radicals.split_whitespace().map(String::from).collect(),
+        });
+    }
+
+    Ok(kanjis)
+}
+
+fn invalid_line(idx: usize, message: &str) -> Error {
+    Error::InvalidLine {
+        line: idx + 1,
+        message: message.into(),
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Radical and kanji that contain it, in RADKFILE
+#[derive(Debug, PartialEq, Eq, Default, Clone, Serialize, Deserialize)]
+pub struct RadkRadical {
+    pub radical: String,
+    pub stroke_count: u8,
+    pub kanjis: Vec<String>,
+}
+
+/// Kanji and radicals that it is composed of, in KRADFILE
+#[derive(Debug, PartialEq, Eq, Default, Clone, Serialize, Deserialize)]
+pub struct KradKanji {
+    pub kanji: String,
+    pub radicals: Vec<String>,
+}
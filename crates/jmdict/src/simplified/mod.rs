@@ -0,0 +1,8 @@
+mod types;
+mod write;
+
+pub use types::*;
+pub use write::{
+    write_jmdict_simplified, write_jmnedict_simplified, JMDictSimplifiedWriter,
+    JMneDictSimplifiedWriter, JMDICT_SIMPLIFIED_VERSION,
+};
@@ -0,0 +1,325 @@
+//! Types of [jmdict-simplified](https://github.com/scriptin/jmdict-simplified) JSON format
+//!
+//! Tags are entity names without '&' and ';',
+//! and are described in the `tags` field of the root object.
+
+use serde::{Deserialize, Serialize};
+
+use crate::jmdict::{
+    JMEntry, JMGloss, JMGlossType, JMKanji, JMLanguageSource, JMPriority, JMReading, JMReference,
+    JMSense,
+};
+use crate::jmnedict::{JMneEntry, JMneKanji, JMneReading, JMneTranslation};
+
+/// `appliesToKanji` and `appliesToKana` value that means all forms
+const APPLIES_TO_ALL: &str = "*";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedWord {
+    pub id: String,
+    pub kanji: Vec<SimplifiedKanji>,
+    pub kana: Vec<SimplifiedKana>,
+    pub sense: Vec<SimplifiedSense>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedKanji {
+    pub common: bool,
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedKana {
+    pub common: bool,
+    pub text: String,
+    pub tags: Vec<String>,
+    /// `["*"]` if kana applies to all kanji
+    pub applies_to_kanji: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedSense {
+    pub part_of_speech: Vec<String>,
+    /// `["*"]` if sense applies to all kanji
+    pub applies_to_kanji: Vec<String>,
+    /// `["*"]` if sense applies to all kana
+    pub applies_to_kana: Vec<String>,
+    pub related: Vec<SimplifiedXref>,
+    pub antonym: Vec<SimplifiedXref>,
+    pub field: Vec<String>,
+    pub dialect: Vec<String>,
+    pub misc: Vec<String>,
+    pub info: Vec<String>,
+    pub language_source: Vec<SimplifiedLanguageSource>,
+    pub gloss: Vec<SimplifiedGloss>,
+}
+
+/// Serialized as an array, e.g. `["丸", "まる", 1]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SimplifiedXref {
+    TermReadingSense(String, String, u16),
+    TermReading(String, String),
+    TermSense(String, u16),
+    Term(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedLanguageSource {
+    pub lang: String,
+    pub full: bool,
+    pub wasei: bool,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedGloss {
+    pub lang: String,
+    pub gender: Option<String>,
+    #[serde(rename = "type")]
+    pub gloss_type: Option<JMGlossType>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedNameWord {
+    pub id: String,
+    pub kanji: Vec<SimplifiedNameKanji>,
+    pub kana: Vec<SimplifiedNameKana>,
+    pub translation: Vec<SimplifiedTranslation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedNameKanji {
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedNameKana {
+    pub text: String,
+    pub tags: Vec<String>,
+    /// `["*"]` if kana applies to all kanji
+    pub applies_to_kanji: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedTranslation {
+    #[serde(rename = "type")]
+    pub name_type: Vec<String>,
+    pub related: Vec<SimplifiedXref>,
+    pub translation: Vec<SimplifiedTranslationText>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplifiedTranslationText {
+    pub lang: String,
+    pub text: String,
+}
+
+/// Form is common if it has any of `news1`, `ichi1`, `spec1`, `spec2`, `gai1` priority
+fn is_common(priorities: &[JMPriority]) -> bool {
+    priorities.iter().any(|p| {
+        matches!(
+            p,
+            JMPriority::News1
+                | JMPriority::Ichi1
+                | JMPriority::Spec1
+                | JMPriority::Spec2
+                | JMPriority::Gai1
+        )
+    })
+}
+
+fn applies_to(forms: &[String]) -> Vec<String> {
+    if forms.is_empty() {
+        vec![APPLIES_TO_ALL.into()]
+    } else {
+        forms.to_vec()
+    }
+}
+
+impl From<&JMEntry> for SimplifiedWord {
+    fn from(entry: &JMEntry) -> Self {
+        SimplifiedWord {
+            id: entry.id.to_string(),
+            kanji: entry.kanjis.iter().map(SimplifiedKanji::from).collect(),
+            kana: entry.readings.iter().map(SimplifiedKana::from).collect(),
+            sense: entry.senses.iter().map(SimplifiedSense::from).collect(),
+        }
+    }
+}
+
+impl From<&JMKanji> for SimplifiedKanji {
+    fn from(kanji: &JMKanji) -> Self {
+        SimplifiedKanji {
+            common: is_common(&kanji.priority),
+            text: kanji.kanji.clone(),
+            tags: kanji.info.iter().map(|i| i.entity_name().into()).collect(),
+        }
+    }
+}
+
+impl From<&JMReading> for SimplifiedKana {
+    fn from(reading: &JMReading) -> Self {
+        let applies_to_kanji = if reading.nokanji {
+            vec![]
+        } else {
+            applies_to(&reading.to_form)
+        };
+        SimplifiedKana {
+            common: is_common(&reading.priority),
+            text: reading.reading.clone(),
+            tags: reading
+                .info
+                .iter()
+                .map(|i| i.entity_name().into())
+                .collect(),
+            applies_to_kanji,
+        }
+    }
+}
+
+impl From<&JMSense> for SimplifiedSense {
+    fn from(sense: &JMSense) -> Self {
+        SimplifiedSense {
+            part_of_speech: sense.pos.iter().map(|p| p.entity_name().into()).collect(),
+            applies_to_kanji: applies_to(&sense.to_form),
+            applies_to_kana: applies_to(&sense.to_reading),
+            related: sense.references.iter().map(SimplifiedXref::from).collect(),
+            antonym: sense.antonyms.iter().map(SimplifiedXref::from).collect(),
+            field: sense
+                .fields
+                .iter()
+                .map(|f| f.entity_name().into())
+                .collect(),
+            dialect: sense
+                .dialects
+                .iter()
+                .map(|d| d.entity_name().into())
+                .collect(),
+            misc: sense.misc.iter().map(|m| m.entity_name().into()).collect(),
+            info: sense.info.clone(),
+            language_source: sense
+                .language_sources
+                .iter()
+                .map(SimplifiedLanguageSource::from)
+                .collect(),
+            gloss: sense.meanings.iter().map(SimplifiedGloss::from).collect(),
+        }
+    }
+}
+
+impl From<&JMReference> for SimplifiedXref {
+    fn from(reference: &JMReference) -> Self {
+        let term = reference.term.clone();
+        match (reference.reading.clone(), reference.sense) {
+            (Some(reading), Some(sense)) => SimplifiedXref::TermReadingSense(term, reading, sense),
+            (Some(reading), None) => SimplifiedXref::TermReading(term, reading),
+            (None, Some(sense)) => SimplifiedXref::TermSense(term, sense),
+            (None, None) => SimplifiedXref::Term(term),
+        }
+    }
+}
+
+impl From<&JMLanguageSource> for SimplifiedLanguageSource {
+    fn from(source: &JMLanguageSource) -> Self {
+        SimplifiedLanguageSource {
+            lang: source.lang.clone(),
+            full: !source.partial,
+            wasei: source.wasei,
+            text: (!source.text.is_empty()).then(|| source.text.clone()),
+        }
+    }
+}
+
+impl From<&JMGloss> for SimplifiedGloss {
+    fn from(gloss: &JMGloss) -> Self {
+        SimplifiedGloss {
+            lang: gloss.lang.clone(),
+            gender: gloss.gender.clone(),
+            gloss_type: gloss.gloss_type,
+            text: gloss.text.clone(),
+        }
+    }
+}
+
+impl From<&JMneEntry> for SimplifiedNameWord {
+    fn from(entry: &JMneEntry) -> Self {
+        SimplifiedNameWord {
+            id: entry.id.to_string(),
+            kanji: entry.kanjis.iter().map(SimplifiedNameKanji::from).collect(),
+            kana: entry
+                .readings
+                .iter()
+                .map(SimplifiedNameKana::from)
+                .collect(),
+            translation: entry
+                .translations
+                .iter()
+                .map(SimplifiedTranslation::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&JMneKanji> for SimplifiedNameKanji {
+    fn from(kanji: &JMneKanji) -> Self {
+        SimplifiedNameKanji {
+            text: kanji.kanji.clone(),
+            tags: kanji.info.iter().map(|i| i.entity_name().into()).collect(),
+        }
+    }
+}
+
+impl From<&JMneReading> for SimplifiedNameKana {
+    fn from(reading: &JMneReading) -> Self {
+        SimplifiedNameKana {
+            text: reading.reading.clone(),
+            tags: reading
+                .info
+                .iter()
+                .map(|i| i.entity_name().into())
+                .collect(),
+            applies_to_kanji: applies_to(&reading.to_kanji),
+        }
+    }
+}
+
+impl From<&JMneTranslation> for SimplifiedTranslation {
+    fn from(translation: &JMneTranslation) -> Self {
+        SimplifiedTranslation {
+            name_type: translation
+                .name_type
+                .iter()
+                .map(|t| t.entity_name().into())
+                .collect(),
+            related: translation
+                .xref
+                .iter()
+                .map(|xref| SimplifiedXref::from(&JMReference::parse(xref)))
+                .collect(),
+            // only English translations are parsed
+            translation: translation
+                .translations
+                .iter()
+                .map(|text| SimplifiedTranslationText {
+                    lang: "eng".into(),
+                    text: text.clone(),
+                })
+                .collect(),
+        }
+    }
+}
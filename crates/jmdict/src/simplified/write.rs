@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::jmdict::{JMDict, JMEntry};
+use crate::jmnedict::{JMneDict, JMneEntry};
+use crate::Result;
+
+use super::types::{SimplifiedNameWord, SimplifiedWord};
+
+/// Version of jmdict-simplified whose format is written
+pub const JMDICT_SIMPLIFIED_VERSION: &str = "3.6.1";
+
+/// Writes jmdict-simplified JSON of JMdict, one entry at a time.
+///
+/// Fields that are only known after all entries are parsed
+/// (e.g. `dictDate`) are written after `words`.
+pub struct JMDictSimplifiedWriter<W: Write> {
+    inner: WordsWriter<W>,
+}
+
+impl<W: Write> JMDictSimplifiedWriter<W> {
+    /// `tags` maps entity name to its description, as in [`JMDictParser::entities`].
+    ///
+    /// [`JMDictParser::entities`]: crate::JMDictParser::entities
+    pub fn new(writer: W, tags: &BTreeMap<String, String>) -> Result<Self> {
+        let inner = WordsWriter::new(writer, tags, Some(false))?;
+        Ok(JMDictSimplifiedWriter { inner })
+    }
+
+    pub fn write_entry(&mut self, entry: &JMEntry) -> Result<()> {
+        for sense in &entry.senses {
+            for gloss in &sense.meanings {
+                if !self.inner.languages.contains(&gloss.lang) {
+                    self.inner.languages.insert(gloss.lang.clone());
+                }
+            }
+        }
+        self.inner.write_word(&SimplifiedWord::from(entry))
+    }
+
+    /// Closes JSON root object and returns the inner writer
+    pub fn finish(self, dict_date: Option<&str>) -> Result<W> {
+        self.inner.finish(dict_date)
+    }
+}
+
+/// Writes jmdict-simplified JSON of JMnedict, one entry at a time.
+pub struct JMneDictSimplifiedWriter<W: Write> {
+    inner: WordsWriter<W>,
+}
+
+impl<W: Write> JMneDictSimplifiedWriter<W> {
+    /// `tags` maps entity name to its description, as in [`JMneDictParser::entities`].
+    ///
+    /// [`JMneDictParser::entities`]: crate::JMneDictParser::entities
+    pub fn new(writer: W, tags: &BTreeMap<String, String>) -> Result<Self> {
+        let mut inner = WordsWriter::new(writer, tags, None)?;
+        // only English translations are parsed
+        inner.languages.insert("eng".into());
+        Ok(JMneDictSimplifiedWriter { inner })
+    }
+
+    pub fn write_entry(&mut self, entry: &JMneEntry) -> Result<()> {
+        self.inner.write_word(&SimplifiedNameWord::from(entry))
+    }
+
+    /// Closes JSON root object and returns the inner writer
+    pub fn finish(self, dict_date: Option<&str>) -> Result<W> {
+        self.inner.finish(dict_date)
+    }
+}
+
+/// Writes root object fields before and after `words` array
+struct WordsWriter<W: Write> {
+    writer: W,
+    is_first_word: bool,
+    languages: BTreeSet<String>,
+}
+
+impl<W: Write> WordsWriter<W> {
+    fn new(
+        mut writer: W,
+        tags: &BTreeMap<String, String>,
+        common_only: Option<bool>,
+    ) -> Result<Self> {
+        write!(writer, "{{\"version\":")?;
+        serde_json::to_writer(&mut writer, JMDICT_SIMPLIFIED_VERSION)?;
+        if let Some(common_only) = common_only {
+            write!(writer, ",\"commonOnly\":{}", common_only)?;
+        }
+        write!(writer, ",\"tags\":")?;
+        serde_json::to_writer(&mut writer, tags)?;
+        write!(writer, ",\"words\":[")?;
+        Ok(WordsWriter {
+            writer,
+            is_first_word: true,
+            languages: BTreeSet::new(),
+        })
+    }
+
+    fn write_word<T: Serialize>(&mut self, word: &T) -> Result<()> {
+        if self.is_first_word {
+            self.is_first_word = false;
+        } else {
+            write!(self.writer, ",")?;
+        }
+        serde_json::to_writer(&mut self.writer, word)?;
+        Ok(())
+    }
+
+    fn finish(mut self, dict_date: Option<&str>) -> Result<W> {
+        write!(self.writer, "],\"languages\":")?;
+        serde_json::to_writer(&mut self.writer, &self.languages)?;
+        write!(self.writer, ",\"dictDate\":")?;
+        serde_json::to_writer(&mut self.writer, &dict_date)?;
+        write!(self.writer, ",\"dictRevisions\":[]}}")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes `jmdict` as jmdict-simplified JSON
+pub fn write_jmdict_simplified<W: Write>(jmdict: &JMDict, writer: W) -> Result<W> {
+    let mut writer = JMDictSimplifiedWriter::new(writer, &jmdict.entities)?;
+    for entry in &jmdict.entries {
+        writer.write_entry(entry)?;
+    }
+    writer.finish(jmdict.creation_date.as_deref())
+}
+
+/// Writes `jmnedict` as jmdict-simplified JSON
+pub fn write_jmnedict_simplified<W: Write>(jmnedict: &JMneDict, writer: W) -> Result<W> {
+    let mut writer = JMneDictSimplifiedWriter::new(writer, &jmnedict.entities)?;
+    for entry in &jmnedict.entries {
+        writer.write_entry(entry)?;
+    }
+    writer.finish(jmnedict.creation_date.as_deref())
+}
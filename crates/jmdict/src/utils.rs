@@ -28,10 +28,10 @@ macro_rules! jm_entity_enum {
       $enum_name:ident;
       $(
         $( #[$attrs:meta] )*
-        $( $key:pat $(if $guard:expr)? )? => $variant:ident
+        $key:literal => $variant:ident
       ),+,
   ) => {
-      #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ::schemars::JsonSchema)]
+      #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ::schemars::JsonSchema)]
       #[serde(rename_all = "camelCase")]
       $( #[$enum_attrs] )*
       pub enum $enum_name {
@@ -39,44 +39,95 @@ macro_rules! jm_entity_enum {
               $( #[$attrs] )*
               $variant,
           )+
+          /// Entity unknown at the time of development.
+          /// Contains entity name without '&' and ';'.
+          ///
+          /// Not in JSON schema, as dictionary entries do not keep unknown entities.
+          #[serde(rename = "unknown")]
+          #[schemars(skip)]
+          Other(String),
       }
 
       impl $enum_name {
+          /// Returns `None` if `field` is not a single entity.
+          ///
+          /// Unknown entity is parsed into `Other`.
           pub fn parse_field(field: &[u8]) -> Option<$enum_name> {
               if crate::utils::is_single_entity(field) {
-                  match &field[1..field.len() -1] {
+                  let entity = &field[1..field.len() -1];
+                  match entity {
                       $(
-                          $(
-                            $key $(if $guard)? => Some(Self::$variant),
-                          )?
+                          $key => Some(Self::$variant),
                       )+
-                      _ => None,
+                      _ => Some(Self::Other(String::from_utf8_lossy(entity).into_owned())),
                   }
               } else {
                   None
               }
+          }
+
+          /// Entity name without '&' and ';'
+          pub fn entity_name(&self) -> &str {
+              match self {
+                  $(
+                      Self::$variant => ::core::str::from_utf8($key).unwrap_or_default(),
+                  )+
+                  Self::Other(entity) => entity,
+              }
+          }
 
+          /// Entity name if entity is unknown (`Other`)
+          pub fn unknown_entity_name(&self) -> Option<&str> {
+              match self {
+                  Self::Other(entity) => Some(entity),
+                  _ => None,
+              }
           }
       }
   };
 }
 
-/// `parse_entity_enum_into!(EnumName, reader, buf, "tag", add_to)`
+/// `parse_entity_enum_into!(EnumName, reader, buf, "tag", add_to, warnings)`
 ///
 /// Parses a field in "tag" using `EnumName::parse_field()`,
-/// and if it returns `Some(val)`, run `add_to.push(val)`
+/// and if it returns `Some(val)`, run `add_to.push(val)`.
+/// Unknown entity and non-entity field is added to `warnings`.
 macro_rules! parse_entity_enum_into {
-    ($enum: ident, $reader:expr, $buf:expr, $tag: literal, $to:expr ) => {
+    ($enum: ident, $reader:expr, $buf:expr, $tag: literal, $to:expr, $warnings:expr ) => {
         let field = crate::xml::parse_text_in_tag_into($reader, $buf, $tag.as_bytes())?;
         let value = $enum::parse_field(&field);
         if let Some(value) = value {
+            if let $enum::Other(entity) = &value {
+                crate::utils::push_warning!(
+                    $warnings,
+                    "Unknown entity in <{}>: &{};",
+                    $tag,
+                    entity
+                );
+            }
             $to.push(value);
         } else {
-            warn!("Unknown {}: {}", $tag, String::from_utf8_lossy(&field));
+            crate::utils::push_warning!(
+                $warnings,
+                "Unknown {}: {}",
+                $tag,
+                String::from_utf8_lossy(&field)
+            );
         }
     };
 }
 
+/// `push_warning!(warnings, "format {}", args)`
+///
+/// Logs warning message and adds it to `warnings`.
+macro_rules! push_warning {
+    ($warnings:expr, $($arg:tt)+) => {{
+        let message = format!($($arg)+);
+        ::log::warn!("{}", message);
+        $warnings.push(message);
+    }};
+}
+
 /// returns true if
 pub(crate) fn is_single_entity(text: &[u8]) -> bool {
     if text.len() > 2 && text[0] == b'&' && text[text.len() - 1] == b';' {
@@ -90,3 +141,4 @@ pub(crate) fn is_single_entity(text: &[u8]) -> bool {
 
 pub(crate) use jm_entity_enum;
 pub(crate) use parse_entity_enum_into;
+pub(crate) use push_warning;
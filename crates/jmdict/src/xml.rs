@@ -7,14 +7,17 @@
 //! For example, an element must have one and only one id.
 use lazy_regex::{lazy_regex, Lazy, Regex};
 use polonius_the_crab::{polonius, polonius_return};
-use quick_xml::escape::{resolve_predefined_entity, unescape_with};
+use quick_xml::escape::{escape, partial_escape, resolve_predefined_entity, unescape_with};
 use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::name::QName;
 use quick_xml::reader::Reader;
 
 use core::str;
 use std::borrow::Cow;
-use std::io::BufRead;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Read, Write};
 
+use crate::error::ErrorPosition;
 use crate::{Error, Result};
 
 pub trait TagName<'a>
@@ -37,15 +40,194 @@ impl<'a> TagName<'a> for BytesEnd<'a> {
     }
 }
 
+/// Reader that counts the lines of bytes consumed from `inner`
+pub struct LineCounter<R> {
+    inner: R,
+    /// Number of '\n' consumed
+    newlines: u64,
+}
+
+impl<R> LineCounter<R> {
+    pub fn new(inner: R) -> Self {
+        LineCounter { inner, newlines: 0 }
+    }
+
+    /// Line number of the next byte to be consumed, starting from 1
+    pub fn line(&self) -> u64 {
+        self.newlines + 1
+    }
+}
+
+fn count_newlines(bytes: &[u8]) -> u64 {
+    bytes.iter().filter(|b| **b == b'\n').count() as u64
+}
+
+impl<R: Read> Read for LineCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.newlines += count_newlines(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for LineCounter<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if amt > 0 {
+            // does not read from source as `amt` bytes are already in the buffer
+            if let Ok(buf) = self.inner.fill_buf() {
+                self.newlines += count_newlines(&buf[..amt.min(buf.len())]);
+            }
+        }
+        self.inner.consume(amt)
+    }
+}
+
+/// Path of elements currently being parsed, e.g. `entry/sense/gloss`
+#[derive(Debug, Default)]
+pub struct ElementPath {
+    path: String,
+    /// Length of `path` before each element was entered
+    lens: Vec<usize>,
+}
+
+impl ElementPath {
+    pub fn enter(&mut self, name: &[u8]) {
+        self.lens.push(self.path.len());
+        if !self.path.is_empty() {
+            self.path.push('/');
+        }
+        self.path
+            .push_str(str::from_utf8(name).unwrap_or("<Invalid UTF-8>"));
+    }
+
+    pub fn leave(&mut self) {
+        if let Some(len) = self.lens.pop() {
+            self.path.truncate(len);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.path.clear();
+        self.lens.clear();
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Current position of `reader`, to be attached to an error
+pub fn error_position<R>(
+    reader: &Reader<LineCounter<R>>,
+    ent_seq: Option<u32>,
+    path: &ElementPath,
+) -> ErrorPosition {
+    ErrorPosition {
+        byte_offset: reader.buffer_position(),
+        line: reader.get_ref().line(),
+        ent_seq,
+        path: path.as_str().to_owned(),
+    }
+}
+
 pub(crate) static DATE_REG: Lazy<Regex> = lazy_regex!(r#"\d\d\d\d-\d\d-\d\d"#);
 
+static ENTITY_DECL_REG: Lazy<Regex> = lazy_regex!(r#"<!ENTITY\s+(\S+)\s+"([^"]*)"\s*>"#);
+
+/// Parses `<!ENTITY name "description">` declarations in DOCTYPE
+pub fn parse_entity_declarations(doctype: &[u8]) -> BTreeMap<String, String> {
+    let doctype = String::from_utf8_lossy(doctype);
+    ENTITY_DECL_REG
+        .captures_iter(&doctype)
+        .map(|caps| (caps[1].to_owned(), caps[2].to_owned()))
+        .collect()
+}
+
 pub fn parse_string_in_tag_into<R: BufRead>(
     reader: &mut Reader<R>,
     buf: &mut Vec<u8>,
     in_tag: &[u8],
 ) -> Result<String> {
     let characters = parse_text_in_tag_into(reader, buf, in_tag)?;
-    let string = str::from_utf8(&characters)?;
+    decode_text(&characters)
+}
+
+/// Parses text in tag, where the text may contain `<child>` elements that only contain text.
+///
+/// Returns the concatenated text, and whether a `<child>` element was found.
+pub fn parse_string_with_child_in_tag_into<R: BufRead>(
+    reader: &mut Reader<R>,
+    buf: &mut Vec<u8>,
+    in_tag: &[u8],
+    child: &[u8],
+) -> Result<(String, bool)> {
+    // whitespace around child elements must be kept
+    let trim_start = reader.config().trim_text_start;
+    let trim_end = reader.config().trim_text_end;
+    reader.config_mut().trim_text_start = false;
+    reader.config_mut().trim_text_end = false;
+    let result = parse_text_with_child_in_tag_into(reader, buf, in_tag, child);
+    reader.config_mut().trim_text_start = trim_start;
+    reader.config_mut().trim_text_end = trim_end;
+
+    let (characters, has_child) = result?;
+    let string = decode_text(characters.trim_ascii())?;
+    Ok((string, has_child))
+}
+
+fn parse_text_with_child_in_tag_into<R: BufRead>(
+    reader: &mut Reader<R>,
+    buf: &mut Vec<u8>,
+    in_tag: &[u8],
+    child: &[u8],
+) -> Result<(Vec<u8>, bool)> {
+    let mut characters = Vec::new();
+    let mut has_child = false;
+    loop {
+        buf.clear();
+        match reader.read_event_into(buf)? {
+            Event::Start(tag) => {
+                if tag.name().0 != child {
+                    return Err(Error::Unexpected {
+                        expected: "text".into(),
+                        actual: format!("starting tag <{}>", tag.tag_name()),
+                    });
+                }
+                has_child = true;
+                let text = parse_text_in_tag_into(reader, buf, child)?;
+                characters.extend_from_slice(&text);
+            }
+            Event::Text(text) => {
+                let text = text.into_inner();
+                characters.extend_from_slice(&text);
+            }
+            Event::End(tag) => {
+                if tag.name().0 == in_tag {
+                    return Ok((characters, has_child));
+                } else {
+                    return Err(Error::Unexpected {
+                        expected: format!("ending tag </{}>", str::from_utf8(in_tag)?),
+                        actual: format!("ending tag </{}>", tag.tag_name()),
+                    });
+                }
+            }
+            Event::Eof => {
+                return Err(Error::InvalidXml(format!(
+                    "<{}> not closed",
+                    str::from_utf8(in_tag)?
+                )))
+            }
+            _ => {}
+        }
+    }
+}
+
+fn decode_text(characters: &[u8]) -> Result<String> {
+    let string = str::from_utf8(characters)?;
     let string = resolve_custom_entity_item(string);
     let string = unescape_with(&string, unescape_entity)?;
     Ok(string.into())
@@ -80,9 +262,18 @@ pub fn parse_text_in_tag_into<R: BufRead>(
                     });
                 }
             }
-            _ => {
-                unimplemented!()
+            Event::CData(text) => {
+                let text = text.escape().map_err(quick_xml::Error::from)?;
+                characters.extend_from_slice(&text.into_inner());
+            }
+            Event::Eof => {
+                return Err(Error::InvalidXml(format!(
+                    "<{}> not closed",
+                    str::from_utf8(in_tag)?
+                )))
             }
+            // comments, processing instructions
+            _ => {}
         }
     }
 }
@@ -115,6 +306,17 @@ fn unescape_entity(entity: &str) -> Option<&'static str> {
     }
 }
 
+/// Skips everything up to and including the end of `<in_tag>`
+pub fn skip_tag_into<R: BufRead>(
+    reader: &mut Reader<R>,
+    buf: &mut Vec<u8>,
+    in_tag: &[u8],
+) -> Result<()> {
+    buf.clear();
+    reader.read_to_end_into(QName(in_tag), buf)?;
+    Ok(())
+}
+
 pub fn get_next_child_in<'b, R: BufRead>(
     reader: &mut Reader<R>,
     mut buf: &'b mut Vec<u8>,
@@ -144,3 +346,93 @@ pub fn get_next_child_in<'b, R: BufRead>(
         })
     }
 }
+
+/// Writes xml declaration, and DOCTYPE that declares `entities`
+pub fn write_xml_start<W: Write>(
+    writer: &mut W,
+    root: &str,
+    entities: &BTreeMap<String, String>,
+) -> Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, "<!DOCTYPE {} [", root)?;
+    for (name, description) in entities {
+        writeln!(writer, r#"<!ENTITY {} "{}">"#, name, escape(description))?;
+    }
+    writeln!(writer, "]>")?;
+    Ok(())
+}
+
+/// Returns `entities` with undeclared entity `names` added,
+/// using entity name as its description
+pub fn with_undeclared_entities<'a, I>(
+    entities: &BTreeMap<String, String>,
+    names: I,
+) -> BTreeMap<String, String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut entities = entities.clone();
+    for name in names {
+        if !entities.contains_key(name) {
+            entities.insert(name.to_string(), name.to_string());
+        }
+    }
+    entities
+}
+
+/// Returns error if any entity of `names` is not declared in `entities`
+pub fn check_entities_declared<'a, I>(
+    entities: &BTreeMap<String, String>,
+    names: I,
+    ent_seq: u32,
+) -> Result<()>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    for name in names {
+        if !entities.contains_key(name) {
+            return Err(Error::UndeclaredEntity {
+                entity: name.to_string(),
+                ent_seq,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Writes `<tag>text</tag>` with `text` escaped
+pub fn write_text_element<W: Write>(writer: &mut W, tag: &str, text: &str) -> Result<()> {
+    write_text_element_with_attributes(writer, tag, &[], text)
+}
+
+/// Writes `<tag key="value">text</tag>` with attribute values and `text` escaped
+pub fn write_text_element_with_attributes<W: Write>(
+    writer: &mut W,
+    tag: &str,
+    attributes: &[(&str, &str)],
+    text: &str,
+) -> Result<()> {
+    write_start_tag(writer, tag, attributes)?;
+    writeln!(writer, "{}</{}>", partial_escape(text), tag)?;
+    Ok(())
+}
+
+/// Writes `<tag>&entity;</tag>`
+pub fn write_entity_element<W: Write>(writer: &mut W, tag: &str, entity: &str) -> Result<()> {
+    writeln!(writer, "<{tag}>&{entity};</{tag}>")?;
+    Ok(())
+}
+
+/// Writes `<tag key="value">` with attribute values escaped
+pub fn write_start_tag<W: Write>(
+    writer: &mut W,
+    tag: &str,
+    attributes: &[(&str, &str)],
+) -> Result<()> {
+    write!(writer, "<{}", tag)?;
+    for (key, value) in attributes {
+        write!(writer, r#" {}="{}""#, key, escape(*value))?;
+    }
+    write!(writer, ">")?;
+    Ok(())
+}
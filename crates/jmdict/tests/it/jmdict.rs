@@ -1,5 +1,6 @@
 use insta::assert_yaml_snapshot;
-use yomikiri_jmdict::{parse_jmdict_xml, Result};
+use yomikiri_jmdict::jmdict::JMPatch;
+use yomikiri_jmdict::{parse_jmdict_xml, ErrorPosition, JMDictPatchParser, Result};
 
 #[test]
 fn parse_jmdict() -> Result<()> {
@@ -136,3 +137,314 @@ fn parse_jmdict_2() -> Result<()> {
     assert_yaml_snapshot!(result);
     Ok(())
 }
+
+#[test]
+fn parse_references() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1352320</ent_seq>
+<k_ele>
+<keb>上げる</keb>
+</k_ele>
+<r_ele>
+<reb>あげる</reb>
+</r_ele>
+<sense>
+<pos>&v1;</pos>
+<xref>上がる・あがる・1</xref>
+<xref>揚げる・1</xref>
+<xref>あげる</xref>
+<ant>下げる・さげる</ant>
+<gloss>to raise</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdict_xml(xml.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_field_and_language_source() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1012980</ent_seq>
+<r_ele>
+<reb>アルバイト</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<pos>&vs;</pos>
+<lsource xml:lang="ger">Arbeit</lsource>
+<gloss>part-time job</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1080510</ent_seq>
+<r_ele>
+<reb>ナイター</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<field>&baseb;</field>
+<field>&sports;</field>
+<field>&unknownfield;</field>
+<lsource ls_wasei="y">nighter</lsource>
+<gloss>game played under lights</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1133780</ent_seq>
+<r_ele>
+<reb>アイスキャンデー</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<lsource ls_type="part" ls_wasei="y">ice candy</lsource>
+<lsource xml:lang="eng"></lsource>
+<gloss>ice candy</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdict_xml(xml.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_gloss_attributes() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1060500</ent_seq>
+<r_ele>
+<reb>ウォークマン</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss g_type="tm">Walkman</gloss>
+<gloss g_type="expl">portable cassette player</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss g_type="lit" g_gend="fem">cat &amp; kitten</gloss>
+<gloss>a <pri>highlighted</pri> gloss</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdict_xml(xml.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_multilingual_glosses() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+<sense>
+<gloss xml:lang="dut">kat</gloss>
+<gloss xml:lang="dut">poes</gloss>
+</sense>
+<sense>
+<gloss xml:lang="ger">Katze</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdict_xml(xml.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_unknown_entity() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele>
+<reb>ヽ</reb>
+<!-- comment inside element -->
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<pos>&new-pos;</pos>
+<misc>&oth;</misc>
+<misc>&new-misc;</misc>
+<gloss>repetition mark in katakana</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdict_xml(xml.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_priority() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<k_ele>
+<keb>面白い</keb>
+<ke_pri>ichi1</ke_pri>
+<ke_pri>news1</ke_pri>
+<ke_pri>nf19</ke_pri>
+</k_ele>
+<k_ele>
+<keb>面白</keb>
+<ke_pri>nf99</ke_pri>
+</k_ele>
+<r_ele>
+<reb>おもしろい</reb>
+<re_pri>ichi1</re_pri>
+<re_pri>news1</re_pri>
+<re_pri>nf19</re_pri>
+</r_ele>
+<sense>
+<pos>&adj-i;</pos>
+<gloss>interesting</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdict_xml(xml.as_bytes())?;
+    assert_eq!(result.entries[0].priority(), 156);
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_error_position() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele>
+<reb>ヽ</reb>
+</r_ele>
+</entry>
+<entry>
+<ent_seq>1000010</ent_seq>
+<r_ele>
+<reb>ゝ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>repetition <b>mark</b> in hiragana</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let err = parse_jmdict_xml(xml.as_bytes()).unwrap_err();
+    assert_eq!(
+        err.position().unwrap(),
+        &ErrorPosition {
+            byte_offset: xml.find("mark").unwrap() as u64,
+            line: 16,
+            ent_seq: Some(1000010),
+            path: "entry/sense/gloss".into(),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Expected text, encountered starting tag <b>, at line 16 (byte 235) in <entry/sense/gloss> of entry 1000010"
+    );
+}
+
+#[test]
+fn parse_examples() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1207590</ent_seq>
+<k_ele>
+<keb>絵</keb>
+</k_ele>
+<r_ele>
+<reb>え</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>picture</gloss>
+<example>
+<ex_srce exsrc_type="tat">75470</ex_srce>
+<ex_text>絵</ex_text>
+<ex_sent xml:lang="jpn">壁に絵を掛けた。</ex_sent>
+<ex_sent xml:lang="eng">I hung a picture on the wall.</ex_sent>
+</example>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdict_xml(xml.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_patch() -> Result<()> {
+    let xml = r#"<JMdict_patch>
+<add>
+<entry>
+<ent_seq>2900001</ent_seq>
+<r_ele>
+<reb>よみきり</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>reading to the end</gloss>
+</sense>
+</entry>
+</add>
+<modify>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele>
+<reb>まる</reb>
+</r_ele>
+<sense>
+<gloss>circle</gloss>
+</sense>
+</entry>
+</modify>
+<delete><ent_seq>1000010</ent_seq></delete>
+</JMdict_patch>
+"#;
+    let mut parser = JMDictPatchParser::new(xml.as_bytes())?;
+    let mut patches = vec![];
+    while let Some(patch) = parser.next_patch()? {
+        patches.push(patch);
+    }
+    assert!(parser.warnings().is_empty());
+    assert_eq!(patches.len(), 3);
+    assert!(matches!(&patches[0], JMPatch::Add(entry) if entry.id == 2900001));
+    assert!(matches!(&patches[1], JMPatch::Modify(entry) if entry.id == 1000000));
+    assert!(matches!(&patches[2], JMPatch::Delete(1000010)));
+    Ok(())
+}
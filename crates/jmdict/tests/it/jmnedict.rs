@@ -76,3 +76,60 @@ fn test_parse_jmnedict() -> Result<()> {
     assert_yaml_snapshot!(result);
     Ok(())
 }
+
+#[test]
+fn parse_error_position() {
+    let xml = r#"<?xml version="1.0"?>
+<JMnedict>
+<entry>
+<ent_seq>5000000</ent_seq>
+<k_ele>
+<keb>ゝ泉堂</keb>
+</k_ele>
+<r_ele>
+</r_ele>
+</entry>
+</JMnedict>
+"#;
+    let err = parse_jmnedict_xml(xml.as_bytes()).unwrap_err();
+    let position = err.position().unwrap();
+    assert_eq!(position.line, 9);
+    assert_eq!(position.ent_seq, Some(5000000));
+    assert_eq!(position.path, "entry/r_ele");
+}
+
+#[test]
+fn parse_info_and_priority() -> Result<()> {
+    let xml = r#"<?xml version="1.0"?>
+<JMnedict>
+<entry>
+<ent_seq>5000001</ent_seq>
+<k_ele>
+<keb>佐藤</keb>
+<ke_pri>spec1</ke_pri>
+</k_ele>
+<k_ele>
+<keb>佐籐</keb>
+<ke_inf>&iK;</ke_inf>
+</k_ele>
+<r_ele>
+<reb>さとう</reb>
+<re_pri>spec1</re_pri>
+<re_pri>nf99</re_pri>
+</r_ele>
+<r_ele>
+<reb>さと</reb>
+<re_inf>&ok;</re_inf>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<trans_det>Satou</trans_det>
+</trans>
+</entry>
+</JMnedict>
+"#;
+    let result = parse_jmnedict_xml(xml.as_bytes())?;
+    assert_eq!(result.entries[0].priority(), 100);
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
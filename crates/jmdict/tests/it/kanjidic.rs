@@ -0,0 +1,80 @@
+use insta::assert_yaml_snapshot;
+use yomikiri_jmdict::kanjidic::parse_kanjidic_xml;
+use yomikiri_jmdict::Result;
+
+#[test]
+fn parse_kanjidic() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE kanjidic2 [
+<!ELEMENT kanjidic2 (header,character*)>
+]>
+<kanjidic2>
+<header>
+<file_version>4</file_version>
+<database_version>2024-236</database_version>
+<date_of_creation>2024-08-23</date_of_creation>
+</header>
+<!-- Entry for Kanji: 亜 -->
+<character>
+<literal>亜</literal>
+<codepoint>
+<cp_value cp_type="ucs">4e9c</cp_value>
+<cp_value cp_type="jis208">1-16-01</cp_value>
+</codepoint>
+<radical>
+<rad_value rad_type="classical">7</rad_value>
+<rad_value rad_type="nelson_c">1</rad_value>
+</radical>
+<misc>
+<grade>8</grade>
+<stroke_count>7</stroke_count>
+<stroke_count>8</stroke_count>
+<variant var_type="jis208">1-48-19</variant>
+<freq>1509</freq>
+<jlpt>1</jlpt>
+</misc>
+<dic_number>
+<dic_ref dr_type="nelson_c">43</dic_ref>
+</dic_number>
+<query_code>
+<q_code qc_type="skip">4-7-1</q_code>
+</query_code>
+<reading_meaning>
+<rmgroup>
+<reading r_type="pinyin">ya4</reading>
+<reading r_type="ja_on">ア</reading>
+<reading r_type="ja_kun">つ.ぐ</reading>
+<meaning>Asia</meaning>
+<meaning>rank next</meaning>
+<meaning m_lang="fr">Asie</meaning>
+<meaning m_lang="es">pref. para indicar</meaning>
+</rmgroup>
+<nanori>や</nanori>
+<nanori>つぎ</nanori>
+</reading_meaning>
+</character>
+<!-- Entry for Kanji: 丂 -->
+<character>
+<literal>丂</literal>
+<codepoint>
+<cp_value cp_type="ucs">4e02</cp_value>
+</codepoint>
+<radical>
+<rad_value rad_type="classical">1</rad_value>
+</radical>
+<misc>
+<stroke_count>2</stroke_count>
+</misc>
+<reading_meaning>
+<rmgroup>
+<reading r_type="pinyin">kao3</reading>
+<reading r_type="ja_on">コウ</reading>
+</rmgroup>
+</reading_meaning>
+</character>
+</kanjidic2>
+"#;
+    let result = parse_kanjidic_xml(xml.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
@@ -1,2 +1,6 @@
 mod jmdict;
 mod jmnedict;
+mod kanjidic;
+mod radical;
+mod simplified;
+mod write;
@@ -0,0 +1,39 @@
+use insta::assert_yaml_snapshot;
+use yomikiri_jmdict::radical::{parse_kradfile, parse_radkfile};
+use yomikiri_jmdict::Result;
+
+#[test]
+fn parse_radkfile_radicals() -> Result<()> {
+    let text = "\
+# RADKFILE
+#
+$ 一 1
+亜唖娃阿哀愛
+挨姶逢
+$ ｜ 1
+亜
+$ 化 2 js01
+化花
+";
+    let result = parse_radkfile(text.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn parse_kradfile_kanjis() -> Result<()> {
+    let text = "\
+# KRADFILE
+亜 : ｜ 一 口
+唖 : ｜ 一 口
+";
+    let result = parse_kradfile(text.as_bytes())?;
+    assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn radkfile_kanji_before_radical_is_error() {
+    let text = "亜唖\n$ 一 1\n";
+    assert!(parse_radkfile(text.as_bytes()).is_err());
+}
@@ -0,0 +1,101 @@
+use insta::assert_yaml_snapshot;
+use yomikiri_jmdict::jmnedict::parse_jmnedict_xml;
+use yomikiri_jmdict::simplified::{write_jmdict_simplified, write_jmnedict_simplified};
+use yomikiri_jmdict::{parse_jmdict_xml, Result};
+
+#[test]
+fn jmdict_to_simplified() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ELEMENT JMdict (entry*)>
+<!ENTITY n "noun (common) (futsuumeishi)">
+<!ENTITY adj-no "nouns which may take the genitive case particle 'no'">
+<!ENTITY ateji "ateji (phonetic) reading">
+<!ENTITY uk "word usually written using kana alone">
+<!ENTITY ksb "Kansai-ben">
+]>
+<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<k_ele>
+<keb>丸</keb>
+<ke_pri>ichi1</ke_pri>
+</k_ele>
+<k_ele>
+<keb>円</keb>
+<ke_inf>&ateji;</ke_inf>
+</k_ele>
+<r_ele>
+<reb>まる</reb>
+<re_pri>ichi1</re_pri>
+</r_ele>
+<r_ele>
+<reb>マル</reb>
+<re_nokanji/>
+</r_ele>
+<sense>
+<stagk>丸</stagk>
+<pos>&n;</pos>
+<pos>&adj-no;</pos>
+<xref>円・えん・1</xref>
+<xref>球</xref>
+<ant>四角・しかく</ant>
+<misc>&uk;</misc>
+<dial>&ksb;</dial>
+<lsource xml:lang="ger" ls_type="part">Kreis</lsource>
+<gloss>circle</gloss>
+<gloss g_type="fig">completeness</gloss>
+</sense>
+<sense>
+<gloss xml:lang="ger" g_gend="masc">Kreis</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>9999999</ent_seq>
+<r_ele>
+<reb>ＪＭｄｉｃｔ</reb>
+</r_ele>
+<sense>
+<gloss>JMdict created: 2024-08-07</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let jmdict = parse_jmdict_xml(xml.as_bytes())?;
+    let json = write_jmdict_simplified(&jmdict, Vec::new())?;
+    let value: serde_json::Value = serde_json::from_slice(&json)?;
+    assert_yaml_snapshot!(value);
+    Ok(())
+}
+
+#[test]
+fn jmnedict_to_simplified() -> Result<()> {
+    let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE JMnedict [
+<!ENTITY surname "family or surname">
+<!ENTITY place "place name">
+]>
+<JMnedict>
+<entry>
+<ent_seq>5000000</ent_seq>
+<k_ele>
+<keb>ゝ泉堂</keb>
+</k_ele>
+<r_ele>
+<reb>けいせんどう</reb>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<name_type>&place;</name_type>
+<xref>泉堂・せんどう</xref>
+<trans_det>Keisendou</trans_det>
+</trans>
+</entry>
+</JMnedict>
+"#;
+    let jmnedict = parse_jmnedict_xml(xml.as_bytes())?;
+    let json = write_jmnedict_simplified(&jmnedict, Vec::new())?;
+    let value: serde_json::Value = serde_json::from_slice(&json)?;
+    assert_yaml_snapshot!(value);
+    Ok(())
+}
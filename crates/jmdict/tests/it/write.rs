@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use insta::assert_snapshot;
+use yomikiri_jmdict::jmdict::{write_jmdict_xml, JMDictXmlWriter};
+use yomikiri_jmdict::jmnedict::{parse_jmnedict_xml, write_jmnedict_xml};
+use yomikiri_jmdict::{parse_jmdict_xml, Error, Result};
+
+const JMDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ELEMENT JMdict (entry*)>
+<!ENTITY n "noun (common) (futsuumeishi)">
+<!ENTITY vs "noun or participle which takes the aux. verb suru">
+<!ENTITY ateji "ateji (phonetic) reading">
+<!ENTITY ik "word containing irregular kana usage">
+<!ENTITY uk "word usually written using kana alone">
+<!ENTITY baseb "baseball">
+<!ENTITY ksb "Kansai-ben">
+]>
+<!-- JMdict created: 2024-08-07 -->
+<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<k_ele>
+<keb>丸</keb>
+<ke_pri>ichi1</ke_pri>
+<ke_pri>nf05</ke_pri>
+</k_ele>
+<k_ele>
+<keb>円</keb>
+<ke_inf>&ateji;</ke_inf>
+</k_ele>
+<r_ele>
+<reb>まる</reb>
+<re_pri>ichi1</re_pri>
+</r_ele>
+<r_ele>
+<reb>マル</reb>
+<re_nokanji/>
+<re_inf>&ik;</re_inf>
+</r_ele>
+<r_ele>
+<reb>えん</reb>
+<re_restr>円</re_restr>
+</r_ele>
+<sense>
+<stagk>丸</stagk>
+<stagr>まる</stagr>
+<pos>&n;</pos>
+<pos>&vs;</pos>
+<pos>&new-pos;</pos>
+<xref>丸い・まるい・1</xref>
+<xref>円</xref>
+<ant>四角・しかく</ant>
+<field>&baseb;</field>
+<misc>&uk;</misc>
+<s_inf>"quoted" &amp; escaped &lt;info&gt;</s_inf>
+<lsource xml:lang="ger" ls_type="part" ls_wasei="y">Rund</lsource>
+<lsource xml:lang="eng"></lsource>
+<dial>&ksb;</dial>
+<gloss g_type="lit" g_gend="fem">circle &amp; round</gloss>
+<gloss xml:lang="ger">Kreis</gloss>
+<gloss><pri>whole</pri></gloss>
+<example>
+<ex_srce exsrc_type="tat">75470</ex_srce>
+<ex_text>丸</ex_text>
+<ex_sent xml:lang="jpn">丸を描いた。</ex_sent>
+<ex_sent xml:lang="eng">I drew a circle.</ex_sent>
+</example>
+</sense>
+</entry>
+<entry>
+<ent_seq>9999999</ent_seq>
+<k_ele>
+<keb>ＪＭｄｉｃｔ</keb>
+</k_ele>
+<r_ele>
+<reb>ジェイエムディクト</reb>
+</r_ele>
+<sense>
+<gloss>Japanese-Multilingual Dictionary Project - Creation Date: 2024-08-07</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+const JMNEDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMnedict [
+<!ENTITY surname "family or surname">
+<!ENTITY place "place name">
+<!ENTITY rK "rarely used kanji form">
+]>
+<JMnedict>
+<entry>
+<ent_seq>5000001</ent_seq>
+<k_ele>
+<keb>佐藤</keb>
+<ke_inf>&rK;</ke_inf>
+<ke_pri>spec1</ke_pri>
+</k_ele>
+<r_ele>
+<reb>さとう</reb>
+<re_restr>佐藤</re_restr>
+<re_pri>spec1</re_pri>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<name_type>&place;</name_type>
+<xref>佐藤・さとう</xref>
+<trans_det>Satou</trans_det>
+<trans_det>Sato</trans_det>
+</trans>
+</entry>
+</JMnedict>
+"#;
+
+#[test]
+fn jmdict_round_trip() -> Result<()> {
+    let mut jmdict = parse_jmdict_xml(JMDICT.as_bytes())?;
+    let written = write_jmdict_xml(&jmdict, vec![])?;
+    let reparsed = parse_jmdict_xml(written.as_slice())?;
+    // unknown entity that was not declared is declared in written xml
+    jmdict
+        .entities
+        .insert("new-pos".to_string(), "new-pos".to_string());
+    assert_eq!(jmdict, reparsed);
+    assert_snapshot!(String::from_utf8(written).unwrap());
+    Ok(())
+}
+
+#[test]
+fn jmnedict_round_trip() -> Result<()> {
+    let jmnedict = parse_jmnedict_xml(JMNEDICT.as_bytes())?;
+    let written = write_jmnedict_xml(&jmnedict, vec![])?;
+    let reparsed = parse_jmnedict_xml(written.as_slice())?;
+    assert_eq!(jmnedict, reparsed);
+    assert_snapshot!(String::from_utf8(written).unwrap());
+    Ok(())
+}
+
+const UNKNOWN_ENTITY_JMDICT: &str = r#"<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele>
+<reb>ヽ</reb>
+</r_ele>
+<sense>
+<pos>&new-pos;</pos>
+<misc>&new-misc;</misc>
+<gloss>repetition mark in katakana</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+#[test]
+fn writer_rejects_undeclared_unknown_entity() -> Result<()> {
+    let jmdict = parse_jmdict_xml(UNKNOWN_ENTITY_JMDICT.as_bytes())?;
+    let mut writer = JMDictXmlWriter::new(vec![], &BTreeMap::new(), None)?;
+    let Err(err) = writer.write_entry(&jmdict.entries[0]) else {
+        panic!("expected undeclared entity error");
+    };
+    assert!(matches!(
+        err,
+        Error::UndeclaredEntity {
+            ent_seq: 1000000,
+            ..
+        }
+    ));
+    Ok(())
+}
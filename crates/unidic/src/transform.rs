@@ -7,7 +7,7 @@ use std::path::Path;
 use std::str::FromStr;
 use yomikiri_dictionary::dictionary::Dictionary;
 use yomikiri_dictionary::entry::{PartOfSpeech, Rarity, WordEntry};
-use yomikiri_dictionary::DICT_FILENAME;
+use yomikiri_dictionary::{dict_filename, DEFAULT_LANGUAGE};
 use yomikiri_unidic_types::{UnidicConjugationForm, UnidicPos};
 
 struct LexItem {
@@ -157,7 +157,7 @@ pub fn transform(input_dir: &Path, transform_dir: &Path, resource_dir: &Path) ->
     transform_lex(
         &lex_csv_path,
         transform_dir,
-        &resource_dir.join(DICT_FILENAME),
+        &resource_dir.join(dict_filename(&[DEFAULT_LANGUAGE])),
     )?;
 
     // transform_matrix(&matrix_def_path, transform_dir, lid_map, rid_map)?;
@@ -12,17 +12,18 @@ use yomikiri_rs::dictionary::Dictionary;
 use yomikiri_rs::tokenize::create_tokenizer;
 use yomikiri_rs::SharedBackend;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use flate2::bufread::GzDecoder;
 use memmap2::{Mmap, MmapOptions};
 use serde_json;
 use uniffi;
 use ureq;
-use yomikiri_dictionary::dictionary::DictionaryWriter;
-use yomikiri_dictionary::{DICT_FILENAME, SCHEMA_VER};
+use yomikiri_dictionary::dictionary::{Dictionary as InnerDictionary, DictionaryWriter};
+use yomikiri_dictionary::spill::SpillLocation;
+use yomikiri_dictionary::{dict_filename, DEFAULT_LANGUAGE, SCHEMA_VER};
 
 use fs_err::{self as fs, File};
-use std::io::BufReader;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
@@ -208,10 +209,14 @@ fn create_dictionary(dir: String) -> Result<()> {
     let dir = PathBuf::from(dir);
     let jmdict_path = dir.join("JMdict_e.gz");
     let jmnedict_path = dir.join("JMnedict.xml.gz");
-    let temp_dict_path = dir.join(format!("{}.temp", DICT_FILENAME));
-    let dict_path = dir.join(DICT_FILENAME);
+    let languages = vec![DEFAULT_LANGUAGE.to_string()];
+    let filename = dict_filename(&languages);
+    let temp_dict_path = dir.join(format!("{}.temp", filename));
+    let dict_path = dir.join(filename);
 
-    let writer = DictionaryWriter::new();
+    // entries are spilled to disk to keep memory low on mobile devices
+    let writer =
+        DictionaryWriter::with_languages(languages).spill_to(SpillLocation::Directory(dir.clone()));
 
     let jmdict_reader = decode_gzip_xml(&jmdict_path)?;
     let writer = writer
@@ -222,17 +227,36 @@ fn create_dictionary(dir: String) -> Result<()> {
     let writer = writer
         .read_jmnedict(jmnedict_reader)
         .context("Failed to parse JMneDict xml file")?;
+    if !writer.warnings().is_empty() {
+        log::warn!(
+            "Created dictionary with {} parser warnings",
+            writer.warnings().len()
+        );
+    }
 
-    let mut temp_dict_file = File::create(&temp_dict_path)?;
+    let mut temp_dict_file = BufWriter::new(File::create(&temp_dict_path)?);
     writer
         .write(&mut temp_dict_file)
         .context("Failed to write dictionary file")?;
+    temp_dict_file.flush()?;
     std::mem::drop(temp_dict_file);
+    verify_dictionary_file(&temp_dict_path).context("Created dictionary file is invalid")?;
     fs::rename(&temp_dict_path, &dict_path)?;
 
     Ok(())
 }
 
+fn verify_dictionary_file(path: &Path) -> Result<()> {
+    let file = File::open(path)?;
+    let mmap = unsafe { MmapOptions::new().map(&file)? };
+    let dictionary = InnerDictionary::try_decode(mmap)?;
+    let report = dictionary.borrow_view().verify();
+    if !report.is_ok() {
+        return Err(anyhow!("{}", report));
+    }
+    Ok(())
+}
+
 fn decode_gzip_xml(path: &Path) -> Result<BufReader<GzDecoder<BufReader<File>>>> {
     let file = File::open(path)?;
     let gzip_reader = BufReader::new(file);
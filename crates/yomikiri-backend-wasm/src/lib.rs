@@ -11,7 +11,7 @@ use anyhow::Context;
 use flate2::bufread::GzDecoder;
 use js_sys::Uint8Array;
 use log::debug;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor};
 use wasm_bindgen::prelude::*;
 use yomikiri_dictionary::dictionary::DictionaryWriter;
 use yomikiri_dictionary::SCHEMA_VER;
@@ -58,6 +58,9 @@ impl Backend {
         gzip_jmdict: &Uint8Array,
         gzip_jmnedict: &Uint8Array,
     ) -> WasmResult<DictUpdateResult> {
+        // wasm has no file system to spill to, so entries and index keys are kept
+        // encoded in memory until dictionary is written. Memory is not bounded,
+        // but only one gzipped source and the encoded items are alive at a time.
         let writer = DictionaryWriter::new();
         let gzip_jmdict = gzip_jmdict.to_vec();
         debug!("will parse jmdict file");
@@ -66,6 +69,7 @@ impl Backend {
         let writer = writer
             .read_jmdict(jmdict_reader)
             .context("Failed to parse JMDict xml file")?;
+        drop(gzip_jmdict);
         debug!("parsed jmdict file");
 
         let gzip_jmnedict = gzip_jmnedict.to_vec();
@@ -75,12 +79,15 @@ impl Backend {
         let writer = writer
             .read_jmnedict(jmnedict_reader)
             .context("Failed to parse JMneDict xml file")?;
+        drop(gzip_jmnedict);
         debug!("parsed jmnedict file");
 
-        let mut dict_bytes: Vec<u8> = Vec::with_capacity(84 * 1024 * 1024);
+        // output grows as it is written instead of reserving for the largest dictionary
+        let mut dict_bytes = Cursor::new(Vec::new());
         writer
             .write(&mut dict_bytes)
             .context("Failed to write dictionary file")?;
+        let dict_bytes = dict_bytes.into_inner();
         debug!("built dictionary file");
 
         let dict_array = Uint8Array::from(&dict_bytes[..]);
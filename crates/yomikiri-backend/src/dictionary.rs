@@ -3,6 +3,9 @@ use std::cmp::Ordering;
 use anyhow::{Context, Result};
 use yomikiri_dictionary::dictionary::{Dictionary as InnerDictionary, DictionaryMetadata};
 use yomikiri_dictionary::entry::{Entry, Rarity};
+use yomikiri_dictionary::fuzzy::FuzzySearchItem;
+use yomikiri_dictionary::radical::RadicalSearchResult;
+use yomikiri_dictionary::term_rank::TermSearchItem;
 use yomikiri_dictionary::PartOfSpeech;
 use yomikiri_unidic_types::UnidicPos;
 
@@ -33,6 +36,45 @@ impl<D: AsRef<[u8]> + 'static> Dictionary<D> {
         Ok(entries)
     }
 
+    /// Returns entries with terms within edit distance `max_distance` of `term`, closest first
+    pub fn search_term_fuzzy(&self, term: &str, max_distance: u32) -> Result<Vec<FuzzySearchItem>> {
+        let view = self.inner.borrow_view();
+        let result = view.search_term_fuzzy(term, max_distance)?;
+        Ok(result)
+    }
+
+    /// Returns entries with meanings within edit distance `max_distance` of `query`, closest first
+    pub fn search_meaning_fuzzy(
+        &self,
+        query: &str,
+        max_distance: u32,
+    ) -> Result<Vec<FuzzySearchItem>> {
+        let view = self.inner.borrow_view();
+        let result = view.search_meaning_fuzzy(query, max_distance)?;
+        Ok(result)
+    }
+
+    /// Finds kanji that contain all `radicals`, and words that contain those kanji
+    pub fn search_radicals(&self, radicals: &[String]) -> Result<RadicalSearchResult> {
+        let view = self.inner.borrow_view();
+        let result = view.search_radicals(radicals)?;
+        Ok(result)
+    }
+
+    /// Returns up to `limit` terms that start with `prefix` with their entries, ranked by priority
+    pub fn search_prefix(&self, prefix: &str, limit: usize) -> Result<Vec<TermSearchItem>> {
+        let view = self.inner.borrow_view();
+        let result = view.search_prefix(prefix, limit)?;
+        Ok(result)
+    }
+
+    /// Returns up to `limit` terms that match wildcard `pattern` with their entries, ranked by priority
+    pub fn search_wildcard(&self, pattern: &str, limit: usize) -> Result<Vec<TermSearchItem>> {
+        let view = self.inner.borrow_view();
+        let result = view.search_wildcard(pattern, limit)?;
+        Ok(result)
+    }
+
     /// Returns true only if there is a dictionary term
     /// that starts with `prefix` and is not `prefix`
     pub fn has_starts_with_excluding(&self, prefix: &str) -> bool {
@@ -65,12 +107,15 @@ impl<D: AsRef<[u8]> + 'static> Dictionary<D> {
     /// 3. If POS is proper noun, prioritize proper noun
     /// 4. Entries whose POS matches token.pos
     /// 5. Rare -> Non rare
-    /// 6. Entry with higher priority is shown first
+    /// 6. Entry whose matched form has higher priority is shown first
+    /// 7. Entry with higher priority is shown first
     pub(crate) fn search_for_token(&self, token: &InnerToken) -> Result<Vec<Entry>> {
         struct EntryMeta {
             entry: Entry,
             rarity: Rarity,
             from_base: bool,
+            /// priority of the matched form
+            priority: u16,
         }
 
         // word entry metas
@@ -81,19 +126,23 @@ impl<D: AsRef<[u8]> + 'static> Dictionary<D> {
             match entry {
                 Entry::Word(inner) => {
                     let rarity = inner.term_rarity(&token.base)?;
+                    let priority = inner.term_priority(&token.base);
                     let entry_meta = EntryMeta {
                         entry: inner.into(),
                         rarity,
                         from_base: true,
+                        priority,
                     };
                     entry_metas.push(entry_meta);
                 }
                 Entry::Name(inner) => {
                     let rarity = Rarity::Normal;
+                    let priority = inner.priority();
                     let entry_meta = EntryMeta {
                         entry: inner.into(),
                         rarity,
                         from_base: true,
+                        priority,
                     };
                     entry_metas.push(entry_meta);
                 }
@@ -111,10 +160,12 @@ impl<D: AsRef<[u8]> + 'static> Dictionary<D> {
                         continue;
                     }
                     let rarity = inner.term_rarity(&token.text)?;
+                    let priority = inner.term_priority(&token.text);
                     let entry_meta = EntryMeta {
                         entry: inner.into(),
                         rarity,
                         from_base: false,
+                        priority,
                     };
                     entry_metas.push(entry_meta);
                 }
@@ -126,10 +177,12 @@ impl<D: AsRef<[u8]> + 'static> Dictionary<D> {
                         continue;
                     }
                     let rarity = Rarity::Normal;
+                    let priority = inner.priority();
                     let entry_meta = EntryMeta {
                         entry: inner.into(),
                         rarity,
                         from_base: false,
+                        priority,
                     };
                     entry_metas.push(entry_meta);
                 }
@@ -161,6 +214,7 @@ impl<D: AsRef<[u8]> + 'static> Dictionary<D> {
                     let b_is_normal = a.rarity == Rarity::Normal;
                     a_is_normal.cmp(&b_is_normal).reverse()
                 })
+                .then(a.priority.cmp(&b.priority).reverse())
                 .then(a.entry.priority().cmp(&b.entry.priority()).reverse())
         });
 
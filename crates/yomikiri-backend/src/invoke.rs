@@ -4,6 +4,12 @@ use anyhow::Result;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use yomikiri_dictionary::dictionary::DictionaryMetadata;
+use yomikiri_dictionary::term_rank::TermSearchItem;
+
+/// Maximum number of terms returned by autocomplete, regardless of requested limit
+const MAX_AUTOCOMPLETE_LIMIT: usize = 200;
+/// Maximum number of terms returned by wildcard search, regardless of requested limit
+const MAX_WILDCARD_SEARCH_LIMIT: usize = 200;
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", content = "args")]
@@ -11,6 +17,8 @@ enum Command {
     Search(SearchArgs),
     Tokenize(TokenizeArgs),
     DictionaryMetadata(()),
+    Autocomplete(AutocompleteArgs),
+    WildcardSearch(WildcardSearchArgs),
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -19,6 +27,8 @@ pub enum CommandResultSpec {
     Search(TokenizeResult),
     Tokenize(TokenizeResult),
     DictionaryMetadata(DictionaryMetadata),
+    Autocomplete(Vec<TermSearchItem>),
+    WildcardSearch(Vec<TermSearchItem>),
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -33,6 +43,22 @@ struct TokenizeArgs {
     char_idx: usize,
 }
 
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+struct AutocompleteArgs {
+    prefix: String,
+    /// Maximum number of terms returned, up to 200
+    limit: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+struct WildcardSearchArgs {
+    /// `?` or `〇` matches a single character, and `*` matches zero or more characters.
+    /// Pattern that starts with `*` only searches part of the dictionary.
+    pattern: String,
+    /// Maximum number of terms returned, up to 200
+    limit: usize,
+}
+
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct TypeBindingExports {
     command: Command,
@@ -53,6 +79,14 @@ impl<D: AsRef<[u8]> + 'static> SharedBackend<D> {
                 serde_json::to_string(&self.tokenize(&args.sentence, args.char_idx)?)?
             }
             DictionaryMetadata(_) => serde_json::to_string(self.dictionary.metadata())?,
+            Autocomplete(args) => {
+                let limit = args.limit.min(MAX_AUTOCOMPLETE_LIMIT);
+                serde_json::to_string(&self.dictionary.search_prefix(&args.prefix, limit)?)?
+            }
+            WildcardSearch(args) => {
+                let limit = args.limit.min(MAX_WILDCARD_SEARCH_LIMIT);
+                serde_json::to_string(&self.dictionary.search_wildcard(&args.pattern, limit)?)?
+            }
         };
         Ok(json)
     }
@@ -1,19 +1,26 @@
 use std::borrow::Cow;
 
 use anyhow::Result;
-use japanese_utils::JapaneseChar;
+use japanese_utils::romaji::romaji_to_hiragana;
+use japanese_utils::{JapaneseChar, JapaneseString};
 use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use yomikiri_dictionary::entry::Entry;
+use yomikiri_dictionary::fuzzy::FuzzySearchItem;
 
-use crate::tokenize::{InnerToken, Token, TokenDetails, TokenizeResult};
+use crate::tokenize::{EntrySource, InnerToken, RomajiSearch, Token, TokenDetails, TokenizeResult};
 use crate::SharedBackend;
 
 /// Maximum number of entries returned when searching for meaning
 const MAX_ENTRY_LEN: usize = 100;
+/// Maximum edit distance of similar terms searched when query does not match any entry
+const FUZZY_TERM_DISTANCE: u32 = 1;
+/// Maximum edit distance of similar meanings searched when query does not match any entry
+const FUZZY_MEANING_DISTANCE: u32 = 2;
 
 impl<D: AsRef<[u8]> + 'static> SharedBackend<D> {
     pub fn search(&self, query: &str, char_idx: usize) -> Result<TokenizeResult> {
         if query.chars().any(|c| c.is_japanese_content()) {
-            let result = self.tokenize(query, char_idx)?;
+            let mut result = self.tokenize(query, char_idx)?;
 
             // if tokenize separates the term into multiple tokens,
             // but term exists as-is in dictionary, return that instead.
@@ -23,24 +30,44 @@ impl<D: AsRef<[u8]> + 'static> SharedBackend<D> {
                 }
             }
 
+            if result.entries.is_empty() {
+                let items = self
+                    .dictionary
+                    .search_term_fuzzy(&nfc_normalized(query), FUZZY_TERM_DISTANCE)?;
+                (result.entries, result.suggestions) = fuzzy_entries(items);
+                // entries are not of selected token, and must not be filtered by its surface
+                if !result.entries.is_empty() {
+                    result.tokenIdx = -1;
+                }
+            }
             Ok(result)
         } else {
-            let mut entries = self.dictionary.search_meaning(query)?;
-            if entries.len() > 100 {
-                entries = entries.into_iter().take(MAX_ENTRY_LEN).collect();
+            let result = match romaji_to_hiragana(query) {
+                Some(hiragana) => self.search_romaji(query, hiragana)?,
+                None => self.search_meaning(query)?,
+            };
+            if result.entries.is_empty() {
+                let items = self
+                    .dictionary
+                    .search_meaning_fuzzy(query, FUZZY_MEANING_DISTANCE)?;
+                let (entries, suggestions) = fuzzy_entries(items);
+                return Ok(TokenizeResult {
+                    suggestions,
+                    ..TokenizeResult::with_entries(entries)
+                });
             }
-            Ok(TokenizeResult::with_entries(entries))
+            Ok(result)
         }
     }
 
-    fn search_term_as_is(&self, term: &str) -> Result<Option<TokenizeResult>> {
-        let normalized_term = if is_nfc_quick(term.chars()) == IsNormalized::Yes {
-            Cow::Borrowed(term)
-        } else {
-            let normalized = term.nfc().collect::<String>();
-            Cow::Owned(normalized)
-        };
+    fn search_meaning(&self, query: &str) -> Result<TokenizeResult> {
+        let mut entries = self.dictionary.search_meaning(query)?;
+        entries.truncate(MAX_ENTRY_LEN);
+        Ok(TokenizeResult::with_entries(entries))
+    }
 
+    fn search_term_as_is(&self, term: &str) -> Result<Option<TokenizeResult>> {
+        let normalized_term = nfc_normalized(term);
         let entries = self.dictionary.search_term(&normalized_term)?;
         if let Some(entry) = entries.first() {
             let form = entry.main_form();
@@ -56,9 +83,74 @@ impl<D: AsRef<[u8]> + 'static> SharedBackend<D> {
                 tokenIdx: 0,
                 entries,
                 grammars: vec![],
+                suggestions: vec![],
+                romaji: None,
             }))
         } else {
             Ok(None)
         }
     }
+
+    /// Searches kana converted from romaji `query` as a term, and `query` as a meaning.
+    ///
+    /// Entries found by both are returned first, then entries found as term, then as meaning.
+    /// If kana is not a term, `query` is searched only as a meaning and `romaji` is not set.
+    fn search_romaji(&self, query: &str, hiragana: String) -> Result<TokenizeResult> {
+        let mut kana = hiragana;
+        let mut term_entries = self.dictionary.search_term(&kana)?;
+        // loanwords are written in katakana
+        if term_entries.is_empty() {
+            kana = kana.to_katakana();
+            term_entries = self.dictionary.search_term(&kana)?;
+        }
+        if term_entries.is_empty() {
+            return self.search_meaning(query);
+        }
+        let term_len = term_entries.len();
+        let mut found: Vec<(Entry, EntrySource)> = term_entries
+            .into_iter()
+            .map(|entry| (entry, EntrySource::Term))
+            .collect();
+        for entry in self.dictionary.search_meaning(query)? {
+            match found[..term_len].iter_mut().find(|(e, _)| *e == entry) {
+                Some((_, source)) => *source = EntrySource::Both,
+                None => found.push((entry, EntrySource::Meaning)),
+            }
+        }
+        // stable sort keeps order of entries found from the same source
+        found.sort_by_key(|(_, source)| *source);
+        found.truncate(MAX_ENTRY_LEN);
+
+        let (entries, sources) = found.into_iter().unzip();
+        Ok(TokenizeResult {
+            romaji: Some(RomajiSearch { kana, sources }),
+            ..TokenizeResult::with_entries(entries)
+        })
+    }
+}
+
+fn nfc_normalized(term: &str) -> Cow<'_, str> {
+    if is_nfc_quick(term.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(term)
+    } else {
+        let normalized = term.nfc().collect::<String>();
+        Cow::Owned(normalized)
+    }
+}
+
+/// Returns up to `MAX_ENTRY_LEN` entries found by fuzzy search,
+/// and unique terms or meanings they were found with, closest first
+fn fuzzy_entries(items: Vec<FuzzySearchItem>) -> (Vec<Entry>, Vec<String>) {
+    let mut suggestions: Vec<String> = vec![];
+    let entries = items
+        .into_iter()
+        .take(MAX_ENTRY_LEN)
+        .map(|item| {
+            if !suggestions.contains(&item.matched) {
+                suggestions.push(item.matched);
+            }
+            item.entry
+        })
+        .collect();
+    (entries, suggestions)
 }
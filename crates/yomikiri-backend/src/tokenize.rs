@@ -84,12 +84,40 @@ pub struct TokenizeResult {
     pub tokens: Vec<Token>,
     /// selected token index
     ///
-    /// may be -1 if there are no tokens
+    /// may be -1 if there are no tokens,
+    /// or if `entries` are not of selected token but similar to it (`suggestions` is not empty)
     pub tokenIdx: i32,
     /// DicEntry JSONs returned by lindera tokenizer
     /// searched with base and surface of selected token
     pub entries: Vec<Entry>,
     pub grammars: Vec<GrammarInfo>,
+    /// Terms or meanings that `entries` were found with,
+    /// when search query did not match any entry exactly and similar entries are returned instead.
+    ///
+    /// Empty if `entries` match search query.
+    pub suggestions: Vec<String>,
+    /// Set if search query is romaji, and kana converted from it is a term in dictionary
+    pub romaji: Option<RomajiSearch>,
+}
+
+/// Search of romaji query, both as a term converted to kana and as a meaning
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RomajiSearch {
+    /// Kana that search query was converted to and searched as a term
+    pub kana: String,
+    /// How each entry in `entries` was found, in the same order
+    pub sources: Vec<EntrySource>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EntrySource {
+    /// Found both as kana term and as meaning
+    Both,
+    /// Found as kana term
+    Term,
+    /// Found as meaning
+    Meaning,
 }
 
 impl TokenizeResult {
@@ -234,6 +262,8 @@ impl<D: AsRef<[u8]> + 'static> SharedBackend<D> {
                 .with_context(|| format!("Failed to convert token_idx as u32: {}", token_idx))?,
             entries,
             grammars,
+            suggestions: vec![],
+            romaji: None,
         })
     }
 
@@ -1,3 +1,4 @@
 pub mod common;
 mod grammar;
+mod search;
 mod tokenize;
@@ -0,0 +1,123 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use yomikiri_dictionary::dictionary::DictionaryWriter;
+use yomikiri_dictionary::entry::Entry;
+use yomikiri_rs::dictionary::Dictionary;
+use yomikiri_rs::tokenize::{create_tokenizer, EntrySource};
+use yomikiri_rs::SharedBackend;
+
+const JMDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ENTITY n "noun (common) (futsuumeishi)">
+<!ENTITY int "interjection (kandoushi)">
+]>
+<JMdict>
+<entry>
+<ent_seq>1383240</ent_seq>
+<k_ele>
+<keb>鮭</keb>
+</k_ele>
+<r_ele>
+<reb>さけ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>salmon</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1293810</ent_seq>
+<k_ele>
+<keb>酒</keb>
+</k_ele>
+<r_ele>
+<reb>さけ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>alcohol</gloss>
+<gloss>sake</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1465000</ent_seq>
+<k_ele>
+<keb>日本酒</keb>
+</k_ele>
+<r_ele>
+<reb>にほんしゅ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>sake</gloss>
+<gloss>Japanese rice wine</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1289400</ent_seq>
+<r_ele>
+<reb>こんにちは</reb>
+</r_ele>
+<sense>
+<pos>&int;</pos>
+<gloss>hello</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+fn setup_backend() -> Result<SharedBackend<Vec<u8>>> {
+    let writer = DictionaryWriter::new()
+        .read_jmdict(JMDICT.as_bytes())?
+        .read_jmnedict("<JMnedict></JMnedict>".as_bytes())?;
+    let mut bytes = vec![];
+    writer.write(&mut Cursor::new(&mut bytes))?;
+    Ok(SharedBackend {
+        tokenizer: create_tokenizer(),
+        dictionary: Dictionary::try_new(bytes)?,
+    })
+}
+
+fn entry_ids(entries: &[Entry]) -> Vec<u32> {
+    entries
+        .iter()
+        .map(|entry| match entry {
+            Entry::Word(word) => word.id,
+            Entry::Name(_) => panic!("expected word entry"),
+        })
+        .collect()
+}
+
+#[test]
+fn romaji_search_merges_term_and_meaning_entries() -> Result<()> {
+    let backend = setup_backend()?;
+    let result = backend.search("sake", 0)?;
+
+    // 酒 is found both as さけ and as meaning 'sake', 鮭 only as さけ,
+    // and 日本酒 only as meaning 'sake'
+    assert_eq!(entry_ids(&result.entries), [1293810, 1383240, 1465000]);
+    let romaji = result.romaji.expect("query is romaji");
+    assert_eq!(romaji.kana, "さけ");
+    assert_eq!(
+        romaji.sources,
+        [EntrySource::Both, EntrySource::Term, EntrySource::Meaning]
+    );
+    Ok(())
+}
+
+#[test]
+fn romaji_search_without_kana_term() -> Result<()> {
+    let backend = setup_backend()?;
+
+    // 'hello' is not romaji
+    let result = backend.search("hello", 0)?;
+    assert_eq!(entry_ids(&result.entries), [1289400]);
+    assert!(result.romaji.is_none());
+
+    // 'sakana' is romaji, but さかな is not in dictionary
+    let result = backend.search("sakana", 0)?;
+    assert!(result.entries.is_empty());
+    assert!(result.romaji.is_none());
+    Ok(())
+}
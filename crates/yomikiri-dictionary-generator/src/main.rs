@@ -1,4 +1,4 @@
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Context, Result};
@@ -6,7 +6,13 @@ use clap::{Args, Parser, Subcommand};
 use flate2::read::GzDecoder;
 use fs_err::{self as fs, File};
 use tempfile::NamedTempFile;
-use yomikiri_dictionary::dictionary::DictionaryWriter;
+use yomikiri_dictionary::dictionary::{Dictionary, DictionaryWriter};
+use yomikiri_dictionary::diff::{diff_entries, DiffEntry};
+use yomikiri_dictionary::{dict_filename, DEFAULT_LANGUAGE};
+use yomikiri_jmdict::simplified::{
+    JMDictSimplifiedWriter, JMneDictSimplifiedWriter, JMDICT_SIMPLIFIED_VERSION,
+};
+use yomikiri_jmdict::{JMDictParser, JMneDictParser};
 
 const GH_DICT_TAG: &str = "jmdict-2025-01-04";
 
@@ -28,6 +34,20 @@ const JMNEDICT_FILE_META: RawFileMeta = RawFileMeta {
     out_filename: "jmnedict.xml",
 };
 
+/// JMdict file with glosses in all languages
+const JMDICT_MULTILINGUAL_FILE_META: RawFileMeta = RawFileMeta {
+    source_filename: "JMdict.gz",
+    source_url: "http://ftp.edrdg.org/pub/Nihongo/JMdict.gz",
+    out_filename: "jmdict.xml",
+};
+
+/// English JMdict file with example sentences from Tatoeba project
+const JMDICT_EXAMPLES_FILE_META: RawFileMeta = RawFileMeta {
+    source_filename: "JMdict_e_examp.gz",
+    source_url: "http://ftp.edrdg.org/pub/Nihongo/JMdict_e_examp.gz",
+    out_filename: "jmdict_english_examples.xml",
+};
+
 const RAW_FILE_METAS: [RawFileMeta; 2] = [JMDICT_FILE_META, JMNEDICT_FILE_META];
 
 #[derive(Parser, Debug)]
@@ -42,6 +62,12 @@ enum Commands {
     Download(DownloadOpts),
     /// Generate dictionary file from jmdict file
     Generate(GenerateOpts),
+    /// Export jmdict files as jmdict-simplified JSON files
+    ExportJson(ExportJsonOpts),
+    /// Compare entries of two jmdict files, or two yomikiri dictionary files
+    Diff(DiffOpts),
+    /// Check that indexes and entries of yomikiri dictionary file are consistent
+    Verify(VerifyOpts),
 }
 
 #[derive(Args, Debug)]
@@ -55,6 +81,14 @@ struct DownloadOpts {
     /// Force download jmdict file even if file exists at output path
     #[arg(short, long, default_value_t = false)]
     force: bool,
+    /// Also download multilingual jmdict file from source website (edrdg.org).
+    /// It is not stored in yomikiri repo releases.
+    #[arg(long, default_value_t = false)]
+    multilingual: bool,
+    /// Also download English jmdict file with example sentences from source website (edrdg.org).
+    /// It is not stored in yomikiri repo releases.
+    #[arg(long, default_value_t = false)]
+    examples: bool,
 }
 
 #[derive(Args, Debug)]
@@ -73,12 +107,69 @@ struct GenerateOpts {
     /// Path to directory that contains jmdict files
     #[arg(long)]
     rawdir: PathBuf,
-    /// Output path to yomikiri dictionary file
+    /// Output path to yomikiri dictionary file.
+    /// If it is a directory, file is named after languages, e.g. 'german-english.yomikiridict'
     #[arg(short, long)]
     out: PathBuf,
     /// Skip if dictionary file already exist at output path
     #[arg(short, long, default_value_t = false)]
     skip_exist: bool,
+    /// ISO 639-2 code of gloss language to include (e.g. 'ger'). Can be repeated.
+    /// The first language is the primary language used in meaning search.
+    /// Multilingual jmdict file is used if any language other than 'eng' is given.
+    #[arg(long = "lang", default_values_t = [DEFAULT_LANGUAGE.to_string()])]
+    languages: Vec<String>,
+    /// Path to UTF-8 encoded RADKFILE used to build radical index.
+    /// Original EUC-JP file can be converted with `iconv -f EUC-JP -t UTF-8`.
+    #[arg(long)]
+    radkfile: Option<PathBuf>,
+    /// Use English jmdict file with example sentences, and include the sentences in dictionary.
+    /// Cannot be used with languages other than 'eng'.
+    #[arg(long, default_value_t = false)]
+    examples: bool,
+    /// Path to JMdict patch file with entries to add, modify or delete. Can be repeated.
+    /// Patch files are applied in the given order.
+    #[arg(long = "patch")]
+    patches: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ExportJsonOpts {
+    /// Path to directory that contains jmdict files
+    #[arg(long)]
+    rawdir: PathBuf,
+    /// Output directory path to JSON files
+    #[arg(long)]
+    outdir: PathBuf,
+    /// Export multilingual jmdict file instead of English jmdict file
+    #[arg(long, default_value_t = false)]
+    multilingual: bool,
+}
+
+#[derive(Args, Debug)]
+struct DiffOpts {
+    /// Path to old JMdict or JMnedict xml file, or yomikiri dictionary file
+    #[arg(long)]
+    old: PathBuf,
+    /// Path to new file of the same kind as `old`
+    #[arg(long)]
+    new: PathBuf,
+    /// Compare xml files as JMnedict files
+    #[arg(long, default_value_t = false)]
+    jmnedict: bool,
+    /// Print diff as JSON instead of human-readable text
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+#[derive(Args, Debug)]
+struct VerifyOpts {
+    /// Path to yomikiri dictionary file
+    #[arg(long)]
+    dict: PathBuf,
+    /// Print report as JSON instead of human-readable text
+    #[arg(long, default_value_t = false)]
+    json: bool,
 }
 
 fn main() -> Result<()> {
@@ -88,6 +179,9 @@ fn main() -> Result<()> {
     match &cli.command {
         Commands::Download(opts) => run_download(opts),
         Commands::Generate(opts) => run_generate(opts),
+        Commands::ExportJson(opts) => run_export_json(opts),
+        Commands::Diff(opts) => run_diff(opts),
+        Commands::Verify(opts) => run_verify(opts),
     }
 }
 
@@ -96,7 +190,7 @@ fn run_download(opts: &DownloadOpts) -> Result<()> {
 
     fs::create_dir_all(&output_dir)?;
 
-    for meta in RAW_FILE_METAS {
+    for meta in &RAW_FILE_METAS {
         let output_path = output_dir.join(meta.out_filename);
         if output_path.try_exists()? {
             if !opts.force {
@@ -119,12 +213,37 @@ fn run_download(opts: &DownloadOpts) -> Result<()> {
         }
         println!("Downloaded file '{}'", &meta.out_filename);
     }
+
+    let mut source_only_metas = vec![];
+    if opts.multilingual {
+        source_only_metas.push(&JMDICT_MULTILINGUAL_FILE_META);
+    }
+    if opts.examples {
+        source_only_metas.push(&JMDICT_EXAMPLES_FILE_META);
+    }
+    for meta in source_only_metas {
+        let output_path = output_dir.join(meta.out_filename);
+        if output_path.try_exists()? && !opts.force {
+            println!("Skipped: '{}' already exists.", &meta.out_filename);
+        } else {
+            download_dict(meta.source_url, &output_path)?;
+            println!("Downloaded file '{}'", &meta.out_filename);
+        }
+    }
     Ok(())
 }
 
+fn output_path(opts: &GenerateOpts) -> PathBuf {
+    if opts.out.is_dir() {
+        opts.out.join(dict_filename(&opts.languages))
+    } else {
+        opts.out.clone()
+    }
+}
+
 fn run_generate(opts: &GenerateOpts) -> Result<()> {
     let rawdir_path = &opts.rawdir;
-    let output_path = &opts.out;
+    let output_path = &output_path(opts);
 
     let output_dir = output_path
         .parent()
@@ -135,37 +254,195 @@ fn run_generate(opts: &GenerateOpts) -> Result<()> {
         return Ok(());
     }
 
-    let jmdict_file_path = rawdir_path.join(JMDICT_FILE_META.out_filename);
+    let english_only = opts.languages.iter().all(|l| l == DEFAULT_LANGUAGE);
+    let jmdict_file_meta = if opts.examples {
+        if !english_only {
+            return Err(anyhow!(
+                "Example sentences are only available in English jmdict file"
+            ));
+        }
+        &JMDICT_EXAMPLES_FILE_META
+    } else if english_only {
+        &JMDICT_FILE_META
+    } else {
+        &JMDICT_MULTILINGUAL_FILE_META
+    };
+    let jmdict_file_path = rawdir_path.join(jmdict_file_meta.out_filename);
     let jmnedict_file_path = rawdir_path.join(JMNEDICT_FILE_META.out_filename);
     if !jmdict_file_path.exists() {
-        return Err(anyhow!("Jmdict file does not exist"));
+        return Err(anyhow!(
+            "Jmdict file '{}' does not exist",
+            jmdict_file_meta.out_filename
+        ));
     }
     if !jmnedict_file_path.exists() {
         return Err(anyhow!("JMnedict file does not exist"));
     }
 
-    let writer = DictionaryWriter::new();
+    let writer = DictionaryWriter::with_languages(opts.languages.clone());
 
     println!("Parsing JMDict xml file...",);
     let jmdict_file = File::open(&jmdict_file_path)?;
     let jmdict_reader = BufReader::new(jmdict_file);
-    let writer = writer.read_jmdict(jmdict_reader)?;
+    let mut writer = writer.read_jmdict(jmdict_reader)?;
+
+    for patch_path in &opts.patches {
+        println!("Applying JMDict patch file {}...", patch_path.display());
+        let patch_file = File::open(patch_path)?;
+        writer = writer.apply_jmdict_patch(BufReader::new(patch_file))?;
+    }
 
     println!("Parsing JMneDict xml file...");
     let jmnedict_file = File::open(&jmnedict_file_path)?;
     let jmnedict_reader = BufReader::new(jmnedict_file);
-    let writer = writer.read_jmnedict(jmnedict_reader)?;
+    let mut writer = writer.read_jmnedict(jmnedict_reader)?;
+
+    if let Some(radkfile_path) = &opts.radkfile {
+        println!("Parsing RADKFILE...");
+        let radkfile = File::open(radkfile_path)?;
+        writer = writer.read_radkfile(BufReader::new(radkfile))?;
+    }
 
     println!("Creating index and writing yomikiri dictionary file...");
     fs::create_dir_all(&output_dir)?;
     let output_file = File::create(&output_path)?;
     let mut output_writer = BufWriter::new(output_file);
-    writer.write(&mut output_writer)?;
+    let header = writer.write(&mut output_writer)?;
+    output_writer.flush()?;
+    std::mem::drop(output_writer);
 
-    println!("Generated yomikiri dictionary file.");
+    // builds are deterministic, so checksum can be published with the file
+    println!(
+        "Generated yomikiri dictionary file. (content checksum: {:08x})",
+        header.checksum()
+    );
     Ok(())
 }
 
+fn run_export_json(opts: &ExportJsonOpts) -> Result<()> {
+    let (jmdict_file_meta, jmdict_lang) = if opts.multilingual {
+        (&JMDICT_MULTILINGUAL_FILE_META, "all")
+    } else {
+        (&JMDICT_FILE_META, DEFAULT_LANGUAGE)
+    };
+    let jmdict_file_path = opts.rawdir.join(jmdict_file_meta.out_filename);
+    let jmnedict_file_path = opts.rawdir.join(JMNEDICT_FILE_META.out_filename);
+    if !jmdict_file_path.exists() {
+        return Err(anyhow!(
+            "Jmdict file '{}' does not exist",
+            jmdict_file_meta.out_filename
+        ));
+    }
+    if !jmnedict_file_path.exists() {
+        return Err(anyhow!("JMnedict file does not exist"));
+    }
+
+    fs::create_dir_all(&opts.outdir)?;
+
+    println!("Exporting JMDict xml file...");
+    let jmdict_output_path = opts.outdir.join(format!(
+        "jmdict-{}-{}.json",
+        jmdict_lang, JMDICT_SIMPLIFIED_VERSION
+    ));
+    let mut parser = JMDictParser::new(BufReader::new(File::open(&jmdict_file_path)?))?;
+    let output_writer = BufWriter::new(File::create(&jmdict_output_path)?);
+    let mut writer = JMDictSimplifiedWriter::new(output_writer, parser.entities())?;
+    while let Some(entry) = parser.next_entry()? {
+        writer.write_entry(&entry)?;
+    }
+    writer.finish(parser.creation_date())?;
+
+    println!("Exporting JMneDict xml file...");
+    let jmnedict_output_path = opts
+        .outdir
+        .join(format!("jmnedict-all-{}.json", JMDICT_SIMPLIFIED_VERSION));
+    let mut parser = JMneDictParser::new(BufReader::new(File::open(&jmnedict_file_path)?))?;
+    let output_writer = BufWriter::new(File::create(&jmnedict_output_path)?);
+    let mut writer = JMneDictSimplifiedWriter::new(output_writer, parser.entities())?;
+    while let Some(entry) = parser.next_entry()? {
+        writer.write_entry(&entry)?;
+    }
+    writer.finish(parser.creation_date())?;
+
+    println!("Exported jmdict-simplified JSON files.");
+    Ok(())
+}
+
+fn run_diff(opts: &DiffOpts) -> Result<()> {
+    let is_dictionary_file =
+        |path: &Path| path.extension().is_some_and(|ext| ext == "yomikiridict");
+    if is_dictionary_file(&opts.old) != is_dictionary_file(&opts.new) {
+        return Err(anyhow!(
+            "Cannot compare a yomikiri dictionary file with a jmdict file"
+        ));
+    }
+
+    let output = if is_dictionary_file(&opts.old) {
+        let old = Dictionary::try_decode(fs::read(&opts.old)?)?;
+        let new = Dictionary::try_decode(fs::read(&opts.new)?)?;
+        let diff = old.borrow_view().diff(new.borrow_view())?;
+        if opts.json {
+            serde_json::to_string_pretty(&diff)?
+        } else {
+            diff.to_string()
+        }
+    } else {
+        let diff = if opts.jmnedict {
+            diff_entries(
+                read_jmnedict_diff_entries(&opts.old)?,
+                read_jmnedict_diff_entries(&opts.new)?,
+            )
+        } else {
+            diff_entries(
+                read_jmdict_diff_entries(&opts.old)?,
+                read_jmdict_diff_entries(&opts.new)?,
+            )
+        };
+        if opts.json {
+            serde_json::to_string_pretty(&diff)?
+        } else {
+            diff.to_string()
+        }
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+fn run_verify(opts: &VerifyOpts) -> Result<()> {
+    let dictionary = Dictionary::try_decode(fs::read(&opts.dict)?)?;
+    let report = dictionary.borrow_view().verify();
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+    if !report.is_ok() {
+        return Err(anyhow!(
+            "Dictionary file has {} problems",
+            report.problem_count
+        ));
+    }
+    Ok(())
+}
+
+fn read_jmdict_diff_entries(path: &Path) -> Result<Vec<DiffEntry>> {
+    let mut parser = JMDictParser::new(BufReader::new(File::open(path)?))?;
+    let mut entries = vec![];
+    while let Some(entry) = parser.next_entry()? {
+        entries.push(DiffEntry::from(&entry));
+    }
+    Ok(entries)
+}
+
+fn read_jmnedict_diff_entries(path: &Path) -> Result<Vec<DiffEntry>> {
+    let mut parser = JMneDictParser::new(BufReader::new(File::open(path)?))?;
+    let mut entries = vec![];
+    while let Some(entry) = parser.next_entry()? {
+        entries.push(DiffEntry::from(&entry));
+    }
+    Ok(entries)
+}
+
 /// download and unzip jmdict file into `output_path`
 fn download_dict(url: &str, output_path: &Path) -> Result<()> {
     let output_dir = output_path
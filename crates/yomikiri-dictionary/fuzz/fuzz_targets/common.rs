@@ -0,0 +1,13 @@
+use yomikiri_dictionary::header::DictionaryHeader;
+
+/// Replaces checksum in header with checksum of content,
+/// so that `verify` checks corrupt content instead of stopping at checksum.
+pub fn fix_checksum(data: &[u8]) -> Vec<u8> {
+    let mut bytes = data.to_vec();
+    if let Ok((header, len)) = DictionaryHeader::try_decode(&bytes) {
+        let end = len.saturating_add(header.content_len()).min(bytes.len());
+        let checksum = crc32fast::hash(&bytes[len..end]);
+        bytes[len - 4..len].copy_from_slice(&checksum.to_le_bytes());
+    }
+    bytes
+}
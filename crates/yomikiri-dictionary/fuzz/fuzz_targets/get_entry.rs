@@ -0,0 +1,30 @@
+//! `cargo fuzz run get_entry`
+//!
+//! Input is a length byte, a term of that length, and a dictionary file.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use yomikiri_dictionary::dictionary::DictionaryView;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&term_len, rest)) = data.split_first() else {
+        return;
+    };
+    let (term, dict_bytes) = rest.split_at((term_len as usize).min(rest.len()));
+    let Ok((dict, _)) = DictionaryView::try_decode(dict_bytes) else {
+        return;
+    };
+
+    if let Ok(idxs) = dict.term_index.get(term) {
+        for idx in &idxs {
+            let _ = dict.get_entry(idx);
+        }
+    }
+    for entry in dict.entries.all_items_iter().flatten() {
+        let _ = dict.examples_for_entry(&entry);
+    }
+    for _ in dict.name_entries.all_items_iter() {}
+    if let Ok(query) = std::str::from_utf8(term) {
+        let _ = dict.search_meaning(query);
+    }
+});
@@ -0,0 +1,15 @@
+//! `cargo fuzz run try_decode`
+//!
+//! A dictionary file written by `DictionaryWriter` is a good seed in corpus.
+#![no_main]
+
+mod common;
+
+use libfuzzer_sys::fuzz_target;
+use yomikiri_dictionary::dictionary::Dictionary;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(dict) = Dictionary::try_decode(common::fix_checksum(data)) {
+        let _ = dict.borrow_view().verify();
+    }
+});
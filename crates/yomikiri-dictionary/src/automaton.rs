@@ -0,0 +1,102 @@
+//! Automata over characters of index keys.
+//!
+//! fst reads keys byte by byte, so `Chars` collects bytes of each UTF-8 character
+//! before passing the character to a `CharAutomaton`.
+
+use fst::Automaton;
+
+/// Automaton that reads keys character by character
+pub(crate) trait CharAutomaton {
+    type State: Clone;
+
+    fn start(&self) -> Self::State;
+
+    fn is_match(&self, state: &Self::State) -> bool;
+
+    fn can_match(&self, state: &Self::State) -> bool;
+
+    fn will_always_match(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    fn accept_char(&self, state: &Self::State, ch: char) -> Self::State;
+}
+
+/// Adapts `CharAutomaton` to `fst::Automaton`
+pub(crate) struct Chars<A>(pub A);
+
+#[derive(Debug, Clone)]
+pub(crate) struct CharState<S> {
+    /// `None` if key read so far is not valid UTF-8, which no key can match
+    inner: Option<S>,
+    /// Bytes of a key character that is not completely read yet
+    partial: Vec<u8>,
+}
+
+impl<S> CharState<S> {
+    /// State of `CharAutomaton` after the last complete character
+    pub fn inner(&self) -> Option<&S> {
+        self.inner.as_ref()
+    }
+}
+
+impl<A: CharAutomaton> Automaton for Chars<A> {
+    type State = CharState<A::State>;
+
+    fn start(&self) -> Self::State {
+        CharState {
+            inner: Some(self.0.start()),
+            partial: vec![],
+        }
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        state.partial.is_empty() && state.inner().is_some_and(|inner| self.0.is_match(inner))
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.inner().is_some_and(|inner| self.0.can_match(inner))
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        state.partial.is_empty()
+            && state
+                .inner()
+                .is_some_and(|inner| self.0.will_always_match(inner))
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let Some(inner) = state.inner() else {
+            return state.clone();
+        };
+        let mut partial = state.partial.clone();
+        partial.push(byte);
+        if partial.len() < utf8_width(partial[0]) {
+            return CharState {
+                inner: Some(inner.clone()),
+                partial,
+            };
+        }
+        let inner = std::str::from_utf8(&partial)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .map(|ch| self.0.accept_char(inner, ch));
+        CharState {
+            inner,
+            partial: vec![],
+        }
+    }
+}
+
+/// Number of bytes of UTF-8 character that starts with `first` byte
+fn utf8_width(first: u8) -> usize {
+    if first < 0xC0 {
+        1
+    } else if first < 0xE0 {
+        2
+    } else if first < 0xF0 {
+        3
+    } else {
+        4
+    }
+}
@@ -1,18 +1,26 @@
-use std::io::{BufRead, Write};
+use std::collections::HashMap;
+use std::io::{BufRead, Seek, SeekFrom, Write};
 
 use ouroboros::self_referencing;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use yomikiri_jmdict::{JMDictParser, JMneDictParser};
+use yomikiri_jmdict::jmdict::{JMEntry, JMPatch};
+use yomikiri_jmdict::radical::{parse_radkfile, RadkRadical};
+use yomikiri_jmdict::{JMDictParser, JMDictPatchParser, JMneDictParser};
 
-use crate::entry::{Entry, NameEntry};
-use crate::index::{
-    create_sorted_term_indexes, DictIndexMap, EntryIdx, NameEntryIdx, WordEntryIdx,
-};
+use crate::entry::{Entry, NameEntry, Rarity, SenseReference};
+use crate::example::{ExampleIndex, ExamplesBuilder};
+use crate::header::{ContentWriter, DictionaryHeader, Section};
+use crate::index::{DictIndexMap, EntryIdx, NameEntryIdx, WordEntryIdx};
 use crate::jagged_array::JaggedArray;
+use crate::jmdict::{retain_languages, take_examples, ReferenceResolver, SenseIdxMap};
 use crate::jmnedict::{parse_jmnedict_entry, NameEntriesBuilder};
-use crate::meaning::{create_meaning_indexes, MeaningIdx};
-use crate::{Result, WordEntry};
+use crate::meaning::{meaning_index_keys, MeaningIdx};
+use crate::parallel::{map_in_order, BATCH_LEN};
+use crate::radical::{KanjiIndexBuilder, RadicalIndex};
+use crate::spill::{ItemSpill, SortedRuns, SpillLocation};
+use crate::term_rank::{term_rank, TermRankIndex, TermRankIndexBuilder};
+use crate::{Result, WordEntry, DEFAULT_LANGUAGE};
 
 #[self_referencing]
 pub struct Dictionary<D: AsRef<[u8]> + 'static> {
@@ -26,13 +34,28 @@ pub struct Dictionary<D: AsRef<[u8]> + 'static> {
 pub struct DictionaryMetadata {
     jmdict_creation_date: Option<String>,
     jmnedict_creation_date: Option<String>,
+    /// ISO 639-2 codes of gloss languages, primary language first
+    languages: Vec<String>,
+}
+
+impl DictionaryMetadata {
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
 }
 
 pub struct DictionaryView<'a> {
+    pub header: DictionaryHeader,
+    /// Content that follows header, checked against header checksum in `verify`
+    pub(crate) content: &'a [u8],
     pub term_index: DictIndexMap<'a, EntryIdx>,
-    pub meaning_index: DictIndexMap<'a, MeaningIdx>,
+    pub term_ranks: TermRankIndex<'a>,
+    /// Meaning index for each language in `metadata.languages()`, in the same order
+    pub meaning_indexes: Vec<DictIndexMap<'a, MeaningIdx>>,
     pub entries: JaggedArray<'a, WordEntry>,
     pub name_entries: JaggedArray<'a, NameEntry>,
+    pub radical_index: RadicalIndex<'a>,
+    pub example_index: ExampleIndex<'a>,
     pub metadata: DictionaryMetadata,
 }
 
@@ -49,25 +72,40 @@ impl<D: AsRef<[u8]> + 'static> Dictionary<D> {
 }
 
 impl<'a> DictionaryView<'a> {
+    /// Fails if header does not match, before any section is decoded.
     pub fn try_decode(source: &'a [u8]) -> Result<(Self, usize)> {
-        let mut at = 0;
-        let (term_index, len) = DictIndexMap::<EntryIdx>::try_decode(&source[at..])?;
-        at += len;
-        let (meaning_index, len) = DictIndexMap::<MeaningIdx>::try_decode(&source[at..])?;
-        at += len;
-        let (entries, len) = JaggedArray::try_decode(&source[at..])?;
-        at += len;
-        let (name_entries, len) = JaggedArray::try_decode(&source[at..])?;
-        at += len;
-        let start = source[at..].len();
-        let (metadata, rest) = postcard::take_from_bytes(&source[at..])?;
-        at += start - rest.len();
+        let (header, content) = DictionaryHeader::try_decode_with_content(source)?;
+        let section = |section: Section| &content[header.section(section)];
+
+        let source = section(Section::TermIndex);
+        let (term_index, len) = DictIndexMap::<EntryIdx>::try_decode(source)?;
+        let (term_ranks, _) = TermRankIndex::try_decode(&source[len..])?;
+        let source = section(Section::MeaningIndexes);
+        let (meaning_index_count, rest) = postcard::take_from_bytes::<u32>(source)?;
+        let mut at = source.len() - rest.len();
+        let mut meaning_indexes = vec![];
+        for _ in 0..meaning_index_count {
+            let (meaning_index, len) = DictIndexMap::<MeaningIdx>::try_decode(&source[at..])?;
+            at += len;
+            meaning_indexes.push(meaning_index);
+        }
+        let (entries, _) = JaggedArray::try_decode(section(Section::Entries))?;
+        let (name_entries, _) = JaggedArray::try_decode(section(Section::NameEntries))?;
+        let (radical_index, _) = RadicalIndex::try_decode(section(Section::RadicalIndex))?;
+        let (example_index, _) = ExampleIndex::try_decode(section(Section::ExampleIndex))?;
+        let metadata = postcard::from_bytes(section(Section::Metadata))?;
+        let at = DictionaryHeader::LEN + header.content_len();
 
         let s = Self {
+            header,
+            content,
             name_entries,
             term_index,
-            meaning_index,
+            term_ranks,
+            meaning_indexes,
             entries,
+            radical_index,
+            example_index,
             metadata,
         };
         Ok((s, at))
@@ -94,102 +132,397 @@ impl<'a> DictionaryView<'a> {
     pub fn get_name_entry(&self, idx: &NameEntryIdx) -> Result<NameEntry> {
         self.name_entries.get(idx.0 as usize)
     }
+
+    /// Returns `None` if dictionary is not built with `lang`
+    pub fn meaning_index(&self, lang: &str) -> Option<&DictIndexMap<'a, MeaningIdx>> {
+        let pos = self.metadata.languages.iter().position(|l| l == lang)?;
+        self.meaning_indexes.get(pos)
+    }
+
+    /// Returns `None` if referenced entry was not found when dictionary was built
+    pub fn get_referenced_entry(&self, reference: &SenseReference) -> Result<Option<WordEntry>> {
+        reference
+            .entry_idx
+            .as_ref()
+            .map(|idx| self.get_word_entry(idx))
+            .transpose()
+    }
 }
 
-pub struct DictionaryWriterJMDict;
+pub struct DictionaryWriterJMDict {
+    languages: Vec<String>,
+    spill: SpillLocation,
+}
 
 pub struct DictionaryWriterJMneDict {
-    entries: Vec<WordEntry>,
+    languages: Vec<String>,
+    spill: SpillLocation,
+    entries: ItemSpill<WordEntry>,
+    examples: ExamplesBuilder,
+    sense_idxs: SenseIdxMap,
     jmdict_creation_date: Option<String>,
+    warnings: Vec<String>,
 }
 
 pub struct DictionaryWriterFinal {
-    entries: Vec<WordEntry>,
+    languages: Vec<String>,
+    spill: SpillLocation,
+    entries: ItemSpill<WordEntry>,
+    examples: ExamplesBuilder,
+    sense_idxs: SenseIdxMap,
     jmdict_creation_date: Option<String>,
-    name_entries: Vec<NameEntry>,
+    name_entries: ItemSpill<NameEntry>,
     jmnedict_creation_date: Option<String>,
+    radicals: Vec<RadkRadical>,
+    warnings: Vec<String>,
 }
 
 /// ## Dictionary Format:
-/// 1. DictIndexMap<TermIdx>
-/// 2. DictIndexMap<MeaningIdx>
+/// 0. DictionaryHeader
+/// 1. DictIndexMap<TermIdx>, followed by TermRankIndex
+/// 2. Vec<DictIndexMap<MeaningIdx>>, one for each language
 /// 3. JaggedArray<WordEntry>
 /// 4. JaggedArray<NameEntry>
-/// 5. DictionaryMetadata
+/// 5. RadicalIndex
+/// 6. ExampleIndex
+/// 7. DictionaryMetadata
+///
+/// Entries are kept encoded in a spill while dictionary is built,
+/// and indexes are built from sorted runs of keys.
 #[derive(Default)]
 pub struct DictionaryWriter<STATE> {
     state: STATE,
 }
 
 impl DictionaryWriter<DictionaryWriterJMDict> {
+    /// Creates writer for English dictionary
     pub fn new() -> Self {
+        Self::with_languages(vec![DEFAULT_LANGUAGE.to_string()])
+    }
+
+    /// Creates writer for dictionary with glosses in `languages` (ISO 639-2 codes).
+    ///
+    /// Glosses in other languages are discarded, and entries without any gloss left are skipped.
+    /// The first language is used as the primary language of dictionary.
+    /// If `languages` is empty, English is used.
+    pub fn with_languages(mut languages: Vec<String>) -> Self {
+        if languages.is_empty() {
+            languages.push(DEFAULT_LANGUAGE.to_string());
+        }
         Self {
-            state: DictionaryWriterJMDict {},
+            state: DictionaryWriterJMDict {
+                languages,
+                spill: SpillLocation::default(),
+            },
         }
     }
 
+    /// Sets where entries and index keys are kept while dictionary is built.
+    ///
+    /// By default, they are kept encoded in memory, which does not bound memory used.
+    /// Use `SpillLocation::Directory` to build dictionary in bounded memory.
+    pub fn spill_to(mut self, location: SpillLocation) -> Self {
+        self.state.spill = location;
+        self
+    }
+
     pub fn read_jmdict<R: BufRead>(
         self,
         jmdict: R,
     ) -> Result<DictionaryWriter<DictionaryWriterJMneDict>> {
         let mut parser = JMDictParser::new(jmdict)?;
-        // 203736 entries (2022-08-23)
-        let mut entries = Vec::with_capacity(210000);
+        let mut entries = ItemSpill::new(&self.state.spill)?;
+        let mut examples = ExamplesBuilder::new(&self.state.languages);
+        let mut sense_idxs = SenseIdxMap::default();
         while let Some(entry) = parser.next_entry()? {
-            if entry.id < 5000000 || entry.id >= 6000000 {
-                let entry = WordEntry::try_from(entry)?;
-                entries.push(entry);
+            if let Some(entry) =
+                convert_jmdict_entry(entry, &self.state.languages, &mut examples, &mut sense_idxs)?
+            {
+                entries.push(&entry)?;
             }
         }
         Ok(DictionaryWriter {
             state: DictionaryWriterJMneDict {
+                languages: self.state.languages,
+                spill: self.state.spill,
                 entries,
+                examples,
+                sense_idxs,
                 jmdict_creation_date: parser.creation_date().map(|d| d.to_string()),
+                warnings: parser.warnings().to_vec(),
             },
         })
     }
 }
 
+/// Returns `None` if entry is not included in dictionary.
+fn convert_jmdict_entry(
+    mut entry: JMEntry,
+    languages: &[String],
+    examples: &mut ExamplesBuilder,
+    sense_idxs: &mut SenseIdxMap,
+) -> Result<Option<WordEntry>> {
+    // name entries are read from JMnedict
+    if (5000000..6000000).contains(&entry.id) {
+        return Ok(None);
+    }
+    let kept = retain_languages(&mut entry, languages);
+    if entry.senses.is_empty() {
+        return Ok(None);
+    }
+    sense_idxs.add(&entry, &kept);
+    examples.add(entry.id, take_examples(&mut entry));
+    WordEntry::try_from(entry).map(Some)
+}
+
 impl DictionaryWriter<DictionaryWriterJMneDict> {
+    /// Applies JMdict patch file to entries read from JMdict.
+    ///
+    /// Entries are matched by `ent_seq`. Patch files are applied in the order they are read,
+    /// and sense references are resolved after all patches are applied.
+    /// Adding an existing entry replaces it, and modifying a missing entry adds it.
+    /// Such mismatches, and deleting a missing entry, are added to warnings.
+    pub fn apply_jmdict_patch<R: BufRead>(mut self, patch: R) -> Result<Self> {
+        let state = &mut self.state;
+        let mut parser = JMDictPatchParser::new(patch)?;
+        let mut positions: HashMap<u32, usize> = HashMap::new();
+        let mut entries = state.entries.iter()?;
+        while let Some(entry) = entries.next_with_position() {
+            let (position, entry) = entry?;
+            positions.insert(entry.id, position);
+        }
+
+        while let Some(patch) = parser.next_patch()? {
+            let (id, entry) = match patch {
+                JMPatch::Add(entry) => {
+                    if positions.contains_key(&entry.id) {
+                        state.warnings.push(format!(
+                            "Patch adds entry that already exists: {}",
+                            entry.id
+                        ));
+                    }
+                    (entry.id, Some(entry))
+                }
+                JMPatch::Modify(entry) => {
+                    if !positions.contains_key(&entry.id) {
+                        state.warnings.push(format!(
+                            "Patch modifies entry that does not exist: {}",
+                            entry.id
+                        ));
+                    }
+                    (entry.id, Some(entry))
+                }
+                JMPatch::Delete(id) => {
+                    if !positions.contains_key(&id) {
+                        state
+                            .warnings
+                            .push(format!("Patch deletes entry that does not exist: {}", id));
+                    }
+                    (id, None)
+                }
+            };
+
+            state.examples.remove(id);
+            state.sense_idxs.remove(id);
+            let entry = match entry {
+                Some(entry) => convert_jmdict_entry(
+                    entry,
+                    &state.languages,
+                    &mut state.examples,
+                    &mut state.sense_idxs,
+                )?,
+                None => None,
+            };
+            match (positions.get(&id), entry) {
+                (Some(&pos), entry) => {
+                    if entry.is_none() {
+                        positions.remove(&id);
+                    }
+                    state.entries.replace(pos, entry);
+                }
+                (None, Some(entry)) => {
+                    let pos = state.entries.push(&entry)?;
+                    positions.insert(id, pos);
+                }
+                (None, None) => {}
+            }
+        }
+
+        state.warnings.extend_from_slice(parser.warnings());
+        Ok(self)
+    }
+
     pub fn read_jmnedict<R: BufRead>(
         mut self,
         jmnedict: R,
     ) -> Result<DictionaryWriter<DictionaryWriterFinal>> {
-        let mut name_builder = NameEntriesBuilder::new();
+        let mut name_builder = NameEntriesBuilder::new(&self.state.spill);
 
         let mut parser = JMneDictParser::new(jmnedict)?;
         while let Some(entry) = parser.next_entry()? {
-            parse_jmnedict_entry(&mut self.state.entries, &mut name_builder, entry)?;
+            parse_jmnedict_entry(
+                &mut self.state.entries,
+                &mut name_builder,
+                entry,
+                &self.state.languages,
+            )?;
         }
 
-        let name_entries = name_builder.into_iter().collect::<Vec<NameEntry>>();
+        let name_entries = name_builder.build(&self.state.spill)?;
+        let mut warnings = self.state.warnings;
+        warnings.extend_from_slice(parser.warnings());
 
         Ok(DictionaryWriter {
             state: DictionaryWriterFinal {
+                languages: self.state.languages,
+                spill: self.state.spill,
                 entries: self.state.entries,
+                examples: self.state.examples,
+                sense_idxs: self.state.sense_idxs,
                 jmdict_creation_date: self.state.jmdict_creation_date,
                 name_entries,
                 jmnedict_creation_date: parser.creation_date().map(|d| d.to_string()),
+                radicals: vec![],
+                warnings,
             },
         })
     }
 }
 
 impl DictionaryWriter<DictionaryWriterFinal> {
-    pub fn write<W: Write>(self, writer: &mut W) -> Result<()> {
+    /// Warnings from parsing JMdict and JMnedict files.
+    ///
+    /// Unknown data is skipped or kept as-is instead of failing,
+    /// so dictionary can still be built from newer versions of the files.
+    pub fn warnings(&self) -> &[String] {
+        &self.state.warnings
+    }
+
+    /// Optionally reads UTF-8 encoded RADKFILE to build radical index.
+    ///
+    /// If not read, radical index is empty.
+    pub fn read_radkfile<R: BufRead>(mut self, radkfile: R) -> Result<Self> {
+        self.state.radicals = parse_radkfile(radkfile)?;
+        Ok(self)
+    }
+
+    /// Sections are written as they are built, and header is written last
+    /// at the starting position of `writer`.
+    ///
+    /// Returns the written header.
+    pub fn write<W: Write + Seek>(self, writer: &mut W) -> Result<DictionaryHeader> {
+        let mut state = self.state;
         let metadata = DictionaryMetadata {
-            jmdict_creation_date: self.state.jmdict_creation_date,
-            jmnedict_creation_date: self.state.jmnedict_creation_date,
+            jmdict_creation_date: state.jmdict_creation_date,
+            jmnedict_creation_date: state.jmnedict_creation_date,
+            languages: state.languages,
         };
 
-        let term_index_items =
-            create_sorted_term_indexes(&self.state.name_entries, &self.state.entries)?;
-        DictIndexMap::build_and_encode_to(&term_index_items, writer)?;
-        let meaning_indexes = create_meaning_indexes(&self.state.entries)?;
-        DictIndexMap::build_and_encode_to(&meaning_indexes, writer)?;
-        JaggedArray::build_and_encode_to(&self.state.entries, writer)?;
-        JaggedArray::build_and_encode_to(&self.state.name_entries, writer)?;
-        postcard::to_io(&metadata, &mut *writer)?;
-        Ok(())
+        let mut resolver = ReferenceResolver::new(state.sense_idxs);
+        for entry in state.entries.iter()? {
+            resolver.add_references(&entry?);
+        }
+        let mut term_keys = SortedRuns::new(&state.spill);
+        let mut meaning_keys: Vec<SortedRuns<MeaningIdx>> = metadata
+            .languages
+            .iter()
+            .map(|_| SortedRuns::new(&state.spill))
+            .collect();
+        let mut kanji_index = KanjiIndexBuilder::new(&state.radicals);
+        let mut entries = state.entries.iter()?;
+        let mut i = 0;
+        loop {
+            let batch = entries.next_batch(BATCH_LEN)?;
+            if batch.is_empty() {
+                break;
+            }
+            let batch = batch
+                .into_iter()
+                .enumerate()
+                .map(|(j, entry)| Ok((WordEntryIdx((i + j).try_into()?), entry)))
+                .collect::<Result<Vec<_>>>()?;
+            // normalizing glosses takes most of the time
+            let batch = map_in_order(batch, |(idx, entry)| {
+                let keys: Vec<_> = metadata
+                    .languages
+                    .iter()
+                    .map(|lang| meaning_index_keys(idx, &entry, lang))
+                    .collect();
+                (idx, entry, keys)
+            });
+
+            for (idx, entry, entry_meaning_keys) in batch {
+                resolver.add_candidate(i, &entry);
+                // some entries have multiple terms.
+                for term in entry
+                    .kanjis
+                    .iter()
+                    .map(|k| &k.kanji)
+                    .chain(entry.readings.iter().map(|r| &r.reading))
+                {
+                    let search_only = entry.term_rarity(term)? == Rarity::Search;
+                    let rank = term_rank(term, entry.term_priority(term), search_only);
+                    term_keys.push(term.clone(), (idx.entry_idx(), rank))?;
+                }
+                for (keys, entry_keys) in meaning_keys.iter_mut().zip(entry_meaning_keys) {
+                    for (key, meaning_idx) in entry_keys {
+                        keys.push(key, meaning_idx)?;
+                    }
+                }
+                kanji_index.add(idx, &entry);
+                i += 1;
+            }
+        }
+        for (i, entry) in state.name_entries.iter()?.enumerate() {
+            let idx = NameEntryIdx(i.try_into()?);
+            let entry = entry?;
+            let rank = term_rank(&entry.kanji, entry.priority(), false);
+            term_keys.push(entry.kanji, (idx.entry_idx(), rank))?;
+        }
+
+        // header contains offsets and checksum of content, so it is written after content
+        let start = writer.stream_position()?;
+        writer.write_all(&[0; DictionaryHeader::LEN])?;
+        let mut content = ContentWriter::new(writer);
+
+        let mut term_ranks = TermRankIndexBuilder::new();
+        let term_pairs = term_keys.into_sorted()?.map(|pair| {
+            let (term, (idx, rank)) = pair?;
+            term_ranks.add(&term, rank)?;
+            Ok((term, idx))
+        });
+        DictIndexMap::build_and_encode_sorted_to(term_pairs, &mut content)?;
+        term_ranks.finish_and_encode_to(&mut content)?;
+        content.end_section();
+        postcard::to_io(&(metadata.languages.len() as u32), &mut content)?;
+        for keys in meaning_keys {
+            DictIndexMap::build_and_encode_sorted_to(keys.into_sorted()?, &mut content)?;
+        }
+        content.end_section();
+        JaggedArray::build_and_encode_spill_to(
+            &mut state.entries,
+            |i, entry| resolver.resolve(i, entry),
+            &mut content,
+        )?;
+        content.end_section();
+        JaggedArray::build_and_encode_spill_to(
+            &mut state.name_entries,
+            |_, entry| entry,
+            &mut content,
+        )?;
+        content.end_section();
+        RadicalIndex::build_and_encode_to(&state.radicals, kanji_index, &mut content)?;
+        content.end_section();
+        ExampleIndex::build_and_encode_to(&state.examples, &mut content)?;
+        content.end_section();
+        postcard::to_io(&metadata, &mut content)?;
+        content.end_section();
+
+        let header = content.finish()?;
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(start))?;
+        header.encode_to(writer)?;
+        writer.seek(SeekFrom::Start(end))?;
+        Ok(header)
     }
 }
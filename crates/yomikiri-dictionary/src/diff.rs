@@ -0,0 +1,353 @@
+//! Compares two releases of JMdict or JMnedict, matching entries by `ent_seq`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+use yomikiri_jmdict::jmdict::{JMEntry, JMSense};
+use yomikiri_jmdict::jmnedict::JMneEntry;
+
+use crate::dictionary::DictionaryView;
+use crate::entry::{NameEntry, Sense};
+use crate::{Result, WordEntry};
+
+/// Fields of an entry that are compared, as displayable values.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffEntry {
+    /// `ent_seq` of entry
+    pub id: u32,
+    pub kanjis: Vec<String>,
+    pub readings: Vec<String>,
+    /// Glosses of each sense joined by "; "
+    pub senses: Vec<String>,
+    /// Part-of-speech of word entry, or name types of name entry
+    pub pos: Vec<String>,
+    pub priority: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffField {
+    Kanji,
+    Reading,
+    Sense,
+    Pos,
+    Priority,
+}
+
+/// Values of a field that only exist in old or new entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: DiffField,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryDiff {
+    pub id: u32,
+    /// Kanji or reading of entry in new file, used to display entry
+    pub term: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Entries are sorted by id.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntriesDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub modified: Vec<EntryDiff>,
+}
+
+/// Diff of word entries and name entries in built dictionary files
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionaryDiff {
+    pub words: EntriesDiff,
+    pub names: EntriesDiff,
+}
+
+impl DiffEntry {
+    fn term(&self) -> &str {
+        self.kanjis
+            .first()
+            .or_else(|| self.readings.first())
+            .map_or("", |t| t.as_str())
+    }
+}
+
+impl From<&JMEntry> for DiffEntry {
+    fn from(entry: &JMEntry) -> Self {
+        let mut pos = vec![];
+        for p in entry.senses.iter().flat_map(|s| &s.pos) {
+            push_unique(&mut pos, p.entity_name().to_string());
+        }
+        DiffEntry {
+            id: entry.id,
+            kanjis: entry.kanjis.iter().map(|k| k.kanji.clone()).collect(),
+            readings: entry.readings.iter().map(|r| r.reading.clone()).collect(),
+            senses: entry.senses.iter().map(jm_sense_text).collect(),
+            pos,
+            priority: entry.priority(),
+        }
+    }
+}
+
+impl From<&JMneEntry> for DiffEntry {
+    fn from(entry: &JMneEntry) -> Self {
+        let mut pos = vec![];
+        for t in entry.translations.iter().flat_map(|t| &t.name_type) {
+            push_unique(&mut pos, t.entity_name().to_string());
+        }
+        DiffEntry {
+            id: entry.id,
+            kanjis: entry.kanjis.iter().map(|k| k.kanji.clone()).collect(),
+            readings: entry.readings.iter().map(|r| r.reading.clone()).collect(),
+            senses: entry
+                .translations
+                .iter()
+                .map(|t| t.translations.join("; "))
+                .collect(),
+            pos,
+            priority: entry.priority(),
+        }
+    }
+}
+
+impl From<&WordEntry> for DiffEntry {
+    fn from(entry: &WordEntry) -> Self {
+        let mut pos = vec![];
+        for p in entry.grouped_senses.iter().flat_map(|g| &g.pos) {
+            push_unique(&mut pos, serialized_name(p));
+        }
+        DiffEntry {
+            id: entry.id,
+            kanjis: entry.kanjis.iter().map(|k| k.kanji.clone()).collect(),
+            readings: entry.readings.iter().map(|r| r.reading.clone()).collect(),
+            senses: entry
+                .grouped_senses
+                .iter()
+                .flat_map(|g| &g.senses)
+                .map(sense_text)
+                .collect(),
+            pos,
+            priority: entry.priority,
+        }
+    }
+}
+
+/// Splits name entries, which are grouped by kanji, into an entry for each `ent_seq`
+pub fn name_diff_entries(entries: &[NameEntry]) -> Vec<DiffEntry> {
+    let mut map: BTreeMap<u32, DiffEntry> = BTreeMap::new();
+    for entry in entries {
+        for group in &entry.groups {
+            for item in &group.items {
+                let diff_entry = map.entry(item.id).or_insert_with(|| DiffEntry {
+                    id: item.id,
+                    ..DiffEntry::default()
+                });
+                push_unique(&mut diff_entry.kanjis, entry.kanji.clone());
+                push_unique(&mut diff_entry.readings, item.reading.clone());
+                for t in &group.types {
+                    push_unique(&mut diff_entry.pos, t.entity_name().to_string());
+                }
+                diff_entry.priority = diff_entry.priority.max(item.priority);
+            }
+        }
+    }
+    map.into_values().collect()
+}
+
+/// Compares entries with the same id in `old` and `new`.
+pub fn diff_entries<O, N>(old: O, new: N) -> EntriesDiff
+where
+    O: IntoIterator<Item = DiffEntry>,
+    N: IntoIterator<Item = DiffEntry>,
+{
+    let mut old: BTreeMap<u32, DiffEntry> = old.into_iter().map(|e| (e.id, e)).collect();
+    let mut diff = EntriesDiff::default();
+
+    let new: BTreeMap<u32, DiffEntry> = new.into_iter().map(|e| (e.id, e)).collect();
+    for (id, new_entry) in new {
+        let Some(old_entry) = old.remove(&id) else {
+            diff.added.push(new_entry);
+            continue;
+        };
+        let changes = diff_fields(&old_entry, &new_entry);
+        if !changes.is_empty() {
+            diff.modified.push(EntryDiff {
+                id,
+                term: new_entry.term().to_string(),
+                changes,
+            });
+        }
+    }
+    diff.removed = old.into_values().collect();
+    diff
+}
+
+impl DictionaryView<'_> {
+    /// Compares word entries and name entries with `other`, which is the newer dictionary.
+    pub fn diff(&self, other: &DictionaryView) -> Result<DictionaryDiff> {
+        let words = diff_entries(self.word_diff_entries()?, other.word_diff_entries()?);
+        let names = diff_entries(self.name_diff_entries()?, other.name_diff_entries()?);
+        Ok(DictionaryDiff { words, names })
+    }
+
+    fn word_diff_entries(&self) -> Result<Vec<DiffEntry>> {
+        self.entries
+            .all_items_iter()
+            .map(|entry| entry.map(|e| DiffEntry::from(&e)))
+            .collect()
+    }
+
+    fn name_diff_entries(&self) -> Result<Vec<DiffEntry>> {
+        let entries = self
+            .name_entries
+            .all_items_iter()
+            .collect::<Result<Vec<NameEntry>>>()?;
+        Ok(name_diff_entries(&entries))
+    }
+}
+
+fn diff_fields(old: &DiffEntry, new: &DiffEntry) -> Vec<FieldChange> {
+    let mut changes = vec![];
+    let fields = [
+        (DiffField::Kanji, &old.kanjis, &new.kanjis),
+        (DiffField::Reading, &old.readings, &new.readings),
+        (DiffField::Sense, &old.senses, &new.senses),
+        (DiffField::Pos, &old.pos, &new.pos),
+    ];
+    for (field, old_values, new_values) in fields {
+        if old_values != new_values {
+            changes.push(FieldChange {
+                field,
+                removed: values_not_in(old_values, new_values),
+                added: values_not_in(new_values, old_values),
+            });
+        }
+    }
+    if old.priority != new.priority {
+        changes.push(FieldChange {
+            field: DiffField::Priority,
+            removed: vec![old.priority.to_string()],
+            added: vec![new.priority.to_string()],
+        });
+    }
+    changes
+}
+
+/// Returns values in `values` that are not in `other`, counting duplicates.
+///
+/// If only order of values changed, all values are returned for both sides.
+fn values_not_in(values: &[String], other: &[String]) -> Vec<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for value in other {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut result = vec![];
+    for value in values {
+        match counts.get_mut(value.as_str()) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => result.push(value.clone()),
+        }
+    }
+    if result.is_empty() && values.len() == other.len() {
+        return values.to_vec();
+    }
+    result
+}
+
+fn push_unique(values: &mut Vec<String>, value: String) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+fn jm_sense_text(sense: &JMSense) -> String {
+    sense
+        .meanings
+        .iter()
+        .map(|m| m.text.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn sense_text(sense: &Sense) -> String {
+    sense
+        .meanings
+        .iter()
+        .map(|m| m.text.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Name of unit enum variant as serialized by serde
+fn serialized_name<T: Serialize + fmt::Debug>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{:?}", value),
+    }
+}
+
+impl fmt::Display for EntriesDiff {
+    /// ```text
+    /// + 1000010 ゝ
+    /// - 1000020 ゞ
+    /// ~ 1000040 〃
+    ///     reading: -おなじく +おなじ
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} added, {} removed, {} modified",
+            self.added.len(),
+            self.removed.len(),
+            self.modified.len()
+        )?;
+        for entry in &self.added {
+            writeln!(f, "+ {} {}", entry.id, entry.term())?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "- {} {}", entry.id, entry.term())?;
+        }
+        for entry in &self.modified {
+            writeln!(f, "~ {} {}", entry.id, entry.term)?;
+            for change in &entry.changes {
+                write!(f, "    {}:", change.field)?;
+                for value in &change.removed {
+                    write!(f, " -{}", value)?;
+                }
+                for value in &change.added {
+                    write!(f, " +{}", value)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DiffField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DiffField::Kanji => "kanji",
+            DiffField::Reading => "reading",
+            DiffField::Sense => "sense",
+            DiffField::Pos => "pos",
+            DiffField::Priority => "priority",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for DictionaryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Word entries: {}", self.words)?;
+        write!(f, "Name entries: {}", self.names)
+    }
+}
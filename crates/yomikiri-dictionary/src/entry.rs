@@ -10,14 +10,17 @@ use std::ops::Deref;
 use schemars::JsonSchema;
 use serde::de::Error as DeserializeError;
 use serde::{Deserialize, Serialize};
-use yomikiri_jmdict::jmdict::{JMDialect, JMPartOfSpeech, JMSenseMisc};
+use yomikiri_jmdict::jmdict::{
+    JMDialect, JMGlossType, JMLanguageSource, JMPartOfSpeech, JMPriority, JMSenseField, JMSenseMisc,
+};
 use yomikiri_jmdict::jmnedict::JMneNameType;
 use yomikiri_unidic_types::{
     UnidicAdjectivePos2, UnidicInterjectionPos2, UnidicNaAdjectivePos2, UnidicNounPos2,
     UnidicParticlePos2, UnidicPos, UnidicSuffixPos2, UnidicSymbolPos2, UnidicVerbPos2,
 };
 
-use crate::{Error, Result};
+use crate::index::WordEntryIdx;
+use crate::{Error, Result, DEFAULT_LANGUAGE};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
 #[serde(tag = "type", content = "entry", rename_all = "camelCase")]
@@ -46,6 +49,7 @@ pub struct WordEntryInner {
 pub struct Kanji {
     pub kanji: String,
     pub rarity: Rarity,
+    pub priority: Vec<JMPriority>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -56,6 +60,7 @@ pub struct Reading {
     pub nokanji: bool,
     pub to_kanji: Vec<String>,
     pub rarity: Rarity,
+    pub priority: Vec<JMPriority>,
 }
 
 /// Ordered by rarity.
@@ -82,16 +87,52 @@ pub struct GroupedSense {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Sense {
-    /// NFKC normalized
-    pub meanings: Vec<String>,
+    pub meanings: Vec<Gloss>,
     pub to_kanji: Vec<String>,
     pub to_reading: Vec<String>,
+    /// 'See also' references to other entries
+    pub references: Vec<SenseReference>,
+    pub antonyms: Vec<SenseReference>,
+    /// Domain of usage (e.g. medicine, computing)
+    pub fields: Vec<JMSenseField>,
     pub misc: Vec<JMSenseMisc>,
     /// NFKC normalized
     pub info: Vec<String>,
+    /// Source language of loanwords
+    pub language_sources: Vec<JMLanguageSource>,
     pub dialects: Vec<JMDialect>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Gloss {
+    /// NFKC normalized
+    pub text: String,
+    /// ISO 639-2 language code, `None` if English.
+    ///
+    /// Most dictionaries only have English glosses, so the code is not stored for each of them.
+    pub lang: Option<String>,
+    pub gloss_type: Option<JMGlossType>,
+    /// Gender of gloss word in its language
+    pub gender: Option<String>,
+    pub highlighted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SenseReference {
+    /// Kanji or reading of referenced entry
+    pub term: String,
+    pub reading: Option<String>,
+    /// Idx of sense in referenced entry, counting senses in all of its `grouped_senses`.
+    ///
+    /// `None` if not specified, or referenced entry or sense is not in dictionary.
+    pub sense: Option<u16>,
+    /// Resolved when dictionary file is written.
+    /// `None` if no matching entry exists in dictionary.
+    pub entry_idx: Option<WordEntryIdx>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum PartOfSpeech {
@@ -148,10 +189,51 @@ pub struct GroupedNameItem {
 pub struct NameItem {
     pub id: u32,
     pub reading: String,
+    /// Rarer of kanji and reading rarity
+    pub rarity: Rarity,
+    /// Higher of kanji and reading priority score
+    pub priority: u16,
 }
 
 pub type NameType = JMneNameType;
 
+impl NameEntry {
+    /// Highest priority of name items
+    pub fn priority(&self) -> u16 {
+        self.groups
+            .iter()
+            .flat_map(|g| &g.items)
+            .map(|item| item.priority)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Gloss {
+    /// Gloss is an explanation of the term, rather than a translation
+    pub fn is_explanation(&self) -> bool {
+        self.gloss_type == Some(JMGlossType::Explanation)
+    }
+
+    /// ISO 639-2 language code
+    pub fn lang(&self) -> &str {
+        self.lang.as_deref().unwrap_or(DEFAULT_LANGUAGE)
+    }
+}
+
+/// English gloss
+impl From<String> for Gloss {
+    fn from(text: String) -> Self {
+        Gloss {
+            text,
+            lang: None,
+            gloss_type: None,
+            gender: None,
+            highlighted: false,
+        }
+    }
+}
+
 impl PartialEq for WordEntryInner {
     fn eq(&self, other: &Self) -> bool {
         other.id == self.id
@@ -163,7 +245,7 @@ impl PartOfSpeech {
         use JMPartOfSpeech::*;
         let mut poss = vec![];
         for jm_pos in jm_poss {
-            if let Some(p) = match *jm_pos {
+            if let Some(p) = match jm_pos {
                 Adverb | AdverbTo => Some(PartOfSpeech::Adverb),
                 Conjunction => Some(PartOfSpeech::Conjunction),
                 Interjection => Some(PartOfSpeech::Interjection),
@@ -202,6 +284,7 @@ impl PartOfSpeech {
                 }
                 // These tags are accompanied by other verb tag to describe them
                 VerbTransitive | VerbIntransitive => None,
+                Other(_) => None,
             } {
                 poss.push(p);
             }
@@ -298,7 +381,7 @@ impl Entry {
     pub fn priority(&self) -> u16 {
         match self {
             Entry::Word(word) => word.priority,
-            Entry::Name(_) => 0,
+            Entry::Name(name) => name.priority(),
         }
     }
 }
@@ -309,6 +392,11 @@ impl WordEntry {
         Ok(Self(inner))
     }
 
+    /// Modifications must not break entry constraints
+    pub(crate) fn inner_mut(&mut self) -> &mut WordEntryInner {
+        &mut self.0
+    }
+
     fn validate_entry(inner: &WordEntryInner) -> Result<()> {
         if inner.readings.is_empty() {
             return Err(Error::InvalidEntry(
@@ -352,6 +440,14 @@ impl WordEntry {
         self.grouped_senses.iter().any(|g| g.pos.contains(&pos))
     }
 
+    /// Returns `true` if any sense is used in domain `field`
+    pub fn has_field(&self, field: JMSenseField) -> bool {
+        self.grouped_senses
+            .iter()
+            .flat_map(|g| &g.senses)
+            .any(|s| s.fields.contains(&field))
+    }
+
     /// Get first reading that can be applied for kanji.
     pub fn reading_for_kanji(&self, kanji: &str) -> Option<&Reading> {
         self.readings.iter().find(|reading| {
@@ -359,6 +455,23 @@ impl WordEntry {
         })
     }
 
+    /// Returns priority score of kanji or reading form that is `term`.
+    ///
+    /// Returns 0 if entry does not have `term`.
+    pub fn term_priority(&self, term: &str) -> u16 {
+        self.kanjis
+            .iter()
+            .find(|kanji_obj| kanji_obj.kanji == term)
+            .map(|k_obj| &k_obj.priority)
+            .or_else(|| {
+                self.readings
+                    .iter()
+                    .find(|reading_obj| reading_obj.reading == term)
+                    .map(|r_obj| &r_obj.priority)
+            })
+            .map_or(0, |priority| JMPriority::score(priority))
+    }
+
     pub fn term_rarity(&self, term: &str) -> Result<Rarity> {
         self.kanjis
             .iter()
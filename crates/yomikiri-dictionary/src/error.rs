@@ -2,6 +2,7 @@ use std::io;
 use std::num::TryFromIntError;
 
 use thiserror::Error;
+use yomikiri_jmdict::ErrorPosition;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -15,7 +16,7 @@ pub enum Error {
     TryFromInt(#[from] TryFromIntError),
     #[error("Postcard: {0}")]
     Postcard(#[from] postcard::Error),
-    #[error("JMDict parse error {0}")]
+    #[error("JMDict parse error: {0}")]
     JMDict(#[from] yomikiri_jmdict::Error),
     #[error("FST error: {0}")]
     Fst(#[from] fst::Error),
@@ -27,7 +28,32 @@ pub enum Error {
     InvalidEntry(String),
     #[error("Invalid meaning index: {0}")]
     InvalidIndex(String),
+    #[error("Not a yomikiri dictionary file")]
+    InvalidMagic,
+    #[error("Dictionary file schema version is {actual}, but expected {expected}")]
+    SchemaVersionMismatch { expected: u16, actual: u16 },
+    #[error(
+        "Dictionary file is truncated: expected at least {expected} bytes, but found {actual}"
+    )]
+    Truncated { expected: usize, actual: usize },
+    #[error("Dictionary file checksum mismatch: expected {expected:08x}, but found {actual:08x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+    #[error("Invalid dictionary file header: {0}")]
+    InvalidHeader(String),
+    /// Decoded bytes are inconsistent, e.g. an offset points outside of data
+    #[error("Invalid dictionary data: {0}")]
+    InvalidData(String),
     /// An item (e.g. term) is expected to be in another resource, but not found
     #[error("Could not find {0}")]
     NotFound(String),
 }
+
+impl Error {
+    /// Position in JMdict or JMnedict xml where parsing failed
+    pub fn xml_position(&self) -> Option<&ErrorPosition> {
+        match self {
+            Self::JMDict(err) => err.position(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use yomikiri_jmdict::jmdict::JMExample;
+
+use crate::dictionary::DictionaryView;
+use crate::index::{DictIndexItem, DictIndexMap, EncodableIdx};
+use crate::jagged_array::JaggedArray;
+use crate::verify::VerifyReport;
+use crate::{Result, WordEntry};
+
+/// Language code of the Japanese sentence in `<ex_sent>`
+const JAPANESE_LANGUAGE: &str = "jpn";
+
+/// Example sentence, e.g. from Tatoeba project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExampleSentence {
+    /// Source of sentence. "tat" for Tatoeba
+    pub source: String,
+    /// Id of sentence in source
+    pub source_id: String,
+    /// Japanese sentence
+    pub sentence: String,
+    /// Translations in dictionary languages
+    pub translations: Vec<ExampleTranslation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExampleTranslation {
+    /// ISO 639-2 code of language
+    pub lang: String,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+pub struct ExampleIdx {
+    /// Idx of sense within entry. Counts sense in each group
+    sense_idx: u16,
+    /// Idx of sentence within `ExampleIndex.sentences`
+    sentence_idx: u32,
+}
+
+impl EncodableIdx for ExampleIdx {}
+
+/// Example sentence of a sense in word entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryExample {
+    /// Idx of sense within entry. Counts sense in each group
+    pub sense_idx: u16,
+    pub example: ExampleSentence,
+}
+
+/// Example sentences, and word entry to sentence index.
+///
+/// Word entries are keyed by their JMdict entry id,
+/// as the same sentence may be linked to multiple entries.
+pub struct ExampleIndex<'a> {
+    sentences: JaggedArray<'a, ExampleSentence>,
+    entry_index: DictIndexMap<'a, ExampleIdx>,
+}
+
+impl<'a> ExampleIndex<'a> {
+    pub fn len(&self) -> usize {
+        self.sentences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sentences.is_empty()
+    }
+
+    pub(crate) fn build_and_encode_to<W: Write>(
+        builder: &ExamplesBuilder,
+        writer: &mut W,
+    ) -> Result<()> {
+        JaggedArray::build_and_encode_to(&builder.sentences, writer)?;
+        let mut items: Vec<DictIndexItem<ExampleIdx>> = builder
+            .links
+            .iter()
+            .map(|(id, idxs)| DictIndexItem {
+                key: id.to_string(),
+                entry_indexes: idxs.clone(),
+            })
+            .collect();
+        items.sort_by(|a, b| a.key.cmp(&b.key));
+        DictIndexMap::build_and_encode_to(&items, writer)?;
+        Ok(())
+    }
+
+    pub fn try_decode(source: &'a [u8]) -> Result<(Self, usize)> {
+        let mut at = 0;
+        let (sentences, len) = JaggedArray::try_decode(&source[at..])?;
+        at += len;
+        let (entry_index, len) = DictIndexMap::try_decode(&source[at..])?;
+        at += len;
+        let index = ExampleIndex {
+            sentences,
+            entry_index,
+        };
+        Ok((index, at))
+    }
+}
+
+impl ExampleIndex<'_> {
+    /// Every sentence must be decodable, and every entry must point to existing sentences
+    pub(crate) fn verify(&self, report: &mut VerifyReport) {
+        report.example_sentences = self.sentences.len();
+        for (i, sentence) in self.sentences.all_items_iter().enumerate() {
+            if let Err(err) = sentence {
+                report.problem(format!(
+                    "Example sentence {} could not be decoded: {}",
+                    i, err
+                ));
+            }
+        }
+        self.entry_index.for_each_item(|key, idxs| {
+            let id = String::from_utf8_lossy(key);
+            if id.parse::<u32>().is_err() {
+                report.problem(format!("Examples are keyed by invalid entry id '{}'", id));
+            }
+            let idxs = match idxs {
+                Ok(idxs) => idxs,
+                Err(err) => {
+                    report.problem(format!(
+                        "Examples of entry {} could not be decoded: {}",
+                        id, err
+                    ));
+                    return;
+                }
+            };
+            for idx in &idxs {
+                if idx.sentence_idx as usize >= self.sentences.len() {
+                    report.problem(format!(
+                        "Examples of entry {} point to missing sentence {}",
+                        id, idx.sentence_idx
+                    ));
+                }
+            }
+        });
+    }
+}
+
+/// Collects example sentences of word entries while JMdict is read.
+///
+/// Sentences are deduplicated by their source id.
+pub(crate) struct ExamplesBuilder {
+    languages: Vec<String>,
+    sentences: Vec<ExampleSentence>,
+    /// (source, source id) -> idx of sentence
+    sentence_idxs: HashMap<(String, String), u32>,
+    /// JMdict entry id -> examples of entry
+    links: BTreeMap<u32, Vec<ExampleIdx>>,
+}
+
+impl ExamplesBuilder {
+    /// Translations not in `languages` are discarded
+    pub fn new(languages: &[String]) -> Self {
+        ExamplesBuilder {
+            languages: languages.to_vec(),
+            sentences: vec![],
+            sentence_idxs: HashMap::new(),
+            links: BTreeMap::new(),
+        }
+    }
+
+    /// `examples` are pairs of sense idx and example, as returned by [`take_examples`].
+    ///
+    /// [`take_examples`]: crate::jmdict::take_examples
+    pub fn add(&mut self, entry_id: u32, examples: Vec<(u16, JMExample)>) {
+        for (sense_idx, example) in examples {
+            let key = (example.source_type, example.source_id);
+            let sentence_idx = match self.sentence_idxs.get(&key) {
+                Some(idx) => *idx,
+                None => {
+                    let mut sentence = String::new();
+                    let mut translations = vec![];
+                    for sent in example.sentences {
+                        if sent.lang == JAPANESE_LANGUAGE {
+                            sentence = sent.text;
+                        } else if self.languages.contains(&sent.lang) {
+                            translations.push(ExampleTranslation {
+                                lang: sent.lang,
+                                text: sent.text,
+                            });
+                        }
+                    }
+                    if sentence.is_empty() {
+                        continue;
+                    }
+                    let idx = self.sentences.len() as u32;
+                    self.sentences.push(ExampleSentence {
+                        source: key.0.clone(),
+                        source_id: key.1.clone(),
+                        sentence,
+                        translations,
+                    });
+                    self.sentence_idxs.insert(key, idx);
+                    idx
+                }
+            };
+            self.links.entry(entry_id).or_default().push(ExampleIdx {
+                sense_idx,
+                sentence_idx,
+            });
+        }
+    }
+
+    /// Removes examples linked to entry.
+    ///
+    /// Sentences are kept, as they may be linked to other entries.
+    pub fn remove(&mut self, entry_id: u32) {
+        self.links.remove(&entry_id);
+    }
+}
+
+impl DictionaryView<'_> {
+    /// Returns example sentences of `entry`, sorted by sense.
+    ///
+    /// Returns empty vec if dictionary was built without examples.
+    pub fn examples_for_entry(&self, entry: &WordEntry) -> Result<Vec<EntryExample>> {
+        let mut idxs = self.example_index.entry_index.get(entry.id.to_string())?;
+        // stable sort keeps sentence order within sense
+        idxs.sort_by_key(|idx| idx.sense_idx);
+        idxs.into_iter()
+            .map(|idx| {
+                let example = self
+                    .example_index
+                    .sentences
+                    .get(idx.sentence_idx as usize)?;
+                Ok(EntryExample {
+                    sense_idx: idx.sense_idx,
+                    example,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,280 @@
+//! Edit distance (Levenshtein) search of terms and meanings,
+//! for queries that do not match any key exactly because of a typo.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::automaton::CharAutomaton;
+use crate::dictionary::DictionaryView;
+use crate::entry::Entry;
+use crate::index::{EntryIdx, WordEntryIdx};
+use crate::meaning::{normalize_meaning, split_meaning_index_words, MeaningIdx};
+use crate::term_rank::term_priority;
+use crate::Result;
+
+/// Maximum number of index keys closest to a query (or a word of meaning query)
+/// that entries are read for.
+pub const MAX_FUZZY_SEARCH_KEYS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzySearchItem {
+    /// Term, or space separated meaning words, that `entry` was found with
+    pub matched: String,
+    /// Edit distance between query and `matched`
+    pub distance: u32,
+    pub entry: Entry,
+}
+
+impl DictionaryView<'_> {
+    /// Searches terms within edit distance `max_distance` of `query`,
+    /// ranked by distance, then by priority of term.
+    ///
+    /// Search-only forms are ranked last among terms of the same distance.
+    pub fn search_term_fuzzy(
+        &self,
+        query: &str,
+        max_distance: u32,
+    ) -> Result<Vec<FuzzySearchItem>> {
+        let max_distance = clamp_distance(query, max_distance);
+        if query.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut found: HashMap<EntryIdx, (FuzzySearchItem, bool, Reverse<u16>)> = HashMap::new();
+        for (term, distance, idxs) in
+            self.term_index
+                .items_within_distance(query, max_distance, MAX_FUZZY_SEARCH_KEYS)?
+        {
+            for idx in idxs {
+                let entry = self.get_entry(&idx)?;
+                let (priority, search_only) = term_priority(&entry, &term)?;
+                let rank = (distance, search_only, Reverse(priority));
+                if let Some((item, search_only, priority)) = found.get(&idx) {
+                    if (item.distance, *search_only, *priority) <= rank {
+                        continue;
+                    }
+                }
+                let item = FuzzySearchItem {
+                    matched: term.clone(),
+                    distance,
+                    entry,
+                };
+                found.insert(idx, (item, search_only, Reverse(priority)));
+            }
+        }
+
+        let mut ranked: Vec<_> = found.into_iter().collect();
+        // sort by entry idx last for deterministic order
+        ranked.sort_by_key(|(idx, (item, search_only, priority))| {
+            (item.distance, *search_only, *priority, *idx)
+        });
+        Ok(ranked.into_iter().map(|(_, (item, _, _))| item).collect())
+    }
+
+    /// Searches meaning in the primary (first) gloss language of dictionary,
+    /// matching each word of `query` to meaning index keys within edit distance.
+    ///
+    /// The sum of distances of all words is at most `max_distance`.
+    /// Results are ranked by distance, then by priority of entry.
+    pub fn search_meaning_fuzzy(
+        &self,
+        query: &str,
+        max_distance: u32,
+    ) -> Result<Vec<FuzzySearchItem>> {
+        let Some(meaning_index) = self.meaning_indexes.first() else {
+            return Ok(vec![]);
+        };
+        let normalized = normalize_meaning(query);
+        let words = split_meaning_index_words(&normalized);
+        if words.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // meaning -> (sum of distances, matched key of each word)
+        let mut matched_meanings: Option<HashMap<MeaningIdx, (u32, Vec<String>)>> = None;
+        for word in &words {
+            let word_distance = clamp_distance(word, max_distance);
+            let mut word_meanings: HashMap<MeaningIdx, (u32, String)> = HashMap::new();
+            // keys are returned closest first, so the first key of a meaning is its closest
+            for (key, distance, idxs) in
+                meaning_index.items_within_distance(word, word_distance, MAX_FUZZY_SEARCH_KEYS)?
+            {
+                for idx in idxs {
+                    word_meanings
+                        .entry(idx)
+                        .or_insert_with(|| (distance, key.clone()));
+                }
+            }
+
+            let meanings = match matched_meanings {
+                None => word_meanings
+                    .into_iter()
+                    .map(|(idx, (distance, key))| (idx, (distance, vec![key])))
+                    .collect(),
+                Some(mut meanings) => {
+                    meanings.retain(|idx, (total, keys)| match word_meanings.remove(idx) {
+                        Some((distance, key)) => {
+                            *total += distance;
+                            keys.push(key);
+                            true
+                        }
+                        None => false,
+                    });
+                    meanings
+                }
+            };
+            matched_meanings = Some(meanings);
+        }
+
+        // closest meaning of each entry
+        let mut entry_matches: HashMap<WordEntryIdx, (u32, Vec<String>)> = HashMap::new();
+        for (idx, (distance, keys)) in matched_meanings.unwrap_or_default() {
+            if distance > max_distance {
+                continue;
+            }
+            let closest = entry_matches
+                .entry(*idx.word_entry_idx())
+                .or_insert_with(|| (distance, keys.clone()));
+            if (distance, &keys) < (closest.0, &closest.1) {
+                *closest = (distance, keys);
+            }
+        }
+
+        let mut ranked = entry_matches
+            .into_iter()
+            .map(|(idx, (distance, keys))| {
+                let entry = self.get_word_entry(&idx)?;
+                let item = FuzzySearchItem {
+                    matched: keys.join(" "),
+                    distance,
+                    entry: Entry::Word(entry),
+                };
+                Ok((idx, item))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // sort by entry idx last for deterministic order
+        ranked.sort_by_key(|(idx, item)| (item.distance, Reverse(item.entry.priority()), *idx));
+        Ok(ranked.into_iter().map(|(_, item)| item).collect())
+    }
+}
+
+/// Limits distance to less than the number of characters in `query`,
+/// so that a short query does not match every short key.
+fn clamp_distance(query: &str, max_distance: u32) -> u32 {
+    let len = query.chars().count() as u32;
+    max_distance.min(len.saturating_sub(1))
+}
+
+/// Automaton that matches keys within edit distance `max_distance` of `query`,
+/// where distance is counted in characters.
+pub(crate) struct Levenshtein {
+    query: Vec<char>,
+    max_distance: u32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LevenshteinState {
+    /// `row[i]` is the edit distance between first `i` characters of query and key read so far
+    row: Vec<u32>,
+}
+
+impl LevenshteinState {
+    /// Edit distance between query and key read so far
+    pub fn distance(&self) -> u32 {
+        *self.row.last().unwrap()
+    }
+}
+
+impl Levenshtein {
+    pub fn new(query: &str, max_distance: u32) -> Self {
+        Self {
+            query: query.chars().collect(),
+            max_distance,
+        }
+    }
+}
+
+impl CharAutomaton for Levenshtein {
+    type State = LevenshteinState;
+
+    fn start(&self) -> LevenshteinState {
+        LevenshteinState {
+            row: (0..=self.query.len() as u32).collect(),
+        }
+    }
+
+    fn is_match(&self, state: &LevenshteinState) -> bool {
+        state.distance() <= self.max_distance
+    }
+
+    fn can_match(&self, state: &LevenshteinState) -> bool {
+        state.row.iter().any(|d| *d <= self.max_distance)
+    }
+
+    fn accept_char(&self, state: &LevenshteinState, ch: char) -> LevenshteinState {
+        let row = &state.row;
+        let mut next = Vec::with_capacity(row.len());
+        next.push(row[0] + 1);
+        for (i, query_ch) in self.query.iter().enumerate() {
+            let substitution = row[i] + u32::from(*query_ch != ch);
+            let distance = substitution.min(row[i + 1] + 1).min(next[i] + 1);
+            next.push(distance);
+        }
+        LevenshteinState { row: next }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fst::{IntoStreamer, Set, Streamer};
+
+    use super::Levenshtein;
+    use crate::automaton::Chars;
+
+    fn matches(keys: &[&str], query: &str, max_distance: u32) -> Vec<(String, u32)> {
+        let mut keys = keys.to_vec();
+        keys.sort();
+        let set = Set::from_iter(keys).unwrap();
+        let mut stream = set
+            .search_with_state(Chars(Levenshtein::new(query, max_distance)))
+            .into_stream();
+        let mut matched = vec![];
+        while let Some((key, state)) = stream.next() {
+            let distance = state.inner().unwrap().distance();
+            matched.push((String::from_utf8(key.to_vec()).unwrap(), distance));
+        }
+        matched
+    }
+
+    #[test]
+    fn distance_is_counted_in_characters() {
+        let keys = [
+            "cat",
+            "cats",
+            "cut",
+            "dog",
+            "ねこ",
+            "ねこぜ",
+            "ねっこ",
+            "なえ",
+        ];
+        assert_eq!(
+            matches(&keys, "cat", 1),
+            [("cat".into(), 0), ("cats".into(), 1), ("cut".into(), 1)]
+        );
+        assert_eq!(
+            matches(&keys, "ねこ", 1),
+            [
+                ("ねこ".into(), 0),
+                ("ねこぜ".into(), 1),
+                ("ねっこ".into(), 1)
+            ]
+        );
+        assert_eq!(matches(&keys, "ねこ", 0), [("ねこ".into(), 0)]);
+        assert!(matches(&keys, "bird", 1).is_empty());
+    }
+}
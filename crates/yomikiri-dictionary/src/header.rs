@@ -0,0 +1,278 @@
+use std::io::{self, Write};
+use std::ops::Range;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{Error, Result, SCHEMA_VER};
+
+/// Bytes at the start of every dictionary file
+pub const MAGIC: &[u8; 8] = b"YOMIKIRI";
+
+/// Sections of dictionary file, in the order they are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    TermIndex,
+    MeaningIndexes,
+    Entries,
+    NameEntries,
+    RadicalIndex,
+    ExampleIndex,
+    Metadata,
+}
+
+impl Section {
+    pub const ALL: [Section; 7] = [
+        Section::TermIndex,
+        Section::MeaningIndexes,
+        Section::Entries,
+        Section::NameEntries,
+        Section::RadicalIndex,
+        Section::ExampleIndex,
+        Section::Metadata,
+    ];
+}
+
+const SECTION_COUNT: usize = Section::ALL.len();
+
+/// Fixed size header at the start of dictionary file.
+///
+/// Header is checked before any section is decoded,
+/// so that a file of another schema version, or a truncated file,
+/// is rejected with a specific error.
+/// Checksum of content is only checked by [`DictionaryHeader::verify_checksum`].
+/*
+  Structure (bytes, little endian):
+  1. magic bytes `MAGIC` (8)
+  2. schema version (2)
+  3. number of sections (2)
+  4. section table
+    repeated 'number of sections' times
+      - starting byte-position of section, relative to content (8)
+      - byte length of section (8)
+  5. byte length of content (8)
+  6. CRC-32 checksum of content (4)
+
+  Content, which is made up of all sections laid out contiguously, follows the header.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryHeader {
+    pub schema_ver: u16,
+    sections: [Range<usize>; SECTION_COUNT],
+    content_len: usize,
+    checksum: u32,
+}
+
+impl DictionaryHeader {
+    /// Byte length of encoded header
+    pub const LEN: usize = MAGIC.len() + 2 + 2 + SECTION_COUNT * 16 + 8 + 4;
+
+    /// `section_ends` are the ending byte-positions of each section within content,
+    /// in the order of [`Section::ALL`].
+    fn from_section_ends(
+        section_ends: &[usize],
+        content_len: usize,
+        checksum: u32,
+    ) -> Result<Self> {
+        if section_ends.len() != SECTION_COUNT {
+            return Err(Error::InvalidHeader(format!(
+                "Expected {} sections, got {}",
+                SECTION_COUNT,
+                section_ends.len()
+            )));
+        }
+        let mut sections: [Range<usize>; SECTION_COUNT] = Default::default();
+        let mut start = 0;
+        for (section, &end) in sections.iter_mut().zip(section_ends) {
+            *section = start..end;
+            start = end;
+        }
+        let header = DictionaryHeader {
+            schema_ver: SCHEMA_VER,
+            sections,
+            content_len,
+            checksum,
+        };
+        header.validate_sections()?;
+        Ok(header)
+    }
+
+    /// Byte range of `section` within content
+    pub fn section(&self, section: Section) -> Range<usize> {
+        self.sections[section as usize].clone()
+    }
+
+    pub fn content_len(&self) -> usize {
+        self.content_len
+    }
+
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
+    pub fn encode_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_u16::<LittleEndian>(self.schema_ver)?;
+        writer.write_u16::<LittleEndian>(SECTION_COUNT as u16)?;
+        for section in &self.sections {
+            writer.write_u64::<LittleEndian>(section.start as u64)?;
+            writer.write_u64::<LittleEndian>(section.len() as u64)?;
+        }
+        writer.write_u64::<LittleEndian>(self.content_len as u64)?;
+        writer.write_u32::<LittleEndian>(self.checksum)?;
+        Ok(())
+    }
+
+    /// Decodes header without checking content.
+    ///
+    /// Fails if `source` is not a dictionary file, or is of another schema version.
+    pub fn try_decode(source: &[u8]) -> Result<(Self, usize)> {
+        if source.len() < MAGIC.len() + 2 {
+            return Err(Error::Truncated {
+                expected: Self::LEN,
+                actual: source.len(),
+            });
+        }
+        if &source[..MAGIC.len()] != MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        let mut bytes = &source[MAGIC.len()..];
+        let schema_ver = bytes.read_u16::<LittleEndian>()?;
+        if schema_ver != SCHEMA_VER {
+            return Err(Error::SchemaVersionMismatch {
+                expected: SCHEMA_VER,
+                actual: schema_ver,
+            });
+        }
+        if source.len() < Self::LEN {
+            return Err(Error::Truncated {
+                expected: Self::LEN,
+                actual: source.len(),
+            });
+        }
+
+        let section_count = bytes.read_u16::<LittleEndian>()? as usize;
+        if section_count != SECTION_COUNT {
+            return Err(Error::InvalidHeader(format!(
+                "Expected {} sections, got {}",
+                SECTION_COUNT, section_count
+            )));
+        }
+        let mut sections: [Range<usize>; SECTION_COUNT] = Default::default();
+        for section in &mut sections {
+            let start = read_usize(&mut bytes)?;
+            let len = read_usize(&mut bytes)?;
+            let end = start
+                .checked_add(len)
+                .ok_or_else(|| Error::InvalidHeader("Section is out of range".into()))?;
+            *section = start..end;
+        }
+        let content_len = read_usize(&mut bytes)?;
+        let checksum = bytes.read_u32::<LittleEndian>()?;
+
+        let header = DictionaryHeader {
+            schema_ver,
+            sections,
+            content_len,
+            checksum,
+        };
+        header.validate_sections()?;
+        Ok((header, Self::LEN))
+    }
+
+    /// Decodes header and returns content that follows it.
+    ///
+    /// Fails if content is truncated. Content is not checked against checksum in header.
+    pub fn try_decode_with_content(source: &[u8]) -> Result<(Self, &[u8])> {
+        let (header, len) = Self::try_decode(source)?;
+        let content = &source[len..];
+        if content.len() < header.content_len {
+            return Err(Error::Truncated {
+                expected: len.saturating_add(header.content_len),
+                actual: source.len(),
+            });
+        }
+        let content = &content[..header.content_len];
+        Ok((header, content))
+    }
+
+    /// Checks `content` against checksum in header.
+    ///
+    /// This hashes the whole content, so it is not done when dictionary is decoded on every load.
+    pub fn verify_checksum(&self, content: &[u8]) -> Result<()> {
+        let checksum = crc32fast::hash(content);
+        if checksum != self.checksum {
+            return Err(Error::ChecksumMismatch {
+                expected: self.checksum,
+                actual: checksum,
+            });
+        }
+        Ok(())
+    }
+
+    /// Sections must be contiguous and in order, and end at the end of content
+    fn validate_sections(&self) -> Result<()> {
+        let mut at = 0;
+        for (section, range) in Section::ALL.iter().zip(&self.sections) {
+            if range.start != at || range.end < range.start {
+                return Err(Error::InvalidHeader(format!(
+                    "Section {:?} is out of place",
+                    section
+                )));
+            }
+            at = range.end;
+        }
+        if at != self.content_len {
+            return Err(Error::InvalidHeader(
+                "Sections do not cover the whole content".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Writes content section by section,
+/// keeping track of what is needed to create header after content is written.
+pub(crate) struct ContentWriter<'w, W: Write> {
+    writer: &'w mut W,
+    len: usize,
+    hasher: crc32fast::Hasher,
+    section_ends: Vec<usize>,
+}
+
+impl<'w, W: Write> ContentWriter<'w, W> {
+    pub fn new(writer: &'w mut W) -> Self {
+        Self {
+            writer,
+            len: 0,
+            hasher: crc32fast::Hasher::new(),
+            section_ends: Vec::with_capacity(SECTION_COUNT),
+        }
+    }
+
+    /// Marks the end of current section
+    pub fn end_section(&mut self) {
+        self.section_ends.push(self.len);
+    }
+
+    pub fn finish(self) -> Result<DictionaryHeader> {
+        DictionaryHeader::from_section_ends(&self.section_ends, self.len, self.hasher.finalize())
+    }
+}
+
+impl<W: Write> Write for ContentWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.len += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn read_usize(bytes: &mut &[u8]) -> Result<usize> {
+    let value = bytes.read_u64::<LittleEndian>()?;
+    Ok(usize::try_from(value)?)
+}
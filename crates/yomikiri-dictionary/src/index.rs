@@ -1,17 +1,17 @@
-use std::collections::HashMap;
+use std::collections::BinaryHeap;
 use std::fmt::Debug;
 use std::io::Write;
 use std::marker::PhantomData;
 use std::ops::Deref;
 
 use fst::{IntoStreamer, MapBuilder, Streamer};
-use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::de::Error as DeserializeError;
 use serde::{Deserialize, Serialize};
 
-use crate::entry::NameEntry;
-use crate::error::Result;
-use crate::WordEntry;
+use crate::automaton::Chars;
+use crate::error::{Error, Result};
+use crate::fuzzy::Levenshtein;
 
 /// Trait that all dictionary index types implement
 pub trait EncodableIdx: Sized + Debug + Serialize + for<'de> Deserialize<'de> {}
@@ -22,7 +22,9 @@ pub enum EntryIdx {
     Name(NameEntryIdx),
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub struct WordEntryIdx(pub(crate) u32);
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
@@ -69,6 +71,8 @@ impl<'de> Deserialize<'de> for EntryIdx {
 
 impl EncodableIdx for EntryIdx {}
 
+impl EncodableIdx for WordEntryIdx {}
+
 /// Multiple jmdict entry indexes that corresponds to a key
 #[derive(Debug)]
 pub struct DictIndexItem<T: EncodableIdx> {
@@ -108,9 +112,19 @@ impl<'a> Deref for Map<'a> {
 }
 
 impl<'a> Map<'a> {
-    fn new(bytes: &'a [u8]) -> fst::Result<Self> {
+    pub(crate) fn new(bytes: &'a [u8]) -> fst::Result<Self> {
         fst::Map::new(bytes).map(Self)
     }
+
+    /// fst may panic when traversing malformed bytes,
+    /// so bytes are checked against the checksum stored in fst before use.
+    ///
+    /// This hashes index bytes only, not the whole dictionary content.
+    fn new_verified(bytes: &'a [u8]) -> fst::Result<Self> {
+        let map = Self::new(bytes)?;
+        map.as_fst().verify()?;
+        Ok(map)
+    }
 }
 
 impl Serialize for Map<'_> {
@@ -131,7 +145,7 @@ where
         D: serde::Deserializer<'de>,
     {
         let bytes = <&'a [u8]>::deserialize(deserializer)?;
-        Self::new(bytes).map_err(DeserializeError::custom)
+        Self::new_verified(bytes).map_err(DeserializeError::custom)
     }
 }
 
@@ -166,13 +180,52 @@ impl<'a, T: EncodableIdx> DictIndexMap<'a, T> {
             .is_some()
     }
 
+    /// Calls `f` with every key and its indexes, in key order
+    pub fn for_each_item<F>(&self, mut f: F)
+    where
+        F: FnMut(&[u8], Result<Vec<T>>),
+    {
+        let mut stream = self.map.stream();
+        while let Some((key, value)) = stream.next() {
+            f(key, self.parse_value(value));
+        }
+    }
+
+    /// Returns up to `limit` keys within edit distance `max_distance` of `query`
+    /// with their distance and indexes, closest first and then in key order
+    pub fn items_within_distance(
+        &self,
+        query: &str,
+        max_distance: u32,
+        limit: usize,
+    ) -> Result<Vec<(String, u32, Vec<T>)>> {
+        let mut stream = self
+            .map
+            .search_with_state(Chars(Levenshtein::new(query, max_distance)))
+            .into_stream();
+        let mut closest = BestKeys::new(limit);
+        while let Some((key, value, state)) = stream.next() {
+            if let Some(levenshtein) = state.inner() {
+                closest.push(levenshtein.distance(), key, value);
+            }
+        }
+        closest
+            .into_sorted_vec()?
+            .into_iter()
+            .map(|(distance, key, value)| Ok((key, distance, self.parse_value(value)?)))
+            .collect()
+    }
+
     fn parse_value(&self, value: u64) -> Result<Vec<T>> {
         if value & (1_u64 << 63) == 0 {
             let idx: T = postcard::from_bytes(&value.to_le_bytes())?;
             Ok(vec![idx])
         } else {
             let idx = (value & ((1_u64 << 63) - 1)) as usize;
-            let idxs = postcard::from_bytes::<Vec<T>>(&self.idxs_storage[idx..])?;
+            let bytes = self.idxs_storage.get(idx..).ok_or_else(|| {
+                Error::InvalidData(format!("Index storage position is out of range: {}", idx))
+            })?;
+            let idxs = postcard::from_bytes::<Vec<T>>(bytes)?;
             Ok(idxs)
         }
     }
@@ -181,34 +234,85 @@ impl<'a, T: EncodableIdx> DictIndexMap<'a, T> {
         items: &[DictIndexItem<T>],
         writer: &mut W,
     ) -> Result<()> {
-        let mut buffer = Vec::with_capacity(16 * items.len());
-        let mut builder = MapBuilder::new(&mut buffer)?;
-        // stores arrays of indexes contiguously
-        let mut idxs_storage: Vec<u8> = vec![];
-
+        let mut builder = DictIndexMapBuilder::with_capacity(16 * items.len())?;
         for item in items {
-            if item.entry_indexes.len() == 1 {
-                let index = &item.entry_indexes[0];
-                let encoded = postcard::to_stdvec(&index)?;
-                if let Some(val) = single_storable(&encoded) {
-                    builder.insert(&item.key, val)?;
-                    continue;
+            builder.insert(&item.key, &item.entry_indexes)?;
+        }
+        builder.finish_and_encode_to(writer)
+    }
+
+    /// Builds index map from key-index pairs sorted by key,
+    /// so that indexes of all keys are not held in memory at once.
+    pub(crate) fn build_and_encode_sorted_to<W, I>(pairs: I, writer: &mut W) -> Result<()>
+    where
+        W: Write,
+        I: Iterator<Item = Result<(String, T)>>,
+    {
+        let mut builder = DictIndexMapBuilder::with_capacity(0)?;
+        let mut key = String::new();
+        let mut idxs: Vec<T> = vec![];
+        for pair in pairs {
+            let (next_key, idx) = pair?;
+            if next_key != key {
+                if !idxs.is_empty() {
+                    builder.insert(&key, &idxs)?;
+                    idxs.clear();
                 }
+                key = next_key;
             }
-            // Store idxes into separate array, and store index within array as fst::map value
-            let mut term_ids: Vec<&T> = vec![];
-            for index in &item.entry_indexes {
-                term_ids.push(index);
+            idxs.push(idx);
+        }
+        if !idxs.is_empty() {
+            builder.insert(&key, &idxs)?;
+        }
+        builder.finish_and_encode_to(writer)
+    }
+
+    pub fn try_decode(source: &'a [u8]) -> Result<(Self, usize)> {
+        let start = source.len();
+        let (idx_map, rest) = postcard::take_from_bytes(source)?;
+        Ok((idx_map, start - rest.len()))
+    }
+}
+
+struct DictIndexMapBuilder<T: EncodableIdx> {
+    builder: MapBuilder<Vec<u8>>,
+    /// stores arrays of indexes contiguously
+    idxs_storage: Vec<u8>,
+    _typ: PhantomData<T>,
+}
+
+impl<T: EncodableIdx> DictIndexMapBuilder<T> {
+    fn with_capacity(capacity: usize) -> Result<Self> {
+        Ok(Self {
+            builder: MapBuilder::new(Vec::with_capacity(capacity))?,
+            idxs_storage: vec![],
+            _typ: PhantomData,
+        })
+    }
+
+    /// Keys must be inserted in lexicographic order
+    fn insert(&mut self, key: &str, idxs: &[T]) -> Result<()> {
+        if idxs.len() == 1 {
+            let encoded = postcard::to_stdvec(&idxs[0])?;
+            if let Some(val) = single_storable(&encoded) {
+                self.builder.insert(key, val)?;
+                return Ok(());
             }
-            debug_assert!((idxs_storage.len() as u64) < (1_u64 << 63));
-            builder.insert(&item.key, (1_u64 << 63) | idxs_storage.len() as u64)?;
-            postcard::to_io(&term_ids, &mut idxs_storage)?;
         }
-        builder.finish()?;
+        // Store idxes into separate array, and store index within array as fst::map value
+        debug_assert!((self.idxs_storage.len() as u64) < (1_u64 << 63));
+        self.builder
+            .insert(key, (1_u64 << 63) | self.idxs_storage.len() as u64)?;
+        postcard::to_io(idxs, &mut self.idxs_storage)?;
+        Ok(())
+    }
 
+    fn finish_and_encode_to<W: Write>(self, writer: &mut W) -> Result<()> {
+        let buffer = self.builder.into_inner()?;
         let idx_map: DictIndexMap<T> = DictIndexMap {
             map: Map::new(&buffer)?,
-            idxs_storage: &idxs_storage,
+            idxs_storage: &self.idxs_storage,
             _typ: PhantomData,
         };
 
@@ -216,55 +320,51 @@ impl<'a, T: EncodableIdx> DictIndexMap<'a, T> {
         postcard::to_io(&idx_map, &mut *writer)?;
         Ok(())
     }
+}
 
-    pub fn try_decode(source: &'a [u8]) -> Result<(Self, usize)> {
-        let start = source.len();
-        let (idx_map, rest) = postcard::take_from_bytes(source)?;
-        Ok((idx_map, start - rest.len()))
-    }
+/// Keeps up to `limit` keys of lowest rank, from keys that are pushed in key order.
+///
+/// Keys of the same rank are kept in key order.
+pub(crate) struct BestKeys<R: Ord, V: Ord> {
+    limit: usize,
+    /// Last ranked key on top
+    heap: BinaryHeap<(R, Vec<u8>, V)>,
 }
 
-pub(crate) fn create_sorted_term_indexes(
-    name_entries: &[NameEntry],
-    entries: &[WordEntry],
-) -> Result<Vec<DictIndexItem<EntryIdx>>> {
-    // some entries have multiple terms
-    let mut indexes: HashMap<&str, Vec<EntryIdx>> = HashMap::with_capacity(entries.len() * 4);
-
-    for (i, entry) in entries.iter().enumerate() {
-        for term in entry
-            .kanjis
-            .iter()
-            .map(|k| &k.kanji)
-            .chain(entry.readings.iter().map(|r| &r.reading))
-        {
-            let idx = WordEntryIdx(i as u32).entry_idx();
-            indexes
-                .entry(term)
-                .and_modify(|v| v.push(idx))
-                .or_insert_with(|| vec![idx]);
+impl<R: Ord, V: Ord> BestKeys<R, V> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::new(),
         }
     }
 
-    for (i, entry) in name_entries.iter().enumerate() {
-        let idx = NameEntryIdx(i as u32).entry_idx();
-        let term = &entry.kanji;
-        indexes
-            .entry(term)
-            .and_modify(|v| v.push(idx))
-            .or_insert_with(|| vec![idx]);
+    pub fn push(&mut self, rank: R, key: &[u8], value: V) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.heap.len() == self.limit {
+            // a key of the same rank that is pushed later is ranked later
+            if self.heap.peek().is_some_and(|(worst, _, _)| rank >= *worst) {
+                return;
+            }
+            self.heap.pop();
+        }
+        self.heap.push((rank, key.to_vec(), value));
     }
 
-    let indexes: Vec<DictIndexItem<EntryIdx>> = indexes
-        .into_iter()
-        .map(|(term, indexes)| DictIndexItem {
-            key: term.to_string(),
-            entry_indexes: indexes,
-        })
-        .sorted_by(|a, b| a.key.cmp(&b.key))
-        .collect();
-
-    Ok(indexes)
+    /// Returns kept keys, ranked first first
+    pub fn into_sorted_vec(self) -> Result<Vec<(R, String, V)>> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(rank, key, value)| {
+                let key = String::from_utf8(key)
+                    .map_err(|_| Error::InvalidData("Index key is not valid UTF-8".into()))?;
+                Ok((rank, key, value))
+            })
+            .collect()
+    }
 }
 
 fn increment_bytes(bytes: &mut Vec<u8>) {
@@ -2,8 +2,11 @@ use std::io::Write;
 use std::marker::PhantomData;
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::parallel::{map_in_order, BATCH_LEN};
+use crate::spill::ItemSpill;
 use crate::{Error, Result};
 
 /// Vector of objects that reads from byte slice without copy
@@ -55,7 +58,9 @@ where
         }
 
         let (item_start, item_end) = self.item_position(index)?;
-        let item_bytes = &self.data[item_start..item_end];
+        let item_bytes = self.data.get(item_start..item_end).ok_or_else(|| {
+            Error::InvalidData(format!("Item {} of jagged array is out of range", index))
+        })?;
         let item = postcard::from_bytes(item_bytes)?;
 
         Ok(item)
@@ -65,13 +70,15 @@ where
     /// index is assumed to be valid
     fn item_position(&self, index: usize) -> Result<(usize, usize)> {
         let at = index * 4;
-        let mut bytes = &self.data[at..at + 4];
-        let start = bytes.read_u32::<LittleEndian>()? as usize;
-        let mut bytes = &self.data[at + 4..at + 8];
-        let end = bytes.read_u32::<LittleEndian>()? as usize;
+        let start = read_u32_at(self.data, at)? as usize;
+        let end = read_u32_at(self.data, at + 4)? as usize;
         let base = self.items_start();
 
-        Ok((base + start, base + end))
+        let out_of_range =
+            || Error::InvalidData(format!("Item {} of jagged array is out of range", index));
+        let start = base.checked_add(start).ok_or_else(out_of_range)?;
+        let end = base.checked_add(end).ok_or_else(out_of_range)?;
+        Ok((start, end))
     }
 
     fn items_start(&self) -> usize {
@@ -91,11 +98,77 @@ where
         Ok(())
     }
 
+    /// Encodes items of `spill` transformed by `f`, without holding all items in memory.
+    ///
+    /// As item positions are written before items, `f` is called twice for each item:
+    /// once to measure encoded item, and once to write it.
+    pub(crate) fn build_and_encode_spill_to<W, S, F>(
+        spill: &mut ItemSpill<S>,
+        f: F,
+        writer: &mut W,
+    ) -> Result<()>
+    where
+        W: Write,
+        S: Serialize + DeserializeOwned + Clone + Send,
+        T: Send,
+        F: Fn(usize, S) -> T + Sync,
+    {
+        postcard::to_io(&spill.len(), &mut *writer)?;
+
+        let mut position: usize = 0;
+        Self::for_each_encoded_batch(spill, &f, |item_bytes| {
+            writer.write_u32::<LittleEndian>(position.try_into()?)?;
+            position += item_bytes.len();
+            Ok(())
+        })?;
+        writer.write_u32::<LittleEndian>(position.try_into()?)?;
+        Self::for_each_encoded_batch(spill, &f, |item_bytes| {
+            writer.write_all(item_bytes)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Items are encoded in batches, in parallel if `parallel` feature is enabled
+    fn for_each_encoded_batch<S, F, G>(spill: &mut ItemSpill<S>, f: &F, mut g: G) -> Result<()>
+    where
+        S: Serialize + DeserializeOwned + Clone + Send,
+        T: Send,
+        F: Fn(usize, S) -> T + Sync,
+        G: FnMut(&[u8]) -> Result<()>,
+    {
+        let mut items = spill.iter()?;
+        let mut i = 0;
+        loop {
+            let batch: Vec<(usize, S)> = items
+                .next_batch(BATCH_LEN)?
+                .into_iter()
+                .enumerate()
+                .map(|(j, item)| (i + j, item))
+                .collect();
+            if batch.is_empty() {
+                return Ok(());
+            }
+            i += batch.len();
+            let encoded = map_in_order(batch, |(i, item)| postcard::to_stdvec(&f(i, item)));
+            for item_bytes in encoded {
+                g(&item_bytes?)?;
+            }
+        }
+    }
+
     pub fn try_decode(source: &'a [u8]) -> Result<(Self, usize)> {
         let start = source.len();
-        let (cnt, source) = postcard::take_from_bytes(source)?;
-        let items_len = (&source[(cnt * 4)..((cnt + 1) * 4)]).read_u32::<LittleEndian>()? as usize;
-        let end_idx = (cnt + 1) * 4 + items_len;
+        let (cnt, source): (usize, &[u8]) = postcard::take_from_bytes(source)?;
+        let items_start = cnt
+            .checked_add(1)
+            .and_then(|n| n.checked_mul(4))
+            .ok_or_else(|| Error::InvalidData(format!("Jagged array is too long: {}", cnt)))?;
+        let items_len = read_u32_at(source, items_start - 4)? as usize;
+        let end_idx = items_start
+            .checked_add(items_len)
+            .filter(|end| *end <= source.len())
+            .ok_or_else(|| Error::InvalidData("Jagged array items are truncated".into()))?;
         let arr = Self {
             data: &source[0..end_idx],
             cnt,
@@ -111,6 +184,15 @@ where
     }
 }
 
+/// Reads little-endian u32 at `at`, failing if `data` is too short
+fn read_u32_at(data: &[u8], at: usize) -> Result<u32> {
+    let mut bytes = at
+        .checked_add(4)
+        .and_then(|end| data.get(at..end))
+        .ok_or_else(|| Error::InvalidData("Jagged array pointers are truncated".into()))?;
+    Ok(bytes.read_u32::<LittleEndian>()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::JaggedArray;
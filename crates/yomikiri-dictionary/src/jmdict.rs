@@ -1,9 +1,16 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::entry::{
-    GroupedSense, Kanji, PartOfSpeech, Rarity, Reading, Sense, WordEntry, WordEntryInner,
+    Gloss, GroupedSense, Kanji, PartOfSpeech, Rarity, Reading, Sense, SenseReference, WordEntry,
+    WordEntryInner,
 };
+use crate::index::WordEntryIdx;
 use crate::utils::nfkc_normalize;
-use crate::{Error, Result};
-use yomikiri_jmdict::jmdict::{JMEntry, JMKanji, JMKanjiInfo, JMReading, JMReadingInfo, JMSense};
+use crate::{Error, Result, DEFAULT_LANGUAGE};
+use yomikiri_jmdict::jmdict::{
+    JMDialect, JMEntry, JMExample, JMGloss, JMKanji, JMKanjiInfo, JMReading, JMReadingInfo,
+    JMReference, JMSense, JMSenseField, JMSenseMisc,
+};
 
 impl TryFrom<JMEntry> for WordEntry {
     type Error = Error;
@@ -26,44 +33,52 @@ impl TryFrom<JMEntry> for WordEntry {
 
 impl From<JMKanji> for Kanji {
     fn from(value: JMKanji) -> Self {
-        let rarity = value
-            .info
-            .iter()
-            .filter_map(Rarity::from_kanji)
-            .min()
-            .unwrap_or(Rarity::Normal);
+        let rarity = Rarity::of_kanji(&value.info);
 
         Self {
             rarity,
             kanji: value.kanji,
+            priority: value.priority,
         }
     }
 }
 
 impl From<JMReading> for Reading {
     fn from(jm_reading: JMReading) -> Self {
-        let rarity = jm_reading
-            .info
-            .iter()
-            .filter_map(Rarity::from_reading)
-            .min()
-            .unwrap_or(Rarity::Normal);
+        let rarity = Rarity::of_reading(&jm_reading.info);
 
         Reading {
             reading: jm_reading.reading,
             nokanji: jm_reading.nokanji,
             rarity,
             to_kanji: jm_reading.to_form,
+            priority: jm_reading.priority,
         }
     }
 }
 
 impl Rarity {
+    pub(crate) fn of_kanji(infos: &[JMKanjiInfo]) -> Rarity {
+        infos
+            .iter()
+            .filter_map(Rarity::from_kanji)
+            .min()
+            .unwrap_or(Rarity::Normal)
+    }
+
+    pub(crate) fn of_reading(infos: &[JMReadingInfo]) -> Rarity {
+        infos
+            .iter()
+            .filter_map(Rarity::from_reading)
+            .min()
+            .unwrap_or(Rarity::Normal)
+    }
+
     fn from_kanji(info: &JMKanjiInfo) -> Option<Rarity> {
         use JMKanjiInfo::*;
         use Rarity::*;
 
-        match *info {
+        match info {
             IrregularKana | IrregularKanji | IrregularOkurigana => Some(Incorrect),
             OutdatedKanji => Some(Outdated),
             RareKanjiForm => Some(Rare),
@@ -76,7 +91,7 @@ impl Rarity {
         use JMReadingInfo::*;
         use Rarity::*;
 
-        match *info {
+        match info {
             Irregular => Some(Incorrect),
             JMReadingInfo::Outdated => Some(Rarity::Outdated),
             JMReadingInfo::Rare => Some(Rarity::Rare),
@@ -91,46 +106,328 @@ impl From<JMSense> for Sense {
         Sense {
             to_kanji: jm_sense.to_form,
             to_reading: jm_sense.to_reading,
-            misc: jm_sense.misc,
+            references: jm_sense
+                .references
+                .into_iter()
+                .map(SenseReference::from)
+                .collect(),
+            antonyms: jm_sense
+                .antonyms
+                .into_iter()
+                .map(SenseReference::from)
+                .collect(),
+            // unknown entities are reported as parser warnings, and cannot be displayed
+            fields: jm_sense
+                .fields
+                .into_iter()
+                .filter(|f| !matches!(f, JMSenseField::Other(_)))
+                .collect(),
+            misc: jm_sense
+                .misc
+                .into_iter()
+                .filter(|m| !matches!(m, JMSenseMisc::Other(_)))
+                .collect(),
             info: jm_sense
                 .info
                 .into_iter()
                 .map(|i| nfkc_normalize(i).into())
                 .collect(),
-            dialects: jm_sense.dialects,
-            meanings: jm_sense
-                .meanings
+            language_sources: jm_sense.language_sources,
+            dialects: jm_sense
+                .dialects
                 .into_iter()
-                .map(|i| nfkc_normalize(i).into())
+                .filter(|d| !matches!(d, JMDialect::Other(_)))
                 .collect(),
+            meanings: jm_sense.meanings.into_iter().map(Gloss::from).collect(),
         }
     }
 }
 
+impl From<JMGloss> for Gloss {
+    fn from(jm_gloss: JMGloss) -> Self {
+        Gloss {
+            text: nfkc_normalize(jm_gloss.text).into(),
+            lang: (jm_gloss.lang != DEFAULT_LANGUAGE).then_some(jm_gloss.lang),
+            gloss_type: jm_gloss.gloss_type,
+            gender: jm_gloss.gender,
+            highlighted: jm_gloss.highlighted,
+        }
+    }
+}
+
+impl From<JMReference> for SenseReference {
+    fn from(jm_reference: JMReference) -> Self {
+        SenseReference {
+            term: jm_reference.term,
+            reading: jm_reference.reading,
+            // converted to sense idx by `ReferenceResolver`
+            sense: jm_reference.sense,
+            entry_idx: None,
+        }
+    }
+}
+
+/// Removes glosses not in `languages`, and then senses without any gloss.
+///
+/// In JMdict, part-of-speech of an earlier sense applies to later senses
+/// that do not specify one. Non-English senses never specify part-of-speech,
+/// so it is copied over if any sense is removed.
+///
+/// Returns whether each sense of `entry` is kept.
+pub(crate) fn retain_languages(entry: &mut JMEntry, languages: &[String]) -> Vec<bool> {
+    for sense in &mut entry.senses {
+        sense
+            .meanings
+            .retain(|gloss| languages.contains(&gloss.lang));
+    }
+    let kept: Vec<bool> = entry
+        .senses
+        .iter()
+        .map(|sense| !sense.meanings.is_empty())
+        .collect();
+    if kept.contains(&false) {
+        let mut prev_pos = vec![];
+        for sense in &mut entry.senses {
+            if sense.pos.is_empty() {
+                sense.pos.clone_from(&prev_pos);
+            } else {
+                prev_pos.clone_from(&sense.pos);
+            }
+        }
+        entry.senses.retain(|sense| !sense.meanings.is_empty());
+    }
+    kept
+}
+
+/// Idx of JMdict senses in `WordEntry`,
+/// kept only for entries whose senses are reordered or removed.
+#[derive(Debug, Default)]
+pub(crate) struct SenseIdxMap {
+    /// entry id -> idx of each JMdict sense, `None` if sense is removed
+    entries: HashMap<u32, Vec<Option<u16>>>,
+}
+
+impl SenseIdxMap {
+    /// `kept` is returned from `retain_languages`, and `entry` must have its senses retained.
+    pub fn add(&mut self, entry: &JMEntry, kept: &[bool]) {
+        let mut grouped_idxs = grouped_sense_idxs(&entry.senses).into_iter();
+        let sense_idxs: Vec<Option<u16>> = kept
+            .iter()
+            .map(|kept| if *kept { grouped_idxs.next() } else { None })
+            .collect();
+        let is_identity = sense_idxs
+            .iter()
+            .enumerate()
+            .all(|(i, idx)| *idx == Some(i as u16));
+        if is_identity {
+            self.entries.remove(&entry.id);
+        } else {
+            self.entries.insert(entry.id, sense_idxs);
+        }
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.entries.remove(&id);
+    }
+
+    fn get(&self, entry: &WordEntry) -> Vec<Option<u16>> {
+        match self.entries.get(&entry.id) {
+            Some(sense_idxs) => sense_idxs.clone(),
+            None => {
+                let sense_count: usize = entry.grouped_senses.iter().map(|g| g.senses.len()).sum();
+                (0..sense_count as u16).map(Some).collect()
+            }
+        }
+    }
+}
+
+/// Removes examples from senses of `entry`,
+/// and returns them with the idx of their sense in `WordEntry`.
+///
+/// Must be called after `retain_languages`, which removes senses.
+pub(crate) fn take_examples(entry: &mut JMEntry) -> Vec<(u16, JMExample)> {
+    let sense_idxs = grouped_sense_idxs(&entry.senses);
+    let mut examples = vec![];
+    for (sense, sense_idx) in entry.senses.iter_mut().zip(sense_idxs) {
+        for example in sense.examples.drain(..) {
+            examples.push((sense_idx, example));
+        }
+    }
+    examples
+}
+
+/// Resolves `entry_idx` of sense references,
+/// and converts their JMdict sense number to sense idx of referenced entry.
+///
+/// As `WordEntryIdx` is the position of entry, all entries must be added in order
+/// with `add_references` and then `add_candidate`, before any entry is resolved.
+/// Only entries that contain a referenced term are kept.
+#[derive(Debug, Default)]
+pub(crate) struct ReferenceResolver {
+    referenced_terms: HashSet<String>,
+    /// referenced term -> idx of entries that contain it
+    terms: HashMap<String, Vec<usize>>,
+    candidates: HashMap<usize, ReferenceCandidate>,
+    sense_idxs: SenseIdxMap,
+}
+
+#[derive(Debug)]
+struct ReferenceCandidate {
+    readings: Vec<String>,
+    /// idx of each JMdict sense in entry, `None` if sense is removed
+    sense_idxs: Vec<Option<u16>>,
+}
+
+impl ReferenceResolver {
+    pub fn new(sense_idxs: SenseIdxMap) -> Self {
+        Self {
+            sense_idxs,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_references(&mut self, entry: &WordEntry) {
+        for sense in entry.grouped_senses.iter().flat_map(|g| &g.senses) {
+            for reference in sense.references.iter().chain(&sense.antonyms) {
+                self.referenced_terms.insert(reference.term.clone());
+                if let Some(reading) = &reference.reading {
+                    self.referenced_terms
+                        .insert(format!("{}・{}", reference.term, reading));
+                }
+            }
+        }
+    }
+
+    pub fn add_candidate(&mut self, idx: usize, entry: &WordEntry) {
+        let mut is_candidate = false;
+        for term in entry
+            .kanjis
+            .iter()
+            .map(|k| &k.kanji)
+            .chain(entry.readings.iter().map(|r| &r.reading))
+        {
+            if !self.referenced_terms.contains(term) {
+                continue;
+            }
+            is_candidate = true;
+            let idxs = self.terms.entry(term.clone()).or_default();
+            if idxs.last() != Some(&idx) {
+                idxs.push(idx);
+            }
+        }
+        if is_candidate {
+            let candidate = ReferenceCandidate {
+                readings: entry.readings.iter().map(|r| r.reading.clone()).collect(),
+                sense_idxs: self.sense_idxs.get(entry),
+            };
+            self.candidates.insert(idx, candidate);
+        }
+    }
+
+    /// Sets `entry_idx` and `sense` of all sense references in entry at `idx`
+    pub fn resolve(&self, idx: usize, mut entry: WordEntry) -> WordEntry {
+        let inner = entry.inner_mut();
+        for sense in inner.grouped_senses.iter_mut().flat_map(|g| &mut g.senses) {
+            for reference in sense.references.iter_mut().chain(&mut sense.antonyms) {
+                let found = self.find_referenced_entry(idx, reference);
+                // referenced sense may be removed if it has no gloss in dictionary languages
+                reference.sense = found.and_then(|i| {
+                    let sense = reference.sense?;
+                    self.candidates[&i].sense_idxs[usize::from(sense) - 1]
+                });
+                reference.entry_idx = found.map(|i| WordEntryIdx(i as u32));
+            }
+        }
+        entry
+    }
+
+    /// Finds entry that contains referenced term and reading.
+    ///
+    /// If multiple entries match, entry other than the referencing entry is preferred.
+    fn find_referenced_entry(&self, from: usize, reference: &SenseReference) -> Option<usize> {
+        let find = |term: &str, reading: Option<&str>| -> Option<usize> {
+            let candidates = self.terms.get(term)?.iter().copied().filter(|i| {
+                let Some(candidate) = self.candidates.get(i) else {
+                    return false;
+                };
+                let reading_matches =
+                    reading.is_none_or(|reading| candidate.readings.iter().any(|r| r == reading));
+                let sense_exists = reference.sense.is_none_or(|sense| {
+                    sense >= 1 && candidate.sense_idxs.len() >= usize::from(sense)
+                });
+                reading_matches && sense_exists
+            });
+            let mut found = None;
+            for i in candidates {
+                if i != from {
+                    return Some(i);
+                }
+                found = Some(i);
+            }
+            found
+        };
+
+        find(&reference.term, reference.reading.as_deref()).or_else(|| {
+            // term itself may contain '・', e.g. 'Ｔ・ジョイント'
+            let reading = reference.reading.as_ref()?;
+            let term = format!("{}・{}", reference.term, reading);
+            find(&term, None)
+        })
+    }
+}
+
+/// Groups senses by part-of-speech, in the order each part-of-speech first appears.
+///
+/// Returns part-of-speech of each group, and group of each sense.
+fn sense_groups(senses: &[JMSense]) -> (Vec<Vec<PartOfSpeech>>, Vec<usize>) {
+    let mut groups: Vec<Vec<PartOfSpeech>> = vec![];
+    let mut group_of_sense = Vec::with_capacity(senses.len());
+    for sense in senses {
+        let pos = PartOfSpeech::from_jmdict(&sense.pos);
+        let group = match groups.iter().position(|p| p == &pos) {
+            Some(group) => group,
+            None => {
+                groups.push(pos);
+                groups.len() - 1
+            }
+        };
+        group_of_sense.push(group);
+    }
+    (groups, group_of_sense)
+}
+
 fn group_senses(values: Vec<JMSense>) -> Vec<GroupedSense> {
-    let mut groups: Vec<GroupedSense> = vec![];
-    for value in values {
-        let pos = PartOfSpeech::from_jmdict(&value.pos);
-        let sense = Sense::from(value);
-        insert_into_grouped_senses(&mut groups, pos, sense);
+    let (groups, group_of_sense) = sense_groups(&values);
+    let mut groups: Vec<GroupedSense> = groups
+        .into_iter()
+        .map(|pos| GroupedSense {
+            pos,
+            senses: vec![],
+        })
+        .collect();
+    for (value, group) in values.into_iter().zip(group_of_sense) {
+        groups[group].senses.push(Sense::from(value));
     }
     groups
 }
 
-fn insert_into_grouped_senses(
-    groups: &mut Vec<GroupedSense>,
-    pos: Vec<PartOfSpeech>,
-    sense: Sense,
-) {
-    for group in groups.iter_mut() {
-        if group.pos == pos {
-            group.senses.push(sense);
-            return;
+/// Returns idx of each sense in `WordEntry` converted from `senses`.
+///
+/// Sense idx counts sense in each group, as senses are grouped by part-of-speech.
+pub(crate) fn grouped_sense_idxs(senses: &[JMSense]) -> Vec<u16> {
+    let (groups, group_of_sense) = sense_groups(senses);
+    let mut group_starts = vec![0_u16; groups.len()];
+    for &group in &group_of_sense {
+        for start in &mut group_starts[group + 1..] {
+            *start += 1;
         }
     }
-    let group = GroupedSense {
-        pos,
-        senses: vec![sense],
-    };
-    groups.push(group);
+    group_of_sense
+        .into_iter()
+        .map(|group| {
+            let idx = group_starts[group];
+            group_starts[group] += 1;
+            idx
+        })
+        .collect()
 }
@@ -1,69 +1,92 @@
-use std::collections::HashMap;
-
-use yomikiri_jmdict::jmdict::JMSenseMisc;
+use serde::{Deserialize, Serialize};
+use yomikiri_jmdict::jmdict::{JMPriority, JMReference, JMSenseMisc};
 use yomikiri_jmdict::jmnedict::{JMneEntry, JMneKanji, JMneNameType, JMneReading, JMneTranslation};
 
 use crate::entry::{
-    GroupedNameItem, GroupedSense, NameEntry, NameItem, NameType, Rarity, WordEntryInner,
+    Gloss, GroupedNameItem, GroupedSense, NameEntry, NameItem, NameType, Rarity, SenseReference,
+    WordEntryInner,
 };
-use crate::{Kanji, PartOfSpeech, Reading, Result, Sense, WordEntry};
-
-type NameEntryKanji = String;
+use crate::spill::{ItemSpill, SortedRuns, SpillLocation};
+use crate::{Kanji, PartOfSpeech, Reading, Result, Sense, WordEntry, DEFAULT_LANGUAGE};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct NameEntryFragmentValue {
     id: u32,
     reading: String,
     // TODO: Instead of having a vector everywhere, reuse combinations.
     name_type: Vec<NameType>,
+    rarity: Rarity,
     priority: u16,
 }
 
-#[derive(Debug, Clone)]
+/// Name entries are built in kanji order,
+/// so that the same JMnedict file always creates the same dictionary file.
+///
+/// Fragments are sorted by kanji in spilled runs, as there are too many to keep in memory.
 pub(crate) struct NameEntriesBuilder {
-    fragments: HashMap<NameEntryKanji, Vec<NameEntryFragmentValue>>,
+    fragments: SortedRuns<NameEntryFragmentValue>,
 }
 
 impl NameEntriesBuilder {
-    /// 234561 (jmnedict: 2024-08-29, calculated: 2024-09-22)
-    pub const ENTRIES_CAPACITY: usize = 250000;
-
-    pub fn new() -> Self {
+    pub fn new(location: &SpillLocation) -> Self {
         Self {
-            fragments: HashMap::with_capacity(Self::ENTRIES_CAPACITY),
+            fragments: SortedRuns::new(location),
         }
     }
 
-    fn add_fragment(&mut self, kanji: &String, fragment_value: NameEntryFragmentValue) {
-        if !self.fragments.contains_key(kanji) {
-            self.fragments.insert(kanji.clone(), vec![]);
-        }
-        self.fragments.get_mut(kanji).unwrap().push(fragment_value)
+    fn add_fragment(&mut self, kanji: &str, fragment_value: NameEntryFragmentValue) -> Result<()> {
+        self.fragments.push(kanji.to_string(), fragment_value)
     }
 
-    pub fn into_iter(self) -> impl Iterator<Item = NameEntry> {
-        // println!("name_entries.len() = {}", self.fragments.len());
-        self.fragments.into_iter().map(|(kanji, values)| {
-            let mut groups: Vec<GroupedNameItem> = vec![];
-            for val in values {
-                let name_item = NameItem {
-                    id: val.id,
-                    reading: val.reading,
-                };
-                if let Some(grp) = groups.iter_mut().find(|grp| grp.types == val.name_type) {
-                    grp.items.push(name_item);
-                } else {
-                    groups.push(GroupedNameItem {
-                        types: val.name_type,
-                        items: vec![name_item],
-                    })
+    /// Builds name entries into a spill
+    pub fn build(self, location: &SpillLocation) -> Result<ItemSpill<NameEntry>> {
+        let mut name_entries = ItemSpill::new(location)?;
+        let mut kanji = String::new();
+        let mut values = vec![];
+        for fragment in self.fragments.into_sorted()? {
+            let (next_kanji, value) = fragment?;
+            if next_kanji != kanji {
+                if !values.is_empty() {
+                    name_entries.push(&build_name_entry(kanji, std::mem::take(&mut values)))?;
                 }
+                kanji = next_kanji;
             }
-            NameEntry { kanji, groups }
-        })
+            values.push(value);
+        }
+        if !values.is_empty() {
+            name_entries.push(&build_name_entry(kanji, values))?;
+        }
+        Ok(name_entries)
     }
 }
 
+fn build_name_entry(kanji: String, values: Vec<NameEntryFragmentValue>) -> NameEntry {
+    let mut groups: Vec<GroupedNameItem> = vec![];
+    for val in values {
+        let name_item = NameItem {
+            id: val.id,
+            reading: val.reading,
+            rarity: val.rarity,
+            priority: val.priority,
+        };
+        if let Some(grp) = groups.iter_mut().find(|grp| grp.types == val.name_type) {
+            grp.items.push(name_item);
+        } else {
+            groups.push(GroupedNameItem {
+                types: val.name_type,
+                items: vec![name_item],
+            })
+        }
+    }
+    for group in &mut groups {
+        // stable sort keeps entry order for items with same priority
+        group
+            .items
+            .sort_by_key(|item| std::cmp::Reverse(item.priority));
+    }
+    NameEntry { kanji, groups }
+}
+
 /// Parses JMnedict entry and adds 0 or 1 new WordEntry and n NameEntryFragments
 ///
 /// A `JMneTranslation` entry either creates (multiple) `NameItem`, or one `Sense` in `Entry`, not both.
@@ -79,11 +102,16 @@ impl NameEntriesBuilder {
 ///     - If it is `Person` with `Female` or `Male`, only a WordItem is created, and `Female` / `Male` is ignored.
 ///       The gender tag seems to be mistagged here to only indicate the person's gender, instead of the forename + gender info.
 ///     - For other tag combinations, we only create `NameItem`, as it's a transliteration anyway.
+///
+/// Translations are only parsed in English, so no `WordEntry` is created
+/// if English is not in `languages` of dictionary, as for JMdict entries without any gloss left.
 pub(crate) fn parse_jmnedict_entry(
-    word_entries: &mut Vec<WordEntry>,
+    word_entries: &mut ItemSpill<WordEntry>,
     name_builder: &mut NameEntriesBuilder,
     entry: JMneEntry,
+    languages: &[String],
 ) -> Result<()> {
+    let entry_priority = entry.priority();
     let mut for_names: Vec<JMneTranslation> = vec![];
     let mut for_words: Vec<JMneTranslation> = vec![];
     if entry.kanjis.is_empty() {
@@ -104,24 +132,38 @@ pub(crate) fn parse_jmnedict_entry(
                 if !reading.is_for_kanji(&kanji.kanji) {
                     continue;
                 }
+                let rarity = Rarity::of_kanji(&kanji.info).max(Rarity::of_reading(&reading.info));
+                let priority =
+                    JMPriority::score(&kanji.priority).max(JMPriority::score(&reading.priority));
                 for trans_obj in &for_names {
                     let fragment_value = NameEntryFragmentValue {
                         id: entry.id,
                         reading: reading.reading.clone(),
-                        name_type: trans_obj.name_type.clone(),
-                        priority: 0, // TODO: Set priority info
+                        name_type: trans_obj
+                            .name_type
+                            .iter()
+                            .filter(|t| !matches!(t, JMneNameType::Other(_)))
+                            .cloned()
+                            .collect(),
+                        rarity,
+                        priority,
                     };
-                    name_builder.add_fragment(&kanji.kanji, fragment_value);
+                    name_builder.add_fragment(&kanji.kanji, fragment_value)?;
                 }
             }
         }
     }
 
-    if !for_words.is_empty() {
-        let inner =
-            WordEntryInner::from_jmnedict(entry.id, &entry.kanjis, &entry.readings, &for_words);
+    if !for_words.is_empty() && languages.iter().any(|lang| lang == DEFAULT_LANGUAGE) {
+        let inner = WordEntryInner::from_jmnedict(
+            entry.id,
+            &entry.kanjis,
+            &entry.readings,
+            &for_words,
+            entry_priority,
+        );
         let entry = WordEntry::new(inner)?;
-        word_entries.push(entry);
+        word_entries.push(&entry)?;
     }
     Ok(())
 }
@@ -159,12 +201,14 @@ impl WordEntryInner {
         kanjis: &[JMneKanji],
         readings: &[JMneReading],
         translations: &[JMneTranslation],
+        priority: u16,
     ) -> WordEntryInner {
         let kanjis = kanjis
             .iter()
             .map(|k| Kanji {
                 kanji: k.kanji.clone(),
-                rarity: Rarity::Normal,
+                rarity: Rarity::of_kanji(&k.info),
+                priority: k.priority.clone(),
             })
             .collect();
         let readings = readings
@@ -173,7 +217,8 @@ impl WordEntryInner {
                 reading: r.reading.clone(),
                 to_kanji: r.to_kanji.clone(),
                 nokanji: false,
-                rarity: Rarity::Normal,
+                rarity: Rarity::of_reading(&r.info),
+                priority: r.priority.clone(),
             })
             .collect();
         let grouped_senses = vec![GroupedSense {
@@ -181,11 +226,25 @@ impl WordEntryInner {
             senses: translations
                 .iter()
                 .map(|t| Sense {
-                    meanings: t.translations.clone(),
+                    meanings: t.translations.iter().cloned().map(Gloss::from).collect(),
                     to_kanji: vec![],
                     to_reading: vec![],
-                    misc: t.name_type.iter().map(|t| JMSenseMisc::from(*t)).collect(),
+                    references: t
+                        .xref
+                        .iter()
+                        .map(|x| SenseReference::from(JMReference::parse(x)))
+                        .collect(),
+                    antonyms: vec![],
+                    fields: vec![],
+                    // unknown entities are reported as parser warnings, and cannot be displayed
+                    misc: t
+                        .name_type
+                        .iter()
+                        .filter(|t| !matches!(t, JMneNameType::Other(_)))
+                        .map(|t| JMSenseMisc::from(t.clone()))
+                        .collect(),
                     info: vec![],
+                    language_sources: vec![],
                     dialects: vec![],
                 })
                 .collect(),
@@ -196,7 +255,7 @@ impl WordEntryInner {
             kanjis,
             readings,
             grouped_senses,
-            priority: 0, // TODO: Calculate priority from entry
+            priority,
         }
     }
 }
@@ -1,17 +1,63 @@
+mod automaton;
 pub mod dictionary;
+pub mod diff;
 pub mod entry;
 pub mod error;
+pub mod example;
+pub mod fuzzy;
+pub mod header;
 pub mod index;
 pub mod jagged_array;
 pub mod jmdict;
 pub mod jmnedict;
 pub mod meaning;
+mod parallel;
+pub mod prefix;
+pub mod radical;
+pub mod spill;
+pub mod term_rank;
 mod utils;
+pub mod verify;
+pub mod wildcard;
 
-pub use entry::{Kanji, PartOfSpeech, Reading, Sense, WordEntry};
+pub use entry::{Gloss, Kanji, PartOfSpeech, Reading, Sense, WordEntry};
 pub use error::{Error, Result};
 
 /** This value should be increased each time dictionary file schema is modified */
-pub const SCHEMA_VER: u16 = 4;
+pub const SCHEMA_VER: u16 = 16;
 
 pub const DICT_FILENAME: &str = "english.yomikiridict";
+
+/// ISO 639-2 code of gloss language used when none is specified
+pub const DEFAULT_LANGUAGE: &str = "eng";
+
+/// Returns dictionary filename for dictionary built with gloss `languages`.
+///
+/// e.g. `["ger", "eng"]` -> "german-english.yomikiridict"
+pub fn dict_filename<S: AsRef<str>>(languages: &[S]) -> String {
+    if languages.is_empty() {
+        return DICT_FILENAME.to_string();
+    }
+    let names: Vec<&str> = languages
+        .iter()
+        .map(|lang| language_name(lang.as_ref()))
+        .collect();
+    format!("{}.yomikiridict", names.join("-"))
+}
+
+/// Returns english name of JMdict gloss language,
+/// or `code` itself if language is unknown.
+fn language_name(code: &str) -> &str {
+    match code {
+        "eng" => "english",
+        "dut" => "dutch",
+        "fre" => "french",
+        "ger" => "german",
+        "hun" => "hungarian",
+        "rus" => "russian",
+        "slv" => "slovenian",
+        "spa" => "spanish",
+        "swe" => "swedish",
+        _ => code,
+    }
+}
@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::hash::Hash;
 
 use itertools::Itertools;
@@ -8,9 +8,9 @@ use serde::{Deserialize, Serialize};
 use unicode_normalization::UnicodeNormalization;
 
 use crate::dictionary::DictionaryView;
-use crate::entry::{Entry, WordEntry};
+use crate::entry::{Entry, Gloss, WordEntry};
 use crate::error::Result;
-use crate::index::{DictIndexItem, EncodableIdx, EntryIdx, WordEntryIdx};
+use crate::index::{DictIndexMap, EncodableIdx, EntryIdx, WordEntryIdx};
 use crate::Error;
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
@@ -33,50 +33,73 @@ impl MeaningIdx {
     pub fn entry_idx(&self) -> EntryIdx {
         self.entry_idx.entry_idx()
     }
+
+    pub(crate) fn word_entry_idx(&self) -> &WordEntryIdx {
+        &self.entry_idx
+    }
+
+    /// Returns meaning of `entry` at this index.
+    ///
+    /// Fails if sense or meaning position is out of bounds.
+    pub(crate) fn meaning_in<'e>(&self, entry: &'e WordEntry) -> Result<&'e Gloss> {
+        MeaningSearchOrderCalculator::word_meaning(entry, &self.inner_idx)
+    }
 }
 
-pub fn create_meaning_indexes(entries: &[WordEntry]) -> Result<Vec<DictIndexItem<MeaningIdx>>> {
-    let mut map: HashMap<String, Vec<MeaningIdx>> = HashMap::new();
-
-    for (word_idx, entry) in entries.iter().enumerate() {
-        let mut sense_idx = 0;
-
-        for grp in &entry.grouped_senses {
-            for sense in &grp.senses {
-                for (meaning_idx, meaning) in sense.meanings.iter().enumerate() {
-                    let idx = MeaningIdx {
-                        entry_idx: WordEntryIdx(word_idx as u32),
-                        inner_idx: InnerWordMeaningIdx {
-                            sense_idx,
-                            meaning_idx,
-                        },
-                    };
-                    let normalized = normalize_meaning(meaning);
-                    let meaning_keys = split_meaning_index_words(&normalized);
-                    for key in meaning_keys {
-                        map.entry(key)
-                            .and_modify(|v| v.push(idx.clone()))
-                            .or_insert_with(|| vec![idx.clone()]);
-                    }
+/// Returns meaning index keys of glosses in `lang` of entry at `entry_idx`
+pub(crate) fn meaning_index_keys(
+    entry_idx: WordEntryIdx,
+    entry: &WordEntry,
+    lang: &str,
+) -> Vec<(String, MeaningIdx)> {
+    let mut keys = vec![];
+    let mut sense_idx = 0;
+    for grp in &entry.grouped_senses {
+        for sense in &grp.senses {
+            for (meaning_idx, meaning) in sense.meanings.iter().enumerate() {
+                if meaning.lang() != lang {
+                    continue;
+                }
+                let idx = MeaningIdx {
+                    entry_idx,
+                    inner_idx: InnerWordMeaningIdx {
+                        sense_idx,
+                        meaning_idx,
+                    },
+                };
+                let normalized = normalize_meaning(&meaning.text);
+                for key in split_meaning_index_words(&normalized) {
+                    keys.push((key, idx.clone()));
                 }
-                sense_idx += 1;
             }
+            sense_idx += 1;
         }
     }
-
-    let items = map
-        .into_iter()
-        .map(|(key, idxs)| DictIndexItem {
-            key,
-            entry_indexes: idxs,
-        })
-        .sorted_by(|a, b| a.key.cmp(&b.key))
-        .collect::<Vec<DictIndexItem<MeaningIdx>>>();
-    Ok(items)
+    keys
 }
 
-impl DictionaryView<'_> {
+impl<'a> DictionaryView<'a> {
+    /// Searches meaning in the primary (first) gloss language of dictionary
     pub fn search_meaning(&self, query: &str) -> Result<Vec<Entry>> {
+        match self.meaning_indexes.first() {
+            Some(index) => self.search_meaning_in_index(index, query),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns empty result if dictionary is not built with `lang`
+    pub fn search_meaning_in_language(&self, query: &str, lang: &str) -> Result<Vec<Entry>> {
+        match self.meaning_index(lang) {
+            Some(index) => self.search_meaning_in_index(index, query),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn search_meaning_in_index(
+        &self,
+        meaning_index: &DictIndexMap<'a, MeaningIdx>,
+        query: &str,
+    ) -> Result<Vec<Entry>> {
         let normalized = normalize_meaning(query);
         let words = split_meaning_index_words(&normalized);
 
@@ -86,7 +109,7 @@ impl DictionaryView<'_> {
 
         let mut idxs_arr: Vec<Vec<MeaningIdx>> = vec![];
         for word in &words {
-            let idxs = self.meaning_index.get(word)?;
+            let idxs = meaning_index.get(word)?;
             idxs_arr.push(idxs);
         }
 
@@ -153,6 +176,8 @@ struct MeaningSearchOrder {
     identical_parenthesis: bool,
     /// Unparenthesized search query is identical to unparenthesized meaning
     identical_unparenthesized: bool,
+    /// Meaning is a translation, not an explanation of the term
+    not_explanation: bool,
     /// Number of Words in query and meaning / Total words in unparenthesized meaning
     words_in_query_and_meaning_ratio: f32,
     /// Is first (main) meaning in first sense of entry
@@ -185,7 +210,8 @@ impl<'a> MeaningSearchOrderCalculator<'a> {
         inner_idx: &InnerWordMeaningIdx,
     ) -> Result<MeaningSearchOrder> {
         let meaning = Self::word_meaning(entry, inner_idx)?;
-        let normalized = normalize_meaning(meaning);
+        let not_explanation = !meaning.is_explanation();
+        let normalized = normalize_meaning(&meaning.text);
         let unparenthesized = remove_parenthesis(&normalized);
 
         let query_contains_parenthesis = self.normalized != self.unparenthesized;
@@ -199,6 +225,7 @@ impl<'a> MeaningSearchOrderCalculator<'a> {
         Ok(MeaningSearchOrder {
             identical_parenthesis,
             identical_unparenthesized,
+            not_explanation,
             words_in_query_and_meaning_ratio,
             first_sense,
             first_meaning,
@@ -213,7 +240,10 @@ impl<'a> MeaningSearchOrderCalculator<'a> {
         intersection_cnt as f32 / meaning_words.len() as f32
     }
 
-    fn word_meaning<'e>(entry: &'e WordEntry, inner_idx: &InnerWordMeaningIdx) -> Result<&'e str> {
+    fn word_meaning<'e>(
+        entry: &'e WordEntry,
+        inner_idx: &InnerWordMeaningIdx,
+    ) -> Result<&'e Gloss> {
         let sense = entry
             .grouped_senses
             .iter()
@@ -229,7 +259,7 @@ impl<'a> MeaningSearchOrderCalculator<'a> {
 }
 
 /// Split text into words and generate list of unique meaning index keys
-fn split_meaning_index_words(normalized: &str) -> Vec<String> {
+pub(crate) fn split_meaning_index_words(normalized: &str) -> Vec<String> {
     split_alphanumeric_words(normalized)
         .into_iter()
         .unique()
@@ -257,7 +287,7 @@ fn split_alphanumeric_words(text: &str) -> Vec<&str> {
 /// 1. Lowercases text
 /// 2. Removes diacritic marks
 /// 3. Returns NFKC normalized string
-fn normalize_meaning(meaning: &str) -> String {
+pub(crate) fn normalize_meaning(meaning: &str) -> String {
     let text = meaning.to_lowercase();
     text.nfkd()
         .filter(is_not_diacritical_marks)
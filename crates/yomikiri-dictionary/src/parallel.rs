@@ -0,0 +1,70 @@
+//! Runs work of dictionary build on multiple threads when `parallel` feature is enabled.
+//!
+//! Results are always returned in input order,
+//! so that dictionary file is identical to the one built on a single thread.
+
+/// Number of items read from spill and processed at once
+pub(crate) const BATCH_LEN: usize = 4096;
+
+/// Maps `items` with `f`, returning results in the same order as `items`
+#[cfg(feature = "parallel")]
+pub(crate) fn map_in_order<T, U, F>(items: Vec<T>, f: F) -> Vec<U>
+where
+    T: Send,
+    U: Send,
+    F: Fn(T) -> U + Sync,
+{
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if threads == 1 || items.len() < 2 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let chunk_len = items.len().div_ceil(threads);
+    let mut items = items.into_iter();
+    let mut chunks = vec![];
+    loop {
+        let chunk: Vec<T> = items.by_ref().take(chunk_len).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunks.push(chunk);
+    }
+
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || chunk.into_iter().map(f).collect::<Vec<U>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            })
+            .collect()
+    })
+}
+
+/// Maps `items` with `f`, returning results in the same order as `items`
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn map_in_order<T, U, F>(items: Vec<T>, f: F) -> Vec<U>
+where
+    F: Fn(T) -> U,
+{
+    items.into_iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::map_in_order;
+
+    #[test]
+    fn results_are_in_input_order() {
+        let items: Vec<u32> = (0..10000).collect();
+        let mapped = map_in_order(items.clone(), |i| i * 2);
+        let expected: Vec<u32> = items.into_iter().map(|i| i * 2).collect();
+        assert_eq!(mapped, expected);
+    }
+}
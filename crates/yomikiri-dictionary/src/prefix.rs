@@ -0,0 +1,19 @@
+use fst::automaton::Str;
+use fst::Automaton;
+
+use crate::dictionary::DictionaryView;
+use crate::term_rank::TermSearchItem;
+use crate::Result;
+
+impl DictionaryView<'_> {
+    /// Returns up to `limit` terms that start with `prefix` with their entries,
+    /// ranked by priority of term.
+    ///
+    /// `prefix` itself is included if it is a term. Search-only forms are ranked last.
+    pub fn search_prefix(&self, prefix: &str, limit: usize) -> Result<Vec<TermSearchItem>> {
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+        self.search_terms(Str::new(prefix).starts_with(), limit)
+    }
+}
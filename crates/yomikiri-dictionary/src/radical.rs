@@ -0,0 +1,223 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use yomikiri_jmdict::radical::RadkRadical;
+
+use crate::dictionary::DictionaryView;
+use crate::index::{DictIndexItem, DictIndexMap, WordEntryIdx};
+use crate::verify::VerifyReport;
+use crate::{Result, WordEntry};
+
+/// Maximum number of word entries returned from radical search
+pub const MAX_RADICAL_SEARCH_ENTRIES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Radical {
+    pub radical: String,
+    pub stroke_count: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RadicalKanjis {
+    radical: Radical,
+    /// Concatenated kanji characters that contain the radical
+    kanjis: String,
+}
+
+/// Radical to kanji index built from RADKFILE,
+/// and kanji to word index for kanji that contain a radical.
+#[derive(Serialize, Deserialize)]
+pub struct RadicalIndex<'a> {
+    radicals: Vec<RadicalKanjis>,
+    /// Word entries with kanji form that contains the kanji.
+    /// Sorted by entry priority, from high to low.
+    #[serde(borrow)]
+    kanji_index: DictIndexMap<'a, WordEntryIdx>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RadicalSearchResult {
+    /// Kanji that contain all radicals, in RADKFILE order
+    pub kanjis: Vec<String>,
+    /// Words that contain any of `kanjis`, sorted by priority
+    pub entries: Vec<WordEntry>,
+}
+
+impl<'a> RadicalIndex<'a> {
+    pub fn radicals(&self) -> impl Iterator<Item = &Radical> {
+        self.radicals.iter().map(|r| &r.radical)
+    }
+
+    fn kanjis_of(&self, radical: &str) -> Option<&str> {
+        self.radicals
+            .iter()
+            .find(|r| r.radical.radical == radical)
+            .map(|r| r.kanjis.as_str())
+    }
+
+    pub(crate) fn build_and_encode_to<W: Write>(
+        radicals: &[RadkRadical],
+        kanji_index: KanjiIndexBuilder,
+        writer: &mut W,
+    ) -> Result<()> {
+        let radicals: Vec<RadicalKanjis> = radicals
+            .iter()
+            .map(|r| RadicalKanjis {
+                radical: Radical {
+                    radical: r.radical.clone(),
+                    stroke_count: r.stroke_count,
+                },
+                kanjis: r.kanjis.concat(),
+            })
+            .collect();
+        postcard::to_io(&radicals, &mut *writer)?;
+
+        let kanji_index = kanji_index.build();
+        DictIndexMap::build_and_encode_to(&kanji_index, writer)?;
+        Ok(())
+    }
+
+    pub fn try_decode(source: &'a [u8]) -> Result<(Self, usize)> {
+        let start = source.len();
+        let (index, rest) = postcard::take_from_bytes(source)?;
+        Ok((index, start - rest.len()))
+    }
+}
+
+impl RadicalIndex<'_> {
+    /// Every kanji must point to word entries that contain the kanji
+    pub(crate) fn verify(&self, dict: &DictionaryView, report: &mut VerifyReport) {
+        self.kanji_index.for_each_item(|key, idxs| {
+            let kanji = String::from_utf8_lossy(key);
+            let idxs = match idxs {
+                Ok(idxs) => idxs,
+                Err(err) => {
+                    report.problem(format!("Kanji '{}' could not be decoded: {}", kanji, err));
+                    return;
+                }
+            };
+            for idx in &idxs {
+                match dict.get_word_entry(idx) {
+                    Ok(entry) if entry.kanjis.iter().any(|k| k.kanji.contains(&*kanji)) => {}
+                    Ok(_) => report.problem(format!(
+                        "Kanji '{}' points to {:?} which does not contain the kanji",
+                        kanji, idx
+                    )),
+                    Err(err) => report.problem(format!(
+                        "Kanji '{}' points to {:?} which could not be read: {}",
+                        kanji, idx, err
+                    )),
+                }
+            }
+        });
+    }
+}
+
+/// Collects word entries of kanji in RADKFILE, as entries are added in order
+pub(crate) struct KanjiIndexBuilder {
+    kanjis: HashSet<char>,
+    /// kanji -> (priority, idx) of entries
+    map: HashMap<char, Vec<(u16, WordEntryIdx)>>,
+}
+
+impl KanjiIndexBuilder {
+    pub fn new(radicals: &[RadkRadical]) -> Self {
+        let kanjis: HashSet<char> = radicals
+            .iter()
+            .flat_map(|r| &r.kanjis)
+            .flat_map(|k| k.chars())
+            .collect();
+        let map = HashMap::with_capacity(kanjis.len());
+        Self { kanjis, map }
+    }
+
+    pub fn add(&mut self, idx: WordEntryIdx, entry: &WordEntry) {
+        for ch in entry.kanjis.iter().flat_map(|k| k.kanji.chars()) {
+            if !self.kanjis.contains(&ch) {
+                continue;
+            }
+            let idxs = self.map.entry(ch).or_default();
+            if idxs.last().map(|(_, last)| last) != Some(&idx) {
+                idxs.push((entry.priority, idx));
+            }
+        }
+    }
+
+    fn build(self) -> Vec<DictIndexItem<WordEntryIdx>> {
+        self.map
+            .into_iter()
+            .map(|(kanji, mut idxs)| {
+                // stable sort keeps entry order for entries with same priority
+                idxs.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+                DictIndexItem {
+                    key: kanji.to_string(),
+                    entry_indexes: idxs.into_iter().map(|(_, idx)| idx).collect(),
+                }
+            })
+            .sorted_by(|a, b| a.key.cmp(&b.key))
+            .collect()
+    }
+}
+
+impl DictionaryView<'_> {
+    /// Finds kanji that contain all of `radicals`, and words that contain those kanji.
+    ///
+    /// Returns empty result if `radicals` is empty or contains an unknown radical.
+    pub fn search_radicals<S: AsRef<str>>(&self, radicals: &[S]) -> Result<RadicalSearchResult> {
+        let mut radical_kanjis = vec![];
+        for radical in radicals {
+            match self.radical_index.kanjis_of(radical.as_ref()) {
+                Some(kanjis) => radical_kanjis.push(kanjis),
+                None => {
+                    return Ok(RadicalSearchResult {
+                        kanjis: vec![],
+                        entries: vec![],
+                    })
+                }
+            }
+        }
+
+        let kanjis: Vec<char> = match radical_kanjis.split_first() {
+            Some((first, rest)) => first
+                .chars()
+                .filter(|ch| rest.iter().all(|kanjis| kanjis.contains(*ch)))
+                .collect(),
+            None => vec![],
+        };
+
+        let mut idxs = vec![];
+        let mut seen = HashSet::new();
+        for kanji in &kanjis {
+            let kanji_idxs = self
+                .radical_index
+                .kanji_index
+                .get(kanji.encode_utf8(&mut [0; 4]))?;
+            for idx in kanji_idxs.into_iter().take(MAX_RADICAL_SEARCH_ENTRIES) {
+                if seen.insert(idx) {
+                    idxs.push(idx);
+                }
+            }
+            // enough candidates to pick highest priority entries from
+            if idxs.len() >= MAX_RADICAL_SEARCH_ENTRIES * 4 {
+                break;
+            }
+        }
+
+        let mut entries = idxs
+            .iter()
+            .map(|idx| self.get_word_entry(idx))
+            .collect::<Result<Vec<WordEntry>>>()?;
+        entries.sort_by_key(|e| std::cmp::Reverse(e.priority));
+        entries.truncate(MAX_RADICAL_SEARCH_ENTRIES);
+
+        Ok(RadicalSearchResult {
+            kanjis: kanjis.into_iter().map(String::from).collect(),
+            entries,
+        })
+    }
+}
@@ -0,0 +1,324 @@
+//! Temporary storage of items while dictionary is built.
+//!
+//! Entries and index keys are kept encoded, in memory or in temporary files,
+//! instead of as a `Vec` of decoded items, so that memory used to build a dictionary stays bounded.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::Result;
+
+/// Number of items sorted in memory before they are spilled as a sorted run
+const RUN_LEN: usize = 1 << 16;
+
+/// Where items are spilled while dictionary is built
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SpillLocation {
+    /// Items are kept encoded in memory.
+    ///
+    /// Memory is not bounded, as all entries and index keys are kept until dictionary is written.
+    #[default]
+    Memory,
+    /// Items are written to temporary files in directory.
+    ///
+    /// Files are unlinked as soon as they are created where the OS allows it,
+    /// so they are not left behind even if the process is killed.
+    Directory(PathBuf),
+}
+
+pub(crate) enum Storage {
+    Memory(Cursor<Vec<u8>>),
+    File(File),
+}
+
+impl Storage {
+    fn new(location: &SpillLocation) -> Result<Self> {
+        match location {
+            SpillLocation::Memory => Ok(Storage::Memory(Cursor::new(Vec::new()))),
+            SpillLocation::Directory(dir) => Ok(Storage::File(tempfile::tempfile_in(dir)?)),
+        }
+    }
+}
+
+impl Read for Storage {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Storage::Memory(cursor) => cursor.read(buf),
+            Storage::File(file) => file.read(buf),
+        }
+    }
+}
+
+impl Write for Storage {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Storage::Memory(cursor) => cursor.write(buf),
+            Storage::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Storage::Memory(cursor) => cursor.flush(),
+            Storage::File(file) => file.flush(),
+        }
+    }
+}
+
+impl Seek for Storage {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Storage::Memory(cursor) => cursor.seek(pos),
+            Storage::File(file) => file.seek(pos),
+        }
+    }
+}
+
+/// Append-only list of postcard encoded items.
+///
+/// Items are read back in the order they are pushed.
+/// An item can be replaced or removed after it is pushed,
+/// in which case the replacement is kept in memory.
+pub(crate) struct ItemSpill<T> {
+    writer: BufWriter<Storage>,
+    /// Byte length of each encoded item
+    lengths: Vec<u32>,
+    /// Position of item -> replacement, `None` if removed
+    replacements: HashMap<usize, Option<T>>,
+    removed: usize,
+    /// Whether storage is positioned at its end, so that items can be appended
+    at_end: bool,
+    buffer: Vec<u8>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> ItemSpill<T> {
+    pub fn new(location: &SpillLocation) -> Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(Storage::new(location)?),
+            lengths: vec![],
+            replacements: HashMap::new(),
+            removed: 0,
+            at_end: true,
+            buffer: vec![],
+        })
+    }
+
+    /// Number of items, excluding removed items
+    pub fn len(&self) -> usize {
+        self.lengths.len() - self.removed
+    }
+
+    /// Returns position of pushed item
+    pub fn push(&mut self, item: &T) -> Result<usize> {
+        if !self.at_end {
+            self.writer.seek(SeekFrom::End(0))?;
+            self.at_end = true;
+        }
+        self.buffer.clear();
+        postcard::to_io(item, &mut self.buffer)?;
+        self.writer.write_all(&self.buffer)?;
+        self.lengths.push(self.buffer.len().try_into()?);
+        Ok(self.lengths.len() - 1)
+    }
+
+    /// Replaces item at `position` that was returned from `push`, or removes it if `item` is `None`
+    pub fn replace(&mut self, position: usize, item: Option<T>) {
+        if item.is_none() {
+            self.removed += 1;
+        }
+        if let Some(None) = self.replacements.insert(position, item) {
+            self.removed -= 1;
+        }
+    }
+
+    /// Iterates items in order, skipping removed items
+    pub fn iter(&mut self) -> Result<Iter<'_, T, &mut Storage>> {
+        self.writer.flush()?;
+        let storage = self.writer.get_mut();
+        storage.seek(SeekFrom::Start(0))?;
+        self.at_end = false;
+        Ok(Iter {
+            reader: BufReader::new(storage),
+            lengths: Cow::Borrowed(&self.lengths),
+            replacements: Cow::Borrowed(&self.replacements),
+            position: 0,
+            buffer: vec![],
+        })
+    }
+
+    pub fn into_items(self) -> Result<Iter<'static, T, Storage>>
+    where
+        T: 'static,
+    {
+        let mut storage = self.writer.into_inner().map_err(|e| e.into_error())?;
+        storage.seek(SeekFrom::Start(0))?;
+        Ok(Iter {
+            reader: BufReader::new(storage),
+            lengths: Cow::Owned(self.lengths),
+            replacements: Cow::Owned(self.replacements),
+            position: 0,
+            buffer: vec![],
+        })
+    }
+}
+
+pub(crate) struct Iter<'a, T: Clone, R> {
+    reader: BufReader<R>,
+    lengths: Cow<'a, [u32]>,
+    replacements: Cow<'a, HashMap<usize, Option<T>>>,
+    position: usize,
+    buffer: Vec<u8>,
+}
+
+impl<T: DeserializeOwned + Clone, R: Read> Iter<'_, T, R> {
+    /// Returns up to `len` next items, or an empty `Vec` if there are no more items
+    pub fn next_batch(&mut self, len: usize) -> Result<Vec<T>> {
+        self.by_ref().take(len).collect()
+    }
+
+    /// Returns next item with its position in spill
+    pub fn next_with_position(&mut self) -> Option<Result<(usize, T)>> {
+        loop {
+            let position = self.position;
+            let len = *self.lengths.get(position)? as usize;
+            self.position += 1;
+
+            self.buffer.resize(len, 0);
+            if let Err(err) = self.reader.read_exact(&mut self.buffer) {
+                return Some(Err(err.into()));
+            }
+            match self.replacements.get(&position) {
+                Some(Some(replacement)) => return Some(Ok((position, replacement.clone()))),
+                Some(None) => continue,
+                None => {}
+            }
+            let item = postcard::from_bytes(&self.buffer)
+                .map(|item| (position, item))
+                .map_err(Into::into);
+            return Some(item);
+        }
+    }
+}
+
+impl<T: DeserializeOwned + Clone, R: Read> Iterator for Iter<'_, T, R> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_position()
+            .map(|item| item.map(|(_, item)| item))
+    }
+}
+
+type RunItem<T> = (String, u64, T);
+
+/// Key-value pairs sorted by key, with items of the same key kept in the order they are pushed.
+///
+/// Pairs are sorted in runs of `RUN_LEN` items which are spilled,
+/// and runs are merged when the sorted pairs are read.
+pub(crate) struct SortedRuns<T> {
+    location: SpillLocation,
+    buffer: Vec<RunItem<T>>,
+    runs: Vec<ItemSpill<RunItem<T>>>,
+    next_seq: u64,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone + 'static> SortedRuns<T> {
+    pub fn new(location: &SpillLocation) -> Self {
+        Self {
+            location: location.clone(),
+            buffer: vec![],
+            runs: vec![],
+            next_seq: 0,
+        }
+    }
+
+    pub fn push(&mut self, key: String, value: T) -> Result<()> {
+        self.buffer.push((key, self.next_seq, value));
+        self.next_seq += 1;
+        if self.buffer.len() >= RUN_LEN {
+            self.spill_run()?;
+        }
+        Ok(())
+    }
+
+    fn spill_run(&mut self) -> Result<()> {
+        self.buffer
+            .sort_unstable_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+        let mut run = ItemSpill::new(&self.location)?;
+        for item in self.buffer.drain(..) {
+            run.push(&item)?;
+        }
+        self.runs.push(run);
+        Ok(())
+    }
+
+    /// Returns all pairs sorted by key
+    pub fn into_sorted(mut self) -> Result<impl Iterator<Item = Result<(String, T)>>> {
+        if !self.buffer.is_empty() {
+            self.spill_run()?;
+        }
+        let runs = self
+            .runs
+            .into_iter()
+            .map(|run| run.into_items())
+            .collect::<Result<Vec<_>>>()?;
+        let merged = runs
+            .into_iter()
+            .kmerge_by(|a, b| match (a, b) {
+                (Ok(a), Ok(b)) => (&a.0, a.1) < (&b.0, b.1),
+                // errors are returned as early as possible
+                (Err(_), _) => true,
+                (Ok(_), Err(_)) => false,
+            })
+            .map(|item| item.map(|(key, _, value)| (key, value)));
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ItemSpill, SortedRuns, SpillLocation, RUN_LEN};
+    use crate::Result;
+
+    #[test]
+    fn sorted_runs_are_merged_in_key_and_insertion_order() -> Result<()> {
+        let mut runs = SortedRuns::new(&SpillLocation::Memory);
+        let count = RUN_LEN * 2 + 10;
+        for i in 0..count {
+            runs.push(format!("{:02}", i % 50), i as u32)?;
+        }
+        let sorted = runs.into_sorted()?.collect::<Result<Vec<_>>>()?;
+        let mut expected: Vec<(String, u32)> = (0..count)
+            .map(|i| (format!("{:02}", i % 50), i as u32))
+            .collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(sorted, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn replaced_items_are_read_in_place() -> Result<()> {
+        let mut spill = ItemSpill::new(&SpillLocation::Memory)?;
+        for i in 0..5_u32 {
+            spill.push(&i)?;
+        }
+        spill.replace(1, Some(10));
+        spill.replace(3, None);
+        assert_eq!(spill.len(), 4);
+        let items = spill.iter()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(items, vec![0, 10, 2, 4]);
+
+        spill.push(&5)?;
+        spill.replace(3, Some(3));
+        let items = spill.iter()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(items, vec![0, 10, 2, 3, 4, 5]);
+        Ok(())
+    }
+}
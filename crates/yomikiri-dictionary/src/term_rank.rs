@@ -0,0 +1,160 @@
+//! Rank of each term in term index,
+//! so that all terms matching a prefix or pattern are ranked without decoding their entries.
+
+use std::cmp::Reverse;
+use std::io::Write;
+
+use fst::{Automaton, IntoStreamer, MapBuilder, Streamer};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::dictionary::DictionaryView;
+use crate::entry::{Entry, Rarity};
+use crate::error::Result;
+use crate::index::{BestKeys, Map};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TermSearchItem {
+    pub term: String,
+    /// Sorted by priority of term in entry, from high to low
+    pub entries: Vec<Entry>,
+}
+
+impl DictionaryView<'_> {
+    /// Returns up to `limit` terms that match automaton `aut` with their entries,
+    /// ranked by `term_rank`.
+    pub(crate) fn search_terms<A: Automaton>(
+        &self,
+        aut: A,
+        limit: usize,
+    ) -> Result<Vec<TermSearchItem>> {
+        self.term_ranks
+            .best_matching(aut, limit)?
+            .into_iter()
+            .map(|term| {
+                let entries = self.term_entries(&term)?;
+                Ok(TermSearchItem { term, entries })
+            })
+            .collect()
+    }
+
+    /// Returns entries of `term`, sorted by priority of term in entry
+    fn term_entries(&self, term: &str) -> Result<Vec<Entry>> {
+        let mut entries = vec![];
+        for idx in self.term_index.get(term)? {
+            let entry = self.get_entry(&idx)?;
+            let (priority, search_only) = term_priority(&entry, term)?;
+            entries.push((entry, priority, search_only));
+        }
+        // stable sort keeps index order for entries with same priority
+        entries.sort_by_key(|(_, priority, search_only)| (*search_only, Reverse(*priority)));
+        Ok(entries.into_iter().map(|(entry, _, _)| entry).collect())
+    }
+}
+
+/// Returns priority of `term` in `entry`, and whether it is a search-only form
+pub(crate) fn term_priority(entry: &Entry, term: &str) -> Result<(u16, bool)> {
+    match entry {
+        Entry::Word(word) => {
+            let search_only = word.term_rarity(term)? == Rarity::Search;
+            Ok((word.term_priority(term), search_only))
+        }
+        Entry::Name(name) => Ok((name.priority(), false)),
+    }
+}
+
+/// Returns rank of term, where lower rank is ranked first.
+///
+/// Search-only forms are ranked last, then terms of higher priority first,
+/// and shorter terms first among terms of same priority.
+pub(crate) fn term_rank(term: &str, priority: u16, search_only: bool) -> u64 {
+    let len = term.chars().count().min(u16::MAX as usize) as u64;
+    (u64::from(search_only) << 32) | (u64::from(u16::MAX - priority) << 16) | len
+}
+
+/// Map of term to its best rank among its entries
+#[derive(Serialize, Deserialize)]
+pub struct TermRankIndex<'a> {
+    #[serde(borrow)]
+    map: Map<'a>,
+}
+
+impl<'a> TermRankIndex<'a> {
+    pub fn try_decode(source: &'a [u8]) -> Result<(Self, usize)> {
+        let start = source.len();
+        let (index, rest) = postcard::take_from_bytes(source)?;
+        Ok((index, start - rest.len()))
+    }
+
+    /// Number of terms
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns rank of `term`, or `None` if it is not in dictionary
+    pub fn get<K: AsRef<[u8]>>(&self, term: K) -> Option<u64> {
+        self.map.get(term)
+    }
+
+    /// Returns up to `limit` terms that match automaton `aut`, ranked first first.
+    ///
+    /// All matching terms are ranked, keeping only `limit` best terms at a time.
+    /// Terms of the same rank are in key order.
+    pub fn best_matching<A: Automaton>(&self, aut: A, limit: usize) -> Result<Vec<String>> {
+        let mut best = BestKeys::new(limit);
+        let mut stream = self.map.search(aut).into_stream();
+        while let Some((term, rank)) = stream.next() {
+            best.push(rank, term, ());
+        }
+        let terms = best.into_sorted_vec()?;
+        Ok(terms.into_iter().map(|(_, term, _)| term).collect())
+    }
+}
+
+pub(crate) struct TermRankIndexBuilder {
+    builder: MapBuilder<Vec<u8>>,
+    /// Term that is being added, and its best rank so far
+    current: Option<(String, u64)>,
+}
+
+impl TermRankIndexBuilder {
+    pub fn new() -> Self {
+        Self {
+            builder: MapBuilder::memory(),
+            current: None,
+        }
+    }
+
+    /// Terms must be added in lexicographic order, and a term may be added multiple times
+    pub fn add(&mut self, term: &str, rank: u64) -> Result<()> {
+        match &mut self.current {
+            Some((current, best)) if current == term => {
+                *best = (*best).min(rank);
+            }
+            _ => {
+                if let Some((term, rank)) = self.current.take() {
+                    self.builder.insert(term, rank)?;
+                }
+                self.current = Some((term.to_string(), rank));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish_and_encode_to<W: Write>(mut self, writer: &mut W) -> Result<()> {
+        if let Some((term, rank)) = self.current.take() {
+            self.builder.insert(term, rank)?;
+        }
+        let buffer = self.builder.into_inner()?;
+        let index = TermRankIndex {
+            map: Map::new(&buffer)?,
+        };
+        postcard::to_io(&index, &mut *writer)?;
+        Ok(())
+    }
+}
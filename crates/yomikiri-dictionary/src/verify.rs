@@ -0,0 +1,202 @@
+//! Checks that indexes and entries of a dictionary file are consistent.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::dictionary::DictionaryView;
+use crate::entry::Entry;
+
+/// Maximum number of problem descriptions kept in [`VerifyReport`]
+pub const MAX_REPORTED_PROBLEMS: usize = 100;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    pub word_entries: usize,
+    pub name_entries: usize,
+    /// Number of keys in term index
+    pub terms: usize,
+    /// Number of keys in meaning indexes of all languages
+    pub meaning_keys: usize,
+    pub example_sentences: usize,
+    /// Number of problems found
+    pub problem_count: usize,
+    /// Descriptions of the first `MAX_REPORTED_PROBLEMS` problems
+    pub problems: Vec<String>,
+}
+
+impl VerifyReport {
+    /// Returns true if no problem was found
+    pub fn is_ok(&self) -> bool {
+        self.problem_count == 0
+    }
+
+    pub(crate) fn problem(&mut self, description: String) {
+        self.problem_count += 1;
+        if self.problems.len() < MAX_REPORTED_PROBLEMS {
+            self.problems.push(description);
+        }
+    }
+}
+
+impl DictionaryView<'_> {
+    /// Walks every index and decodes every item of dictionary, and reports inconsistencies.
+    ///
+    /// `try_decode` only checks file header, section layout and fsts of indexes,
+    /// so this is used to check a newly built dictionary file before it replaces the old one.
+    pub fn verify(&self) -> VerifyReport {
+        let mut report = VerifyReport::default();
+        if let Err(err) = self.header.verify_checksum(self.content) {
+            report.problem(format!("Content is corrupt: {}", err));
+            return report;
+        }
+        self.verify_entries(&mut report);
+        self.verify_term_index(&mut report);
+        self.verify_meaning_indexes(&mut report);
+        self.radical_index.verify(self, &mut report);
+        self.example_index.verify(&mut report);
+        report
+    }
+
+    fn verify_entries(&self, report: &mut VerifyReport) {
+        report.word_entries = self.entries.len();
+        for (i, entry) in self.entries.all_items_iter().enumerate() {
+            if let Err(err) = entry {
+                report.problem(format!("Word entry {} could not be decoded: {}", i, err));
+            }
+        }
+        report.name_entries = self.name_entries.len();
+        for (i, entry) in self.name_entries.all_items_iter().enumerate() {
+            if let Err(err) = entry {
+                report.problem(format!("Name entry {} could not be decoded: {}", i, err));
+            }
+        }
+    }
+
+    /// Every term must be a kanji or reading of its word entries, or kanji of its name entries,
+    /// and have a rank
+    fn verify_term_index(&self, report: &mut VerifyReport) {
+        self.term_index.for_each_item(|key, idxs| {
+            report.terms += 1;
+            let term = String::from_utf8_lossy(key);
+            if self.term_ranks.get(key).is_none() {
+                report.problem(format!("Term '{}' has no rank", term));
+            }
+            let idxs = match idxs {
+                Ok(idxs) => idxs,
+                Err(err) => {
+                    report.problem(format!("Term '{}' could not be decoded: {}", term, err));
+                    return;
+                }
+            };
+            for idx in &idxs {
+                let contains_term = match self.get_entry(idx) {
+                    Ok(entry) => entry_terms(&entry).contains(&term.as_ref()),
+                    Err(err) => {
+                        report.problem(format!(
+                            "Term '{}' points to {:?} which could not be read: {}",
+                            term, idx, err
+                        ));
+                        continue;
+                    }
+                };
+                if !contains_term {
+                    report.problem(format!(
+                        "Term '{}' points to {:?} which does not contain the term",
+                        term, idx
+                    ));
+                }
+            }
+        });
+        if self.term_ranks.len() != report.terms {
+            report.problem(format!(
+                "Term index has {} terms but {} terms are ranked",
+                report.terms,
+                self.term_ranks.len()
+            ));
+        }
+    }
+
+    /// Every meaning position must be valid, and the meaning must be in the index language
+    fn verify_meaning_indexes(&self, report: &mut VerifyReport) {
+        let languages = self.metadata.languages();
+        if languages.len() != self.meaning_indexes.len() {
+            report.problem(format!(
+                "Dictionary has {} languages but {} meaning indexes",
+                languages.len(),
+                self.meaning_indexes.len()
+            ));
+        }
+        for (lang, index) in languages.iter().zip(&self.meaning_indexes) {
+            index.for_each_item(|key, idxs| {
+                report.meaning_keys += 1;
+                let key = String::from_utf8_lossy(key);
+                let idxs = match idxs {
+                    Ok(idxs) => idxs,
+                    Err(err) => {
+                        report.problem(format!(
+                            "Meaning key '{}' ({}) could not be decoded: {}",
+                            key, lang, err
+                        ));
+                        return;
+                    }
+                };
+                for idx in &idxs {
+                    let meaning_lang = self
+                        .get_word_entry(idx.word_entry_idx())
+                        .and_then(|entry| idx.meaning_in(&entry).map(|m| m.lang().to_string()));
+                    match meaning_lang {
+                        Ok(meaning_lang) if &meaning_lang == lang => {}
+                        Ok(meaning_lang) => report.problem(format!(
+                            "Meaning key '{}' ({}) points to {:?} in another language: {}",
+                            key, lang, idx, meaning_lang
+                        )),
+                        Err(err) => report.problem(format!(
+                            "Meaning key '{}' ({}) points to {:?} which could not be read: {}",
+                            key, lang, idx, err
+                        )),
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Terms that entry is indexed by in term index
+fn entry_terms(entry: &Entry) -> Vec<&str> {
+    match entry {
+        Entry::Word(word) => word
+            .kanjis
+            .iter()
+            .map(|k| k.kanji.as_str())
+            .chain(word.readings.iter().map(|r| r.reading.as_str()))
+            .collect(),
+        Entry::Name(name) => vec![name.kanji.as_str()],
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} word entries, {} name entries, {} terms, {} meaning keys, {} example sentences",
+            self.word_entries,
+            self.name_entries,
+            self.terms,
+            self.meaning_keys,
+            self.example_sentences
+        )?;
+        if self.is_ok() {
+            return write!(f, "No problems found");
+        }
+        write!(f, "{} problems found", self.problem_count)?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+        if self.problem_count > self.problems.len() {
+            write!(f, "\n  ...")?;
+        }
+        Ok(())
+    }
+}
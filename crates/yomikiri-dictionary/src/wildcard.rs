@@ -0,0 +1,199 @@
+//! Search of terms with a pattern of wildcards,
+//! where `?` matches a single character and `*` matches zero or more characters.
+
+use std::cell::Cell;
+
+use crate::automaton::{CharAutomaton, Chars};
+use crate::dictionary::DictionaryView;
+use crate::term_rank::TermSearchItem;
+use crate::Result;
+
+/// Maximum number of key characters read by a wildcard search.
+///
+/// A pattern that starts with `*` can't skip any key of term index,
+/// so search stops after reading this many characters.
+const MAX_SCANNED_CHARS: usize = 1 << 20;
+
+impl DictionaryView<'_> {
+    /// Returns up to `limit` terms that match `pattern` with their entries,
+    /// ranked by priority of term.
+    ///
+    /// `?`, `？`, `〇` or `○` matches a single character, and `*` or `＊` matches zero or more characters.
+    /// Returns empty result if `pattern` has no characters other than wildcards.
+    ///
+    /// If `pattern` starts with `*`, search stops after reading `MAX_SCANNED_CHARS` characters of keys,
+    /// so terms later in key order may be missing from the result.
+    pub fn search_wildcard(&self, pattern: &str, limit: usize) -> Result<Vec<TermSearchItem>> {
+        let pattern = WildcardPattern::new(pattern);
+        if !pattern.has_literal() {
+            return Ok(vec![]);
+        }
+        self.search_terms(Chars(pattern), limit)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternToken {
+    Literal(char),
+    /// `?`, or `〇` placeholder as in 「〇〇力」
+    AnyChar,
+    /// `*`
+    AnyChars,
+}
+
+/// Automaton that matches keys with a wildcard pattern
+pub(crate) struct WildcardPattern {
+    tokens: Vec<PatternToken>,
+    /// Number of key characters read so far
+    scanned: Cell<usize>,
+    max_scanned: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct WildcardState {
+    /// Sorted positions in pattern tokens that key read so far can be at
+    positions: Vec<usize>,
+}
+
+impl WildcardPattern {
+    pub fn new(pattern: &str) -> Self {
+        let mut tokens = vec![];
+        for ch in pattern.chars() {
+            let token = match ch {
+                '?' | '？' | '〇' | '○' => PatternToken::AnyChar,
+                '*' | '＊' => PatternToken::AnyChars,
+                _ => PatternToken::Literal(ch),
+            };
+            // consecutive `*` is the same as a single `*`
+            if token == PatternToken::AnyChars && tokens.last() == Some(&token) {
+                continue;
+            }
+            tokens.push(token);
+        }
+        Self {
+            tokens,
+            scanned: Cell::new(0),
+            max_scanned: MAX_SCANNED_CHARS,
+        }
+    }
+
+    fn has_literal(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, PatternToken::Literal(_)))
+    }
+
+    /// Adds positions that are reached without reading a character, by matching `*` to nothing
+    fn with_skipped(&self, mut positions: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < positions.len() {
+            let position = positions[i];
+            if self.tokens.get(position) == Some(&PatternToken::AnyChars) {
+                positions.push(position + 1);
+            }
+            i += 1;
+        }
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+}
+
+impl CharAutomaton for WildcardPattern {
+    type State = WildcardState;
+
+    fn start(&self) -> WildcardState {
+        WildcardState {
+            positions: self.with_skipped(vec![0]),
+        }
+    }
+
+    fn is_match(&self, state: &WildcardState) -> bool {
+        state.positions.last() == Some(&self.tokens.len())
+    }
+
+    fn can_match(&self, state: &WildcardState) -> bool {
+        !state.positions.is_empty() && self.scanned.get() < self.max_scanned
+    }
+
+    fn will_always_match(&self, state: &WildcardState) -> bool {
+        // pattern ends with `*` that is reached
+        self.is_match(state) && self.tokens.last() == Some(&PatternToken::AnyChars)
+    }
+
+    fn accept_char(&self, state: &WildcardState, ch: char) -> WildcardState {
+        self.scanned.set(self.scanned.get() + 1);
+        let mut next = vec![];
+        for &position in &state.positions {
+            match self.tokens.get(position) {
+                Some(PatternToken::Literal(literal)) if *literal == ch => next.push(position + 1),
+                Some(PatternToken::AnyChar) => next.push(position + 1),
+                Some(PatternToken::AnyChars) => next.push(position),
+                _ => {}
+            }
+        }
+        WildcardState {
+            positions: self.with_skipped(next),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fst::{IntoStreamer, Set, Streamer};
+
+    use super::WildcardPattern;
+    use crate::automaton::Chars;
+
+    fn matches(keys: &[&str], pattern: &str) -> Vec<String> {
+        matches_with(keys, WildcardPattern::new(pattern))
+    }
+
+    fn matches_with(keys: &[&str], pattern: WildcardPattern) -> Vec<String> {
+        let mut keys = keys.to_vec();
+        keys.sort();
+        let set = Set::from_iter(keys).unwrap();
+        let mut stream = set.search(Chars(pattern)).into_stream();
+        let mut matched = vec![];
+        while let Some(key) = stream.next() {
+            matched.push(String::from_utf8(key.to_vec()).unwrap());
+        }
+        matched
+    }
+
+    #[test]
+    fn wildcards_match_characters() {
+        let keys = [
+            "力",
+            "努力",
+            "能力",
+            "原動力",
+            "力士",
+            "食べる",
+            "食べ物",
+            "食る",
+        ];
+        assert_eq!(matches(&keys, "??力"), ["原動力"]);
+        assert_eq!(matches(&keys, "*力"), ["力", "努力", "原動力", "能力"]);
+        assert_eq!(
+            matches(&keys, "＊力*"),
+            ["力", "力士", "努力", "原動力", "能力"]
+        );
+        assert_eq!(matches(&keys, "食?る"), ["食べる"]);
+        assert_eq!(matches(&keys, "食*る"), ["食べる", "食る"]);
+        assert_eq!(matches(&keys, "食べる"), ["食べる"]);
+        assert_eq!(matches(&keys, "食?"), ["食る"]);
+        assert_eq!(matches(&keys, "?"), ["力"]);
+        assert_eq!(matches(&keys, "〇〇力"), ["原動力"]);
+        assert_eq!(matches(&keys, "○力"), ["努力", "能力"]);
+    }
+
+    #[test]
+    fn scan_is_bounded() {
+        let keys = ["努力", "原動力", "能力"];
+        let mut pattern = WildcardPattern::new("*力");
+        pattern.max_scanned = 3;
+        // keys are read in byte order: 努力, 原動力
+        assert_eq!(matches_with(&keys, pattern), ["努力"]);
+    }
+}
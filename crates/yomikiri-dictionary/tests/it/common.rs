@@ -1,9 +1,11 @@
 use fs_err as fs;
+use std::io::Cursor;
 use std::path::PathBuf;
 use yomikiri_dictionary::entry::Entry;
+use yomikiri_dictionary::term_rank::TermSearchItem;
 
 use anyhow::Result;
-use yomikiri_dictionary::dictionary::Dictionary;
+use yomikiri_dictionary::dictionary::{Dictionary, DictionaryWriter, DictionaryWriterFinal};
 use yomikiri_dictionary::DICT_FILENAME;
 
 use std::sync::LazyLock;
@@ -20,6 +22,182 @@ pub fn setup_dictionary() -> Result<Dictionary<Vec<u8>>> {
     Ok(dictionary)
 }
 
+pub const EMPTY_JMNEDICT: &str = "<JMnedict></JMnedict>";
+
+/// JMnedict with a single surname entry 佐藤
+pub const SATOU_JMNEDICT: &str = r#"<JMnedict>
+<entry>
+<ent_seq>5000000</ent_seq>
+<k_ele>
+<keb>佐藤</keb>
+</k_ele>
+<r_ele>
+<reb>さとう</reb>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<trans_det>Satou</trans_det>
+</trans>
+</entry>
+</JMnedict>
+"#;
+
+/// JMdict entries shared by tests. Tests build a dictionary of the entries they need with [`jmdict`].
+pub mod entries {
+    pub const NEKO: &str = r#"<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+</entry>"#;
+
+    /// Has a search-only kanji form 猫じた
+    pub const NEKOJITA: &str = r#"<entry>
+<ent_seq>1467650</ent_seq>
+<k_ele>
+<keb>猫舌</keb>
+</k_ele>
+<k_ele>
+<keb>猫じた</keb>
+<ke_inf>&sK;</ke_inf>
+</k_ele>
+<r_ele>
+<reb>ねこじた</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>being unable to tolerate hot food</gloss>
+</sense>
+</entry>"#;
+
+    pub const NEKOZE: &str = r#"<entry>
+<ent_seq>1467660</ent_seq>
+<k_ele>
+<keb>猫背</keb>
+<ke_pri>ichi1</ke_pri>
+</k_ele>
+<r_ele>
+<reb>ねこぜ</reb>
+<re_pri>ichi1</re_pri>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>hunchback</gloss>
+</sense>
+</entry>"#;
+
+    pub const NAE: &str = r#"<entry>
+<ent_seq>1440400</ent_seq>
+<k_ele>
+<keb>苗</keb>
+<ke_pri>ichi1</ke_pri>
+</k_ele>
+<r_ele>
+<reb>なえ</reb>
+<re_pri>ichi1</re_pri>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>seedling</gloss>
+</sense>
+</entry>"#;
+
+    pub const TABERU: &str = r#"<entry>
+<ent_seq>1358280</ent_seq>
+<k_ele>
+<keb>食べる</keb>
+<ke_pri>ichi1</ke_pri>
+</k_ele>
+<r_ele>
+<reb>たべる</reb>
+<re_pri>ichi1</re_pri>
+</r_ele>
+<sense>
+<pos>&v1;</pos>
+<gloss>to eat</gloss>
+</sense>
+</entry>"#;
+
+    pub const TABEMONO: &str = r#"<entry>
+<ent_seq>1358300</ent_seq>
+<k_ele>
+<keb>食べ物</keb>
+</k_ele>
+<r_ele>
+<reb>たべもの</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>food</gloss>
+</sense>
+</entry>"#;
+
+    pub const DORYOKU: &str = r#"<entry>
+<ent_seq>1454950</ent_seq>
+<k_ele>
+<keb>努力</keb>
+</k_ele>
+<r_ele>
+<reb>どりょく</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>effort</gloss>
+</sense>
+</entry>"#;
+
+    pub const NOURYOKU: &str = r#"<entry>
+<ent_seq>1612590</ent_seq>
+<k_ele>
+<keb>能力</keb>
+<ke_pri>ichi1</ke_pri>
+</k_ele>
+<r_ele>
+<reb>のうりょく</reb>
+<re_pri>ichi1</re_pri>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>ability</gloss>
+</sense>
+</entry>"#;
+}
+
+/// JMdict xml of `entries`
+pub fn jmdict(entries: &[&str]) -> String {
+    format!("<JMdict>\n{}\n</JMdict>\n", entries.join("\n"))
+}
+
+/// Writes dictionary of JMdict `entries` without name entries
+pub fn write_entries(entries: &[&str]) -> Result<Vec<u8>> {
+    write_dictionary(&jmdict(entries), EMPTY_JMNEDICT)
+}
+
+/// Writes dictionary built from `jmdict` and `jmnedict` xml
+pub fn write_dictionary(jmdict: &str, jmnedict: &str) -> Result<Vec<u8>> {
+    let writer = DictionaryWriter::new()
+        .read_jmdict(jmdict.as_bytes())?
+        .read_jmnedict(jmnedict.as_bytes())?;
+    write_to_bytes(writer)
+}
+
+pub fn write_to_bytes(writer: DictionaryWriter<DictionaryWriterFinal>) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    writer.write(&mut Cursor::new(&mut bytes))?;
+    Ok(bytes)
+}
+
+pub fn terms(items: &[TermSearchItem]) -> Vec<&str> {
+    items.iter().map(|item| item.term.as_str()).collect()
+}
+
 pub fn short_entry_info(entry: &Entry) -> String {
     match entry {
         Entry::Word(entry) => format!("(Word {}) {}", entry.id, entry.main_form()),
@@ -0,0 +1,69 @@
+use yomikiri_dictionary::dictionary::DictionaryView;
+use yomikiri_dictionary::header::DictionaryHeader;
+
+use crate::common::entries::{NEKO, NEKOJITA};
+use crate::common::{jmdict, write_dictionary, SATOU_JMNEDICT};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Replaces checksum in header with checksum of content,
+/// so that `verify` checks corrupt content instead of stopping at checksum.
+fn fix_checksum(bytes: &mut [u8]) {
+    if let Ok((header, len)) = DictionaryHeader::try_decode(bytes) {
+        let end = len.saturating_add(header.content_len()).min(bytes.len());
+        let checksum = crc32fast::hash(&bytes[len..end]);
+        bytes[len - 4..len].copy_from_slice(&checksum.to_le_bytes());
+    }
+}
+
+/// Decodes `bytes` and reads everything in it, ignoring errors
+fn decode_and_read(bytes: &[u8]) {
+    let Ok((dict, _)) = DictionaryView::try_decode(bytes) else {
+        return;
+    };
+    let _ = dict.verify();
+    for term in ["猫", "猫舌", "ねこ", "佐藤"] {
+        if let Ok(idxs) = dict.term_index.get(term) {
+            let _ = dict.get_entries(&idxs);
+        }
+    }
+    for entry in dict.entries.all_items_iter().flatten() {
+        let _ = dict.examples_for_entry(&entry);
+    }
+    for _ in dict.name_entries.all_items_iter() {}
+    let _ = dict.search_meaning("cat");
+    let _ = dict.search_meaning("hot food");
+    let _ = dict.search_prefix("猫", 10);
+}
+
+#[test]
+fn corrupt_bytes_do_not_panic() -> Result<()> {
+    let bytes = write_dictionary(&jmdict(&[NEKO, NEKOJITA]), SATOU_JMNEDICT)?;
+    for at in 0..bytes.len() {
+        for mask in [0x01, 0x80, 0xff] {
+            let mut corrupt = bytes.clone();
+            corrupt[at] ^= mask;
+            fix_checksum(&mut corrupt);
+            decode_and_read(&corrupt);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn truncated_bytes_do_not_panic() -> Result<()> {
+    let bytes = write_dictionary(&jmdict(&[NEKO, NEKOJITA]), SATOU_JMNEDICT)?;
+    for len in 0..bytes.len() {
+        let mut truncated = bytes[..len].to_vec();
+        assert!(DictionaryView::try_decode(&truncated).is_err());
+        // header describes truncated content
+        if len >= DictionaryHeader::LEN {
+            let content_len = (len - DictionaryHeader::LEN) as u64;
+            let at = DictionaryHeader::LEN - 12;
+            truncated[at..at + 8].copy_from_slice(&content_len.to_le_bytes());
+            fix_checksum(&mut truncated);
+            decode_and_read(&truncated);
+        }
+    }
+    Ok(())
+}
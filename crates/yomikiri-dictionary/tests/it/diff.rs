@@ -0,0 +1,157 @@
+use yomikiri_dictionary::dictionary::DictionaryView;
+use yomikiri_dictionary::diff::{diff_entries, DiffEntry, DiffField};
+use yomikiri_jmdict::parse_jmdict_xml;
+
+use crate::common::{write_dictionary, EMPTY_JMNEDICT};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const OLD_JMDICT: &str = r#"<JMdict>
+<entry>
+<ent_seq>1000040</ent_seq>
+<k_ele>
+<keb>〃</keb>
+</k_ele>
+<r_ele>
+<reb>おなじ</reb>
+</r_ele>
+<r_ele>
+<reb>おなじく</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>ditto mark</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000050</ent_seq>
+<k_ele>
+<keb>仝</keb>
+</k_ele>
+<r_ele>
+<reb>どうじょう</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>"as above" mark</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+const NEW_JMDICT: &str = r#"<JMdict>
+<entry>
+<ent_seq>1000040</ent_seq>
+<k_ele>
+<keb>〃</keb>
+</k_ele>
+<r_ele>
+<reb>おなじ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<pos>&adj-no;</pos>
+<gloss>ditto mark</gloss>
+<gloss>repetition mark</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+<ke_pri>ichi1</ke_pri>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+<re_pri>ichi1</re_pri>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1467650</ent_seq>
+<k_ele>
+<keb>猫舌</keb>
+</k_ele>
+<r_ele>
+<reb>ねこじた</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>being unable to tolerate hot food</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+fn jmdict_diff_entries(xml: &str) -> Result<Vec<DiffEntry>> {
+    let jmdict = parse_jmdict_xml(xml.as_bytes())?;
+    Ok(jmdict.entries.iter().map(DiffEntry::from).collect())
+}
+
+#[test]
+fn diff_jmdict_xml() -> Result<()> {
+    let diff = diff_entries(
+        jmdict_diff_entries(OLD_JMDICT)?,
+        jmdict_diff_entries(NEW_JMDICT)?,
+    );
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].id, 1467650);
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].id, 1000050);
+    let fields: Vec<(u32, Vec<DiffField>)> = diff
+        .modified
+        .iter()
+        .map(|e| (e.id, e.changes.iter().map(|c| c.field).collect()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (
+                1000040,
+                vec![DiffField::Reading, DiffField::Sense, DiffField::Pos]
+            ),
+            (1467640, vec![DiffField::Priority]),
+        ]
+    );
+    insta::assert_snapshot!(diff.to_string());
+    Ok(())
+}
+
+#[test]
+fn diff_dictionary_files() -> Result<()> {
+    let old_bytes = write_dictionary(OLD_JMDICT, EMPTY_JMNEDICT)?;
+    let new_bytes = write_dictionary(NEW_JMDICT, EMPTY_JMNEDICT)?;
+    let (old, _) = DictionaryView::try_decode(&old_bytes)?;
+    let (new, _) = DictionaryView::try_decode(&new_bytes)?;
+    let diff = old.diff(&new)?;
+    insta::assert_yaml_snapshot!(diff);
+    Ok(())
+}
+
+#[test]
+fn diff_identical() -> Result<()> {
+    let diff = diff_entries(
+        jmdict_diff_entries(OLD_JMDICT)?,
+        jmdict_diff_entries(OLD_JMDICT)?,
+    );
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert!(diff.modified.is_empty());
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use yomikiri_dictionary::dictionary::DictionaryView;
+use yomikiri_dictionary::example::ExampleTranslation;
+use yomikiri_dictionary::WordEntry;
+
+use crate::common::entries::NEKO;
+use crate::common::write_entries;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const KAKERU: &str = r#"<entry>
+<ent_seq>1591270</ent_seq>
+<k_ele>
+<keb>掛ける</keb>
+</k_ele>
+<r_ele>
+<reb>かける</reb>
+</r_ele>
+<sense>
+<pos>&v1;</pos>
+<gloss>to hang up</gloss>
+<example>
+<ex_srce exsrc_type="tat">75470</ex_srce>
+<ex_text>掛けた</ex_text>
+<ex_sent xml:lang="jpn">壁に絵を掛けた。</ex_sent>
+<ex_sent xml:lang="eng">I hung a picture on the wall.</ex_sent>
+</example>
+</sense>
+<sense>
+<pos>&aux-v;</pos>
+<gloss>to be partway doing</gloss>
+<example>
+<ex_srce exsrc_type="tat">144396</ex_srce>
+<ex_text>掛けて</ex_text>
+<ex_sent xml:lang="jpn">彼は死に掛けている。</ex_sent>
+<ex_sent xml:lang="eng">He is dying.</ex_sent>
+</example>
+</sense>
+<sense>
+<pos>&v1;</pos>
+<gloss>to put on (glasses)</gloss>
+<example>
+<ex_srce exsrc_type="tat">75470</ex_srce>
+<ex_text>掛けた</ex_text>
+<ex_sent xml:lang="jpn">壁に絵を掛けた。</ex_sent>
+<ex_sent xml:lang="eng">I hung a picture on the wall.</ex_sent>
+</example>
+</sense>
+</entry>"#;
+
+const E: &str = r#"<entry>
+<ent_seq>1207590</ent_seq>
+<k_ele>
+<keb>絵</keb>
+</k_ele>
+<r_ele>
+<reb>え</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>picture</gloss>
+<example>
+<ex_srce exsrc_type="tat">75470</ex_srce>
+<ex_text>絵</ex_text>
+<ex_sent xml:lang="jpn">壁に絵を掛けた。</ex_sent>
+<ex_sent xml:lang="eng">I hung a picture on the wall.</ex_sent>
+</example>
+</sense>
+</entry>"#;
+
+fn find_entry(dict: &DictionaryView, id: u32) -> Result<WordEntry> {
+    for entry in dict.entries.all_items_iter() {
+        let entry = entry?;
+        if entry.id == id {
+            return Ok(entry);
+        }
+    }
+    Err(format!("Entry {} not found", id).into())
+}
+
+#[test]
+fn examples_linked_to_grouped_senses() -> Result<()> {
+    let bytes = write_entries(&[KAKERU, E, NEKO])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    // same sentence is stored once
+    assert_eq!(dict.example_index.len(), 2);
+
+    let entry = find_entry(&dict, 1591270)?;
+    let examples = dict.examples_for_entry(&entry)?;
+    // third sense is grouped with first sense
+    let linked: Vec<(u16, &str)> = examples
+        .iter()
+        .map(|e| (e.sense_idx, e.example.source_id.as_str()))
+        .collect();
+    assert_eq!(linked, [(0, "75470"), (1, "75470"), (2, "144396")]);
+
+    let example = &examples[0].example;
+    assert_eq!(example.source, "tat");
+    assert_eq!(example.sentence, "壁に絵を掛けた。");
+    assert_eq!(
+        example.translations,
+        [ExampleTranslation {
+            lang: "eng".into(),
+            text: "I hung a picture on the wall.".into()
+        }]
+    );
+
+    let entry = find_entry(&dict, 1207590)?;
+    let examples = dict.examples_for_entry(&entry)?;
+    assert_eq!(examples.len(), 1);
+    assert_eq!(examples[0].example.sentence, "壁に絵を掛けた。");
+    Ok(())
+}
+
+#[test]
+fn entry_without_examples() -> Result<()> {
+    let bytes = write_entries(&[KAKERU, E, NEKO])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    let entry = find_entry(&dict, 1467640)?;
+    assert!(dict.examples_for_entry(&entry)?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use yomikiri_dictionary::dictionary::DictionaryView;
+use yomikiri_dictionary::entry::Entry;
+use yomikiri_dictionary::fuzzy::FuzzySearchItem;
+
+use crate::common::entries::{NAE, NEKO, NEKOJITA, NEKOZE};
+use crate::common::write_entries;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn matched(items: &[FuzzySearchItem]) -> Vec<(&str, u32)> {
+    items
+        .iter()
+        .map(|item| (item.matched.as_str(), item.distance))
+        .collect()
+}
+
+fn entry_id(item: &FuzzySearchItem) -> u32 {
+    let Entry::Word(entry) = &item.entry else {
+        panic!("expected word entry");
+    };
+    entry.id
+}
+
+#[test]
+fn search_term_fuzzy_ranks_by_distance_and_priority() -> Result<()> {
+    let bytes = write_entries(&[NEKO, NEKOJITA, NEKOZE, NAE])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    let result = dict.search_term_fuzzy("ねこぢ", 1)?;
+    // ねこ and ねこぜ are both distance 1, ねこぜ has higher priority
+    assert_eq!(matched(&result), [("ねこぜ", 1), ("ねこ", 1)]);
+    assert_eq!(entry_id(&result[0]), 1467660);
+
+    let result = dict.search_term_fuzzy("ねこぢ", 2)?;
+    assert_eq!(
+        matched(&result),
+        [("ねこぜ", 1), ("ねこ", 1), ("ねこじた", 2)]
+    );
+
+    assert_eq!(matched(&dict.search_term_fuzzy("ねこ", 0)?), [("ねこ", 0)]);
+    // distance is limited for short query
+    assert_eq!(matched(&dict.search_term_fuzzy("ね", 2)?), []);
+    assert!(dict.search_term_fuzzy("", 2)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn search_meaning_fuzzy_matches_each_word() -> Result<()> {
+    let bytes = write_entries(&[NEKO, NEKOJITA, NEKOZE, NAE])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    let result = dict.search_meaning_fuzzy("seedlign", 2)?;
+    assert_eq!(matched(&result), [("seedling", 2)]);
+    assert_eq!(entry_id(&result[0]), 1440400);
+
+    let result = dict.search_meaning_fuzzy("hot fod", 2)?;
+    assert_eq!(matched(&result), [("hot food", 1)]);
+    assert_eq!(entry_id(&result[0]), 1467650);
+
+    // sum of distances of words exceeds max distance
+    assert!(dict.search_meaning_fuzzy("hat fod", 1)?.is_empty());
+    assert!(dict.search_meaning_fuzzy("seedlign", 1)?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::io::Cursor;
+
+use yomikiri_dictionary::dictionary::{Dictionary, DictionaryView, DictionaryWriter};
+use yomikiri_dictionary::header::{DictionaryHeader, Section, MAGIC};
+use yomikiri_dictionary::{Error, SCHEMA_VER};
+
+use crate::common::entries::NEKO;
+use crate::common::{jmdict, write_entries, EMPTY_JMNEDICT};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn header_describes_content() -> Result<()> {
+    let bytes = write_entries(&[NEKO])?;
+    assert_eq!(&bytes[..MAGIC.len()], MAGIC);
+
+    let (header, len) = DictionaryHeader::try_decode(&bytes)?;
+    assert_eq!(len, DictionaryHeader::LEN);
+    assert_eq!(header.schema_ver, SCHEMA_VER);
+    assert_eq!(len + header.content_len(), bytes.len());
+    assert_eq!(header.section(Section::TermIndex).start, 0);
+    assert_eq!(header.section(Section::Metadata).end, header.content_len());
+
+    let (dict, decoded_len) = DictionaryView::try_decode(&bytes)?;
+    assert_eq!(decoded_len, bytes.len());
+    assert_eq!(dict.entries.len(), 1);
+    assert_eq!(dict.metadata.languages(), ["eng"]);
+    Ok(())
+}
+
+#[test]
+fn write_returns_header() -> Result<()> {
+    let writer = DictionaryWriter::new()
+        .read_jmdict(jmdict(&[NEKO]).as_bytes())?
+        .read_jmnedict(EMPTY_JMNEDICT.as_bytes())?;
+    let mut bytes = vec![];
+    let written = writer.write(&mut Cursor::new(&mut bytes))?;
+    let (header, _) = DictionaryHeader::try_decode(&bytes)?;
+    assert_eq!(written, header);
+    Ok(())
+}
+
+#[test]
+fn reject_invalid_magic() -> Result<()> {
+    let mut bytes = write_entries(&[NEKO])?;
+    bytes[0] = b'X';
+    let err = Dictionary::try_decode(bytes).err().unwrap();
+    assert!(matches!(err, Error::InvalidMagic));
+    Ok(())
+}
+
+#[test]
+fn reject_schema_version_mismatch() -> Result<()> {
+    let mut bytes = write_entries(&[NEKO])?;
+    let old_ver = SCHEMA_VER - 1;
+    bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&old_ver.to_le_bytes());
+    let err = Dictionary::try_decode(bytes).err().unwrap();
+    assert!(matches!(
+        err,
+        Error::SchemaVersionMismatch { expected, actual } if expected == SCHEMA_VER && actual == old_ver
+    ));
+    Ok(())
+}
+
+#[test]
+fn reject_truncated_file() -> Result<()> {
+    let bytes = write_entries(&[NEKO])?;
+    let len = bytes.len();
+
+    let err = Dictionary::try_decode(bytes[..len - 1].to_vec())
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        Error::Truncated { expected, actual } if expected == len && actual == len - 1
+    ));
+    let err = Dictionary::try_decode(bytes[..20].to_vec()).err().unwrap();
+    assert!(matches!(err, Error::Truncated { actual: 20, .. }));
+    Ok(())
+}
+
+#[test]
+fn reject_checksum_mismatch() -> Result<()> {
+    let mut bytes = write_entries(&[NEKO])?;
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    let (header, content) = DictionaryHeader::try_decode_with_content(&bytes)?;
+    let err = header.verify_checksum(content).err().unwrap();
+    assert!(matches!(err, Error::ChecksumMismatch { .. }));
+    Ok(())
+}
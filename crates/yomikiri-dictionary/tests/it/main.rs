@@ -1,4 +1,13 @@
 mod common;
+mod corrupt;
+mod diff;
+mod example;
+mod fuzzy;
+mod header;
 mod meaning;
 mod parse;
+mod prefix;
+mod radical;
 mod term;
+mod verify;
+mod wildcard;
@@ -1,7 +1,9 @@
 use insta;
 use itertools::Itertools;
 
-use crate::common::{short_entry_info, DICTIONARY};
+use yomikiri_dictionary::dictionary::DictionaryView;
+
+use crate::common::{short_entry_info, write_entries, DICTIONARY};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -81,3 +83,54 @@ test!(meaning4, "to fly", @r#"
 - (Word 1352320) 上げる
 - (Word 1499100) 舞う
 "#);
+
+const CAT_FOOD_EXPLANATION: &str = r#"<entry>
+<ent_seq>1000010</ent_seq>
+<r_ele>
+<reb>キャットフード</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss g_type="expl">cat food</gloss>
+</sense>
+</entry>"#;
+
+const CAT_FOOD_BOWL: &str = r#"<entry>
+<ent_seq>1000020</ent_seq>
+<r_ele>
+<reb>ねこざら</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat food bowl</gloss>
+</sense>
+</entry>"#;
+
+const CAT_FOOD: &str = r#"<entry>
+<ent_seq>1000030</ent_seq>
+<r_ele>
+<reb>ねこまんま</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat food</gloss>
+</sense>
+</entry>"#;
+
+#[test]
+fn explanation_only_breaks_ties() -> Result<()> {
+    let bytes = write_entries(&[CAT_FOOD_EXPLANATION, CAT_FOOD_BOWL, CAT_FOOD])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    let entries = dict.search_meaning("cat food")?;
+    let infos = entries.iter().map(short_entry_info).collect_vec();
+    // identical translation, then identical explanation, then partial translation
+    assert_eq!(
+        infos,
+        [
+            "(Word 1000030) ねこまんま",
+            "(Word 1000010) キャットフード",
+            "(Word 1000020) ねこざら",
+        ]
+    );
+    Ok(())
+}
@@ -1,23 +1,18 @@
 use itertools::Itertools;
-use yomikiri_dictionary::dictionary::{DictionaryView, DictionaryWriter};
-use yomikiri_dictionary::entry::NameEntry;
+use yomikiri_dictionary::dictionary::{DictionaryView, DictionaryWriter, DictionaryWriterJMDict};
+use yomikiri_dictionary::entry::{Entry, NameEntry};
+use yomikiri_dictionary::spill::SpillLocation;
 use yomikiri_dictionary::WordEntry;
 
+use crate::common::{write_to_bytes, EMPTY_JMNEDICT};
+
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn parse_jmdicts(
     jmdict: Option<&str>,
     jmnedict: Option<&str>,
 ) -> Result<(Vec<WordEntry>, Vec<NameEntry>)> {
-    let writer = DictionaryWriter::new();
-    let jmdict = jmdict.unwrap_or("<JMdict></JMdict>");
-    let jmnedict = jmnedict.unwrap_or("<JMnedict></JMnedict>");
-
-    let writer = writer.read_jmdict(jmdict.as_bytes())?;
-    let writer = writer.read_jmnedict(jmnedict.as_bytes())?;
-    let mut bytes = Vec::with_capacity(128);
-    writer.write(&mut bytes)?;
-
+    let bytes = write_dictionary_with(DictionaryWriter::new(), jmdict, jmnedict)?;
     let (dict, _) = DictionaryView::try_decode(&bytes)?;
     let mut word_entries: Vec<WordEntry> = dict.entries.all_items_iter().try_collect()?;
     let mut name_entries: Vec<NameEntry> = dict.name_entries.all_items_iter().try_collect()?;
@@ -27,6 +22,19 @@ fn parse_jmdicts(
     Ok((word_entries, name_entries))
 }
 
+fn write_dictionary_with(
+    writer: DictionaryWriter<DictionaryWriterJMDict>,
+    jmdict: Option<&str>,
+    jmnedict: Option<&str>,
+) -> Result<Vec<u8>> {
+    let jmdict = jmdict.unwrap_or("<JMdict></JMdict>");
+    let jmnedict = jmnedict.unwrap_or(EMPTY_JMNEDICT);
+
+    let writer = writer.read_jmdict(jmdict.as_bytes())?;
+    let writer = writer.read_jmnedict(jmnedict.as_bytes())?;
+    Ok(write_to_bytes(writer)?)
+}
+
 #[test]
 fn test_jmdict_parse_1() -> Result<()> {
     let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -275,3 +283,564 @@ fn test_jmnedict_parse_multiple() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn resolve_sense_references() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1352320</ent_seq>
+<k_ele>
+<keb>上げる</keb>
+</k_ele>
+<r_ele>
+<reb>あげる</reb>
+</r_ele>
+<sense>
+<pos>&v1;</pos>
+<xref>上がる・あがる・1</xref>
+<xref>揚げる</xref>
+<ant>下げる・さげる</ant>
+<gloss>to raise</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1352240</ent_seq>
+<k_ele>
+<keb>上がる</keb>
+</k_ele>
+<r_ele>
+<reb>あがる</reb>
+</r_ele>
+<sense>
+<pos>&v5r;</pos>
+<ant>下がる</ant>
+<gloss>to rise</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1297080</ent_seq>
+<k_ele>
+<keb>下げる</keb>
+</k_ele>
+<r_ele>
+<reb>さげる</reb>
+</r_ele>
+<sense>
+<pos>&v1;</pos>
+<ant>上げる・あげる</ant>
+<gloss>to lower</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let result = parse_jmdicts(Some(xml), None)?;
+    insta::assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn reference_sense_is_grouped_sense_idx() -> Result<()> {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1000010</ent_seq>
+<r_ele>
+<reb>あれ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>that</gloss>
+</sense>
+<sense>
+<pos>&int;</pos>
+<gloss>hey</gloss>
+</sense>
+<sense>
+<pos>&n;</pos>
+<gloss>that thing</gloss>
+</sense>
+<sense>
+<gloss xml:lang="ger">das</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000020</ent_seq>
+<r_ele>
+<reb>これ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<xref>あれ・3</xref>
+<xref>あれ・4</xref>
+<xref>あれ・5</xref>
+<gloss>this</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let (entries, _) = parse_jmdicts(Some(xml), None)?;
+    let senses = &entries[0].grouped_senses;
+    assert_eq!(senses[0].senses[1].meanings[0].text, "that thing");
+
+    let references = &entries[1].grouped_senses[0].senses[0].references;
+    let resolved: Vec<_> = references
+        .iter()
+        .map(|r| (r.entry_idx.is_some(), r.sense))
+        .collect();
+    // 3rd sense is grouped with 1st sense, 4th sense has no English gloss,
+    // and 5th sense does not exist
+    assert_eq!(resolved, [(true, Some(1)), (true, None), (false, None)]);
+    Ok(())
+}
+
+const MULTILINGUAL_JMDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<JMdict>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+<sense>
+<gloss xml:lang="dut">kat</gloss>
+</sense>
+<sense>
+<gloss xml:lang="ger">Katze</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele>
+<reb>ヽ</reb>
+</r_ele>
+<sense>
+<pos>&unc;</pos>
+<gloss xml:lang="dut">herhalingsteken</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+#[test]
+fn multilingual_jmdict_english() -> Result<()> {
+    let result = parse_jmdicts(Some(MULTILINGUAL_JMDICT), None)?;
+    insta::assert_yaml_snapshot!(result);
+    Ok(())
+}
+
+#[test]
+fn multilingual_jmdict_languages() -> Result<()> {
+    let writer = DictionaryWriter::with_languages(vec!["ger".into(), "eng".into()]);
+    let bytes = write_dictionary_with(writer, Some(MULTILINGUAL_JMDICT), None)?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    assert_eq!(dict.metadata.languages(), ["ger", "eng"]);
+    let entries: Vec<WordEntry> = dict.entries.all_items_iter().try_collect()?;
+    insta::assert_yaml_snapshot!(entries);
+
+    assert_eq!(dict.search_meaning("katze")?.len(), 1);
+    assert_eq!(dict.search_meaning("cat")?.len(), 0);
+    assert_eq!(dict.search_meaning_in_language("cat", "eng")?.len(), 1);
+    assert_eq!(dict.search_meaning_in_language("kat", "dut")?.len(), 0);
+    Ok(())
+}
+
+#[test]
+fn jmnedict_word_entries_need_english() -> Result<()> {
+    let jmnedict = r#"<JMnedict>
+<entry>
+<ent_seq>5000268</ent_seq>
+<k_ele>
+<keb>あく屋</keb>
+</k_ele>
+<r_ele>
+<reb>あくや</reb>
+</r_ele>
+<trans>
+<name_type>&place;</name_type>
+<trans_det>Akuya</trans_det>
+</trans>
+</entry>
+<entry>
+<ent_seq>5538230</ent_seq>
+<k_ele>
+<keb>鏑木</keb>
+</k_ele>
+<r_ele>
+<reb>かぶらぎ</reb>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<trans_det>Kaburagi</trans_det>
+</trans>
+</entry>
+</JMnedict>
+"#;
+    let writer = DictionaryWriter::with_languages(vec!["ger".into()]);
+    let bytes = write_dictionary_with(writer, None, Some(jmnedict))?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    assert_eq!(dict.entries.len(), 0);
+    assert_eq!(dict.name_entries.len(), 1);
+
+    let writer = DictionaryWriter::with_languages(vec!["ger".into(), "eng".into()]);
+    let bytes = write_dictionary_with(writer, None, Some(jmnedict))?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    assert_eq!(dict.entries.len(), 1);
+    let entry = dict.entries.get(0)?;
+    assert_eq!(entry.grouped_senses[0].senses[0].meanings[0].lang(), "eng");
+    Ok(())
+}
+
+#[test]
+fn english_senses_keep_unspecified_pos() -> Result<()> {
+    let xml = r#"<JMdict>
+<entry>
+<ent_seq>1467640</ent_seq>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+<sense>
+<gloss>shamisen</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let (entries, _) = parse_jmdicts(Some(xml), None)?;
+    insta::assert_yaml_snapshot!(entries);
+    Ok(())
+}
+
+#[test]
+fn jmdict_parse_error_position() {
+    let jmdict = r#"<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele>
+<reb>ヽ</reb>
+</r_ele>
+<sense>
+<gloss>repetition mark in katakana
+</entry>
+</JMdict>
+"#;
+    let Err(err) = DictionaryWriter::new().read_jmdict(jmdict.as_bytes()) else {
+        panic!("Expected parse error");
+    };
+    let position = err.xml_position().unwrap();
+    assert_eq!(position.ent_seq, Some(1000000));
+    assert_eq!(position.path, "entry/sense/gloss");
+    assert_eq!(position.line, 9);
+}
+
+#[test]
+fn name_items_sorted_by_priority() -> Result<()> {
+    let jmnedict = r#"<JMnedict>
+<entry>
+<ent_seq>5000001</ent_seq>
+<k_ele>
+<keb>佐藤</keb>
+</k_ele>
+<r_ele>
+<reb>さと</reb>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<trans_det>Sato</trans_det>
+</trans>
+</entry>
+<entry>
+<ent_seq>5000002</ent_seq>
+<k_ele>
+<keb>佐藤</keb>
+<ke_pri>spec1</ke_pri>
+</k_ele>
+<r_ele>
+<reb>さとう</reb>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<trans_det>Satou</trans_det>
+</trans>
+</entry>
+</JMnedict>
+"#;
+    let (_, name_entries) = parse_jmdicts(None, Some(jmnedict))?;
+    let entry = &name_entries[0];
+    assert_eq!(entry.priority(), 100);
+    let readings: Vec<&str> = entry.groups[0]
+        .items
+        .iter()
+        .map(|item| item.reading.as_str())
+        .collect();
+    assert_eq!(readings, ["さとう", "さと"]);
+    Ok(())
+}
+
+#[test]
+fn read_patched_jmdict() -> Result<()> {
+    let xml = r#"<JMdict>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let mut jmdict = yomikiri_jmdict::parse_jmdict_xml(xml.as_bytes())?;
+    jmdict.entries[0].senses[0].meanings[0].text = "cat (animal)".into();
+    let patched = yomikiri_jmdict::jmdict::write_jmdict_xml(&jmdict, vec![])?;
+
+    let (entries, _) = parse_jmdicts(Some(std::str::from_utf8(&patched)?), None)?;
+    assert_eq!(
+        entries[0].grouped_senses[0].senses[0].meanings[0].text,
+        "cat (animal)"
+    );
+    Ok(())
+}
+
+#[test]
+fn apply_jmdict_patch() -> Result<()> {
+    let xml = r#"<JMdict>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<xref>読み切り</xref>
+<gloss>cat</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1467650</ent_seq>
+<k_ele>
+<keb>犬</keb>
+</k_ele>
+<r_ele>
+<reb>いぬ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>dog</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let patch = r#"<JMdict_patch>
+<add>
+<entry>
+<ent_seq>2900001</ent_seq>
+<k_ele>
+<keb>読み切り</keb>
+</k_ele>
+<r_ele>
+<reb>よみきり</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>reading to the end</gloss>
+</sense>
+</entry>
+</add>
+<modify>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<xref>読み切り</xref>
+<gloss>cat</gloss>
+</sense>
+<sense>
+<gloss>shamisen</gloss>
+</sense>
+</entry>
+</modify>
+<delete><ent_seq>1467650</ent_seq></delete>
+<delete><ent_seq>1000000</ent_seq></delete>
+</JMdict_patch>
+"#;
+    let writer = DictionaryWriter::new().read_jmdict(xml.as_bytes())?;
+    let writer = writer.apply_jmdict_patch(patch.as_bytes())?;
+    let writer = writer.read_jmnedict(EMPTY_JMNEDICT.as_bytes())?;
+    assert_eq!(
+        writer.warnings(),
+        ["Patch deletes entry that does not exist: 1000000"]
+    );
+    let bytes = write_to_bytes(writer)?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    let mut entries: Vec<WordEntry> = dict.entries.all_items_iter().try_collect()?;
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let ids = entries.iter().map(|e| e.id).collect::<Vec<_>>();
+    assert_eq!(ids, [1467640, 2900001]);
+    let senses = &entries[0].grouped_senses;
+    assert_eq!(senses.iter().map(|g| g.senses.len()).sum::<usize>(), 2);
+    let reference = &senses[0].senses[0].references[0];
+    let referenced = dict.get_referenced_entry(reference)?.unwrap();
+    assert_eq!(referenced.id, 2900001);
+    Ok(())
+}
+
+#[test]
+fn build_is_deterministic() -> Result<()> {
+    let jmdict = r#"<JMdict>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>cat</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1599200</ent_seq>
+<k_ele>
+<keb>佐藤</keb>
+</k_ele>
+<r_ele>
+<reb>さとう</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>Satou (surname)</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let mut jmnedict = String::from("<JMnedict>\n");
+    for (i, (kanji, reading)) in [
+        ("佐藤", "さとう"),
+        ("鈴木", "すずき"),
+        ("高橋", "たかはし"),
+        ("田中", "たなか"),
+        ("伊藤", "いとう"),
+        ("渡辺", "わたなべ"),
+        ("山本", "やまもと"),
+        ("中村", "なかむら"),
+        ("佐藤", "さと"),
+    ]
+    .iter()
+    .enumerate()
+    {
+        jmnedict.push_str(&format!(
+            "<entry>\n<ent_seq>{}</ent_seq>\n<k_ele>\n<keb>{}</keb>\n</k_ele>\n<r_ele>\n<reb>{}</reb>\n</r_ele>\n<trans>\n<name_type>&surname;</name_type>\n<trans_det>name</trans_det>\n</trans>\n</entry>\n",
+            5000000 + i,
+            kanji,
+            reading
+        ));
+    }
+    jmnedict.push_str("</JMnedict>\n");
+
+    let first = write_dictionary_with(DictionaryWriter::new(), Some(jmdict), Some(&jmnedict))?;
+    for _ in 0..5 {
+        let bytes = write_dictionary_with(DictionaryWriter::new(), Some(jmdict), Some(&jmnedict))?;
+        assert!(
+            first == bytes,
+            "dictionary files built from same input differ"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn spill_to_directory() -> Result<()> {
+    let jmdict = r#"<JMdict>
+<entry>
+<ent_seq>1467640</ent_seq>
+<k_ele>
+<keb>猫</keb>
+</k_ele>
+<r_ele>
+<reb>ねこ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<xref>犬</xref>
+<gloss>cat</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1501390</ent_seq>
+<k_ele>
+<keb>犬</keb>
+</k_ele>
+<r_ele>
+<reb>いぬ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>dog</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+    let jmnedict = r#"<JMnedict>
+<entry>
+<ent_seq>5000000</ent_seq>
+<k_ele>
+<keb>佐藤</keb>
+</k_ele>
+<r_ele>
+<reb>さとう</reb>
+</r_ele>
+<trans>
+<name_type>&surname;</name_type>
+<trans_det>Satou</trans_det>
+</trans>
+</entry>
+</JMnedict>
+"#;
+    let dir = std::env::temp_dir().join(format!("yomikiri-spill-test-{}", std::process::id()));
+    fs_err::create_dir_all(&dir)?;
+
+    let in_memory = write_dictionary_with(DictionaryWriter::new(), Some(jmdict), Some(jmnedict))?;
+    let writer = DictionaryWriter::new().spill_to(SpillLocation::Directory(dir.clone()));
+    let spilled = write_dictionary_with(writer, Some(jmdict), Some(jmnedict))?;
+    let leftover = fs_err::read_dir(&dir)?.count();
+    fs_err::remove_dir_all(&dir)?;
+
+    assert!(in_memory == spilled, "spilled dictionary file differs");
+    assert_eq!(leftover, 0, "spill files are not removed");
+    let (dict, _) = DictionaryView::try_decode(&spilled)?;
+    let cat = match dict.get_entry(&dict.term_index.get("猫")?[0])? {
+        Entry::Word(entry) => entry,
+        Entry::Name(_) => panic!("expected word entry"),
+    };
+    let reference = &cat.grouped_senses[0].senses[0].references[0];
+    assert_eq!(dict.get_referenced_entry(reference)?.unwrap().id, 1501390);
+    Ok(())
+}
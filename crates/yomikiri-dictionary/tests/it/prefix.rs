@@ -0,0 +1,38 @@
+use yomikiri_dictionary::dictionary::DictionaryView;
+use yomikiri_dictionary::entry::Entry;
+
+use crate::common::entries::{NEKO, NEKOJITA, NEKOZE};
+use crate::common::{terms, write_entries};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn search_prefix_ranks_by_priority() -> Result<()> {
+    let bytes = write_entries(&[NEKO, NEKOJITA, NEKOZE])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    let result = dict.search_prefix("猫", 10)?;
+    // search-only form is ranked last
+    assert_eq!(terms(&result), ["猫背", "猫", "猫舌", "猫じた"]);
+    let Entry::Word(entry) = &result[0].entries[0] else {
+        panic!("expected word entry");
+    };
+    assert_eq!(entry.id, 1467660);
+
+    let result = dict.search_prefix("ねこ", 10)?;
+    assert_eq!(terms(&result), ["ねこぜ", "ねこ", "ねこじた"]);
+    Ok(())
+}
+
+#[test]
+fn search_prefix_limit() -> Result<()> {
+    let bytes = write_entries(&[NEKO, NEKOJITA, NEKOZE])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    let result = dict.search_prefix("猫", 2)?;
+    assert_eq!(terms(&result), ["猫背", "猫"]);
+    assert!(dict.search_prefix("猫", 0)?.is_empty());
+    assert!(dict.search_prefix("", 10)?.is_empty());
+    assert!(dict.search_prefix("犬", 10)?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use yomikiri_dictionary::dictionary::{DictionaryView, DictionaryWriter};
+
+use crate::common::entries::{NAE, NEKO, NEKOJITA};
+use crate::common::{jmdict, write_to_bytes, EMPTY_JMNEDICT};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const RADKFILE: &str = "\
+$ 田 5
+苗猫畑
+$ 艾 3 js02
+苗猫
+$ 犯 2 js03
+猫
+";
+
+fn write_dictionary_with_radicals() -> Result<Vec<u8>> {
+    let writer = DictionaryWriter::new()
+        .read_jmdict(jmdict(&[NEKO, NEKOJITA, NAE]).as_bytes())?
+        .read_jmnedict(EMPTY_JMNEDICT.as_bytes())?
+        .read_radkfile(RADKFILE.as_bytes())?;
+    Ok(write_to_bytes(writer)?)
+}
+
+fn entry_terms(entries: &[yomikiri_dictionary::WordEntry]) -> Vec<&str> {
+    entries.iter().map(|e| e.kanjis[0].kanji.as_str()).collect()
+}
+
+#[test]
+fn search_single_radical() -> Result<()> {
+    let bytes = write_dictionary_with_radicals()?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    let result = dict.search_radicals(&["艾"])?;
+    assert_eq!(result.kanjis, ["苗", "猫"]);
+    // higher priority entry first
+    assert_eq!(entry_terms(&result.entries), ["苗", "猫", "猫舌"]);
+    Ok(())
+}
+
+#[test]
+fn search_multiple_radicals() -> Result<()> {
+    let bytes = write_dictionary_with_radicals()?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    let result = dict.search_radicals(&["田", "犯"])?;
+    assert_eq!(result.kanjis, ["猫"]);
+    assert_eq!(entry_terms(&result.entries), ["猫", "猫舌"]);
+    Ok(())
+}
+
+#[test]
+fn search_unknown_or_no_radical() -> Result<()> {
+    let bytes = write_dictionary_with_radicals()?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    assert!(dict.search_radicals(&["田", "木"])?.kanjis.is_empty());
+    assert!(dict.search_radicals::<&str>(&[])?.kanjis.is_empty());
+    Ok(())
+}
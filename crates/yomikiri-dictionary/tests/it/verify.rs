@@ -0,0 +1,63 @@
+use yomikiri_dictionary::dictionary::DictionaryView;
+use yomikiri_dictionary::header::{DictionaryHeader, Section};
+
+use crate::common::entries::NEKO;
+use crate::common::{jmdict, write_dictionary, SATOU_JMNEDICT};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const DOG: &str = r#"<entry>
+<ent_seq>1467650</ent_seq>
+<k_ele>
+<keb>犬</keb>
+</k_ele>
+<r_ele>
+<reb>いぬ</reb>
+</r_ele>
+<sense>
+<pos>&n;</pos>
+<gloss>dog</gloss>
+</sense>
+</entry>"#;
+
+fn write_entries_with_names(entries: &[&str]) -> Result<Vec<u8>> {
+    Ok(write_dictionary(&jmdict(entries), SATOU_JMNEDICT)?)
+}
+
+#[test]
+fn verify_valid_dictionary() -> Result<()> {
+    let bytes = write_entries_with_names(&[NEKO, DOG])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    let report = dict.verify();
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(report.word_entries, 2);
+    assert_eq!(report.name_entries, 1);
+    // 猫, ねこ, 犬, いぬ, 佐藤
+    assert_eq!(report.terms, 5);
+    assert_eq!(report.meaning_keys, 2);
+    Ok(())
+}
+
+#[test]
+fn verify_reports_inconsistent_index() -> Result<()> {
+    let mut bytes = write_entries_with_names(&[NEKO, DOG])?;
+    let swapped = write_entries_with_names(&[DOG, NEKO])?;
+
+    // replace word entries with the same entries in another order
+    let (header, len) = DictionaryHeader::try_decode(&bytes)?;
+    let (swapped_header, _) = DictionaryHeader::try_decode(&swapped)?;
+    let range = header.section(Section::Entries);
+    let swapped_range = swapped_header.section(Section::Entries);
+    bytes[len + range.start..len + range.end]
+        .copy_from_slice(&swapped[len + swapped_range.start..len + swapped_range.end]);
+    let checksum = crc32fast::hash(&bytes[len..]);
+    bytes[len - 4..len].copy_from_slice(&checksum.to_le_bytes());
+
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+    let report = dict.verify();
+    assert!(!report.is_ok());
+    // 猫, ねこ, 犬, いぬ
+    assert_eq!(report.problem_count, 4);
+    assert!(report.problems[0].contains("does not contain the term"));
+    Ok(())
+}
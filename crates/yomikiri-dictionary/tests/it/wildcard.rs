@@ -0,0 +1,40 @@
+use yomikiri_dictionary::dictionary::DictionaryView;
+
+use crate::common::entries::{DORYOKU, NOURYOKU, TABEMONO, TABERU};
+use crate::common::{terms, write_entries};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn search_wildcard_ranks_by_priority() -> Result<()> {
+    let bytes = write_entries(&[TABERU, TABEMONO, DORYOKU, NOURYOKU])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    assert_eq!(terms(&dict.search_wildcard("?力", 10)?), ["能力", "努力"]);
+    assert_eq!(terms(&dict.search_wildcard("〇力", 10)?), ["能力", "努力"]);
+    assert_eq!(terms(&dict.search_wildcard("食?る", 10)?), ["食べる"]);
+    assert_eq!(
+        terms(&dict.search_wildcard("＊べ*", 10)?),
+        ["たべる", "食べる", "食べ物", "たべもの"]
+    );
+    assert_eq!(
+        terms(&dict.search_wildcard("*りょく", 10)?),
+        ["のうりょく", "どりょく"]
+    );
+    Ok(())
+}
+
+#[test]
+fn search_wildcard_limit() -> Result<()> {
+    let bytes = write_entries(&[TABERU, TABEMONO, DORYOKU, NOURYOKU])?;
+    let (dict, _) = DictionaryView::try_decode(&bytes)?;
+
+    assert_eq!(terms(&dict.search_wildcard("*力", 1)?), ["能力"]);
+    assert!(dict.search_wildcard("*力", 0)?.is_empty());
+    // pattern without literal characters would match every term
+    assert!(dict.search_wildcard("*", 10)?.is_empty());
+    assert!(dict.search_wildcard("??", 10)?.is_empty());
+    assert!(dict.search_wildcard("〇〇", 10)?.is_empty());
+    assert!(dict.search_wildcard("", 10)?.is_empty());
+    Ok(())
+}
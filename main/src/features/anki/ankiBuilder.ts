@@ -248,9 +248,9 @@ addBuilder("meaning", (opts, data) => {
       for (const sense of group.senses) {
         let items;
         if (opts.full_max_item > 0) {
-          items = sense.meanings.slice(0, opts.full_max_item);
+          items = sense.meanings.slice(0, opts.full_max_item).map((m) => m.text);
         } else {
-          items = sense.meanings;
+          items = sense.meanings.map((m) => m.text);
         }
         if (format === "yomichan") {
           for (const item of items) {
@@ -323,9 +323,9 @@ addBuilder("meaning", (opts, data) => {
 
     let items: string[];
     if (opts.single_max_item > 0) {
-      items = sense.meanings.slice(0, opts.single_max_item);
+      items = sense.meanings.slice(0, opts.single_max_item).map((m) => m.text);
     } else {
-      items = sense.meanings;
+      items = sense.meanings.map((m) => m.text);
     }
 
     line += escapeHTML(items.join(", "));
@@ -85,6 +85,7 @@ const tokenized: TokenizeResult = {
           {
             kanji: "面白い",
             rarity: "normal",
+            priority: ["ichi1", "news1", { nf: 19 }],
           },
         ],
         readings: [
@@ -92,6 +93,7 @@ const tokenized: TokenizeResult = {
             reading: "おもしろい",
             nokanji: false,
             rarity: "normal",
+            priority: ["ichi1", "news1", { nf: 19 }],
             toKanji: [],
           },
         ],
@@ -102,50 +104,70 @@ const tokenized: TokenizeResult = {
               {
                 toKanji: [],
                 toReading: [],
+                references: [],
+                antonyms: [],
+                fields: [],
                 misc: [],
                 info: [],
+                languageSources: [],
                 dialects: [],
                 meanings: [
-                  "interesting",
-                  "fascinating",
-                  "intriguing",
-                  "enthralling",
+                  { text: "interesting", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "fascinating", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "intriguing", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "enthralling", lang: "eng", glossType: null, gender: null, highlighted: false },
                 ],
               },
               {
                 toKanji: [],
                 toReading: [],
+                references: [],
+                antonyms: [],
+                fields: [],
                 misc: [],
                 info: [],
+                languageSources: [],
                 dialects: [],
-                meanings: ["amusing", "funny", "comical"],
+                meanings: [
+                  { text: "amusing", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "funny", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "comical", lang: "eng", glossType: null, gender: null, highlighted: false },
+                ],
               },
               {
                 toKanji: [],
                 toReading: [],
+                references: [],
+                antonyms: [],
+                fields: [],
                 misc: [],
                 info: [],
+                languageSources: [],
                 dialects: [],
                 meanings: [
-                  "enjoyable",
-                  "fun",
-                  "entertaining",
-                  "pleasant",
-                  "agreeable",
+                  { text: "enjoyable", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "fun", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "entertaining", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "pleasant", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "agreeable", lang: "eng", glossType: null, gender: null, highlighted: false },
                 ],
               },
               {
                 toKanji: [],
                 toReading: [],
+                references: [],
+                antonyms: [],
+                fields: [],
                 misc: [],
                 info: ["usu. in the negative"],
+                languageSources: [],
                 dialects: [],
                 meanings: [
-                  "good",
-                  "satisfactory",
-                  "favourable",
-                  "desirable",
-                  "encouraging",
+                  { text: "good", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "satisfactory", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "favourable", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "desirable", lang: "eng", glossType: null, gender: null, highlighted: false },
+                  { text: "encouraging", lang: "eng", glossType: null, gender: null, highlighted: false },
                 ],
               },
             ],
@@ -21,5 +21,7 @@ export function emptyTokenizeResult(): TokenizeResult {
     tokens: [],
     entries: [],
     grammars: [],
+    suggestions: [],
+    romaji: null,
   };
 }